@@ -18,9 +18,327 @@ fn def_split_env(
     }
 }
 
+/// Comma-split every value of a list flag so `--flag a,b` behaves
+/// like repeated flags, matching the env var handling.
+fn split_list(args: &mut minimist::Minimist, key: &str) {
+    if let Some(vals) = args.get_mut(key) {
+        *vals = vals
+            .iter()
+            .flat_map(|v| {
+                v.to_string_lossy()
+                    .split(',')
+                    .map(std::ffi::OsString::from)
+                    .collect::<Vec<_>>()
+            })
+            .filter(|v| !v.is_empty())
+            .collect();
+    }
+}
+
+/// Collect a list flag's values, treating a missing flag as empty.
+fn list_str(args: &minimist::Minimist, key: &str) -> Vec<Arc<str>> {
+    args.to_list_str(key)
+        .map(|l| l.map(|s| s.into()).collect())
+        .unwrap_or_default()
+}
+
+/// Parse a numeric flag value, producing an argument error naming the
+/// flag on malformed input or a value below `min` (which also rejects
+/// NaN for float flags).
+fn parse_num<T>(flag: &str, raw: &str, min: T) -> Result<T>
+where
+    T: std::str::FromStr + PartialOrd + std::fmt::Display,
+{
+    let val: T = raw.parse().map_err(|_| {
+        Error::invalid(format!(
+            "Argument Error: --{flag} expects a number, got: '{raw}'"
+        ))
+    })?;
+    if !matches!(
+        val.partial_cmp(&min),
+        Some(std::cmp::Ordering::Greater | std::cmp::Ordering::Equal),
+    ) {
+        return Err(Error::invalid(format!(
+            "Argument Error: --{flag} must be at least {min}, got: {raw}"
+        )));
+    }
+    Ok(val)
+}
+
+/// The flags each subcommand recognizes, used to reject typos instead
+/// of silently ignoring them.
+struct CmdSpec {
+    cmd: &'static str,
+    flags: &'static [&'static str],
+}
+
+/// Flags accepted by every subcommand.
+const GLOBAL_FLAGS: &[&str] =
+    &["h", "help", "v", "version", "log-format", "access-log-format"];
+
+const CMD_SPECS: &[CmdSpec] = &[
+    CmdSpec { cmd: "help", flags: &[] },
+    CmdSpec { cmd: "version", flags: &[] },
+    CmdSpec {
+        cmd: "serve",
+        flags: &[
+            "config",
+            "sys-admin",
+            "sys-admin-file",
+            "http-addr",
+            "store",
+            "store-cold",
+            "request-timeout-secs",
+            "rate-limit-rps",
+            "read-only",
+            "access-log-health",
+        ],
+    },
+    CmdSpec {
+        cmd: "test",
+        flags: &["http-addr", "code-file", "code-env"],
+    },
+    CmdSpec { cmd: "health", flags: &["url"] },
+    CmdSpec {
+        cmd: "ctx-setup",
+        flags: &[
+            "url",
+            "token",
+            "context",
+            "delete",
+            "ctx-admin",
+            "ctx-admin-file",
+            "timeout-secs",
+            "max-heap-bytes",
+            "max-fn-request-bytes",
+            "max-code-bytes",
+            "warm-threads",
+        ],
+    },
+    CmdSpec {
+        cmd: "ctx-config",
+        flags: &[
+            "url",
+            "token",
+            "context",
+            "ctx-admin",
+            "code-file",
+            "code-dir",
+            "code-entry",
+            "code-env",
+            "env-file",
+            "no-validate",
+        ],
+    },
+    CmdSpec {
+        cmd: "ctx",
+        flags: &[
+            "url",
+            "token",
+            "context",
+            "delete",
+            "ctx-admin",
+            "timeout-secs",
+            "max-heap-bytes",
+            "code-file",
+            "code-env",
+        ],
+    },
+    CmdSpec {
+        cmd: "ctx-versions",
+        flags: &["url", "token", "context"],
+    },
+    CmdSpec {
+        cmd: "ctx-rollback",
+        flags: &["url", "token", "context", "version"],
+    },
+    CmdSpec {
+        cmd: "ctx-clone",
+        flags: &["url", "token", "src", "dst"],
+    },
+    CmdSpec { cmd: "ctx-list", flags: &["url", "token"] },
+    CmdSpec {
+        cmd: "domain-set",
+        flags: &["url", "token", "host", "context"],
+    },
+    CmdSpec { cmd: "domain-list", flags: &["url", "token"] },
+    CmdSpec {
+        cmd: "ctx-admin-add",
+        flags: &["url", "token", "context", "admin"],
+    },
+    CmdSpec {
+        cmd: "ctx-admin-remove",
+        flags: &["url", "token", "context", "admin"],
+    },
+    CmdSpec {
+        cmd: "ctx-tokens",
+        flags: &["url", "token", "context", "add", "remove"],
+    },
+    CmdSpec {
+        cmd: "ctx-revalidate",
+        flags: &["url", "token", "context", "prefix", "limit", "delete-failures"],
+    },
+    CmdSpec {
+        cmd: "bench",
+        flags: &["url", "token", "context", "concurrency", "requests"],
+    },
+    CmdSpec {
+        cmd: "obj-list",
+        flags: &[
+            "url",
+            "token",
+            "context",
+            "prefix",
+            "created-gt",
+            "created-lt",
+            "desc",
+            "limit",
+            "state-file",
+            "detail",
+            "output",
+        ],
+    },
+    CmdSpec {
+        cmd: "obj-get",
+        flags: &["url", "token", "context", "app-path", "format"],
+    },
+    CmdSpec {
+        cmd: "obj-put",
+        flags: &[
+            "url",
+            "token",
+            "context",
+            "app-path",
+            "create",
+            "expire",
+            "format",
+            "content-type",
+        ],
+    },
+    CmdSpec {
+        cmd: "obj-sign",
+        flags: &["url", "token", "context", "app-path", "ttl-secs"],
+    },
+    CmdSpec { cmd: "fsck", flags: &["store", "repair"] },
+    CmdSpec { cmd: "obj-backup-full", flags: &["url", "token"] },
+    CmdSpec { cmd: "obj-restore-full", flags: &["url", "token"] },
+    CmdSpec {
+        cmd: "obj-backup",
+        flags: &["url", "token", "context", "created-gt", "zip-file"],
+    },
+    CmdSpec {
+        cmd: "obj-restore",
+        flags: &["url", "token", "context", "zip-file"],
+    },
+];
+
+/// Edit distance for "did you mean" suggestions.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    let mut cur = vec![0; b.len() + 1];
+    for (i, ca) in a.chars().enumerate() {
+        cur[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let sub = prev[j] + usize::from(ca != *cb);
+            cur[j + 1] = sub.min(prev[j + 1] + 1).min(cur[j] + 1);
+        }
+        std::mem::swap(&mut prev, &mut cur);
+    }
+    prev[b.len()]
+}
+
+/// Pick the closest known name within a small edit distance.
+fn did_you_mean<'a>(
+    unknown: &str,
+    known: impl IntoIterator<Item = &'a str>,
+) -> Option<&'a str> {
+    known
+        .into_iter()
+        .map(|k| (edit_distance(unknown, k), k))
+        .filter(|(d, _)| *d <= 2)
+        .min_by_key(|(d, _)| *d)
+        .map(|(_, k)| k)
+}
+
+/// Reject flags the subcommand doesn't recognize. Must run before
+/// defaults are layered in, so only user-provided keys are checked.
+fn check_unknown_flags(args: &minimist::Minimist, cmd: &str) -> Result<()> {
+    let Some(spec) = CMD_SPECS.iter().find(|s| s.cmd == cmd) else {
+        return Ok(());
+    };
+    for key in args.keys() {
+        if key == minimist::Minimist::POS
+            || key == minimist::Minimist::PASS
+            || GLOBAL_FLAGS.contains(&key.as_str())
+            || spec.flags.contains(&key.as_str())
+        {
+            continue;
+        }
+        let known = spec.flags.iter().chain(GLOBAL_FLAGS).copied();
+        return Err(Error::invalid(match did_you_mean(key, known) {
+            Some(s) => format!(
+                "Argument Error: unrecognized flag --{key} for '{cmd}' (did you mean --{s}?)"
+            ),
+            None => format!(
+                "Argument Error: unrecognized flag --{key} for '{cmd}'"
+            ),
+        }));
+    }
+    Ok(())
+}
+
+/// Toml config file for `vm serve`, mirroring the cli flags. Cli
+/// flags and env vars take precedence over file values.
+#[derive(Default, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ServeConfig {
+    #[serde(default)]
+    sys_admin: Vec<String>,
+    http_addr: Option<String>,
+    store: Option<std::path::PathBuf>,
+    store_cold: Option<std::path::PathBuf>,
+    request_timeout_secs: Option<f64>,
+    #[serde(default)]
+    read_only: bool,
+    rate_limit_rps: Option<f64>,
+    #[serde(default)]
+    access_log_health: bool,
+}
+
+fn load_serve_config(path: Option<&std::path::Path>) -> Result<ServeConfig> {
+    let Some(path) = path else {
+        return Ok(Default::default());
+    };
+    let text = match std::fs::read_to_string(path) {
+        Ok(text) => text,
+        // a missing file is fine: proceed with cli defaults
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+            return Ok(Default::default());
+        }
+        Err(err) => return Err(err),
+    };
+    toml::from_str(&text).map_err(Error::invalid)
+}
+
+/// Read admin tokens from a file, one per line, skipping blank lines.
+/// Keeps secrets out of ps output and shell history.
+fn read_token_file(path: &std::path::Path) -> Result<Vec<Arc<str>>> {
+    Ok(std::fs::read_to_string(path)?
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .map(Into::into)
+        .collect())
+}
+
 fn arg_parse() -> Result<Arg> {
-    let mut args = minimist::Minimist::parse(std::env::args_os().skip(1));
+    arg_parse_from(minimist::Minimist::parse(std::env::args_os().skip(1)))
+}
 
+/// Resolve a parsed argument map into a command. Split from
+/// [arg_parse] so the parser is testable without process args.
+fn arg_parse_from(mut args: minimist::Minimist) -> Result<Arg> {
     let mut cmd = args
         .to_one_str(minimist::Minimist::POS)
         .unwrap_or_else(|| "help".into());
@@ -33,6 +351,8 @@ fn arg_parse() -> Result<Arg> {
         cmd = "help".into();
     }
 
+    check_unknown_flags(&args, &cmd)?;
+
     macro_rules! exp {
         ($a:ident, $t:literal) => {
             $a.to_one_str($t).ok_or_else(|| {
@@ -61,19 +381,83 @@ fn arg_parse() -> Result<Arg> {
         "help" => Ok(Arg::Help),
         "version" => Ok(Arg::Version),
         "serve" => {
+            args.set_default_env("config", "VM_CONFIG");
+            let config = load_serve_config(
+                args.as_one_path("config").map(ToOwned::to_owned).as_deref(),
+            )?;
             def_split_env(&mut args, "sys-admin", "VM_SYS_ADMIN_TOKENS");
-            args.entry("sys-admin".into()).or_default();
+            split_list(&mut args, "sys-admin");
             args.set_default_env("http-addr", "VM_HTTP_ADDR");
+            if let Some(http_addr) = config.http_addr {
+                args.set_default("http-addr", http_addr);
+            }
             args.set_default("http-addr", "[::]:8080");
+            args.set_default_env(
+                "request-timeout-secs",
+                "VM_REQUEST_TIMEOUT_SECS",
+            );
+            if let Some(request_timeout_secs) = config.request_timeout_secs {
+                args.set_default(
+                    "request-timeout-secs",
+                    request_timeout_secs.to_string(),
+                );
+            }
+            args.set_default("request-timeout-secs", "60.0");
+            args.set_default_env("rate-limit-rps", "VM_RATE_LIMIT_RPS");
+            if let Some(rate_limit_rps) = config.rate_limit_rps {
+                args.set_default("rate-limit-rps", rate_limit_rps.to_string());
+            }
+            args.set_default_env("read-only", "VM_READ_ONLY");
+            args.set_default_env(
+                "access-log-health",
+                "VM_ACCESS_LOG_HEALTH",
+            );
             args.set_default_env("store", "VM_STORE");
+            args.set_default_env("store-cold", "VM_STORE_COLD");
+            args.set_default_env("sys-admin-file", "VM_SYS_ADMIN_TOKENS_FILE");
+            let mut sys_admin = list_str(&args, "sys-admin");
+            if let Some(path) = args.as_one_path("sys-admin-file") {
+                sys_admin.extend(read_token_file(path)?);
+            }
+            if sys_admin.is_empty() {
+                sys_admin = config
+                    .sys_admin
+                    .iter()
+                    .map(|s| s.as_str().into())
+                    .collect();
+            }
+            let store = args
+                .as_one_path("store")
+                .map(|p| p.to_owned())
+                .or(config.store);
+            let store_cold = args
+                .as_one_path("store-cold")
+                .map(|p| p.to_owned())
+                .or(config.store_cold);
+            // a cold tier in front of an ephemeral tempdir hot store
+            // would silently lose recent writes on restart
+            if store_cold.is_some() && store.is_none() {
+                return Err(Error::invalid(
+                    "Argument Error: --store-cold requires --store",
+                ));
+            }
             Ok(Arg::Serve {
-                sys_admin: args
-                    .to_list_str("sys-admin")
-                    .expect("--sys-admin is required")
-                    .map(|s| s.into())
-                    .collect::<Vec<_>>(),
+                sys_admin,
                 http_addr: exp!(args, "http-addr").into(),
-                store: args.as_one_path("store").map(|p| p.to_owned()),
+                store,
+                store_cold,
+                request_timeout_secs: parse_num(
+                    "request-timeout-secs",
+                    &exp!(args, "request-timeout-secs"),
+                    0.0,
+                )?,
+                rate_limit_rps: match args.to_one_str("rate-limit-rps") {
+                    Some(v) => Some(parse_num("rate-limit-rps", &v, 0.0)?),
+                    None => None,
+                },
+                read_only: args.as_flag("read-only") || config.read_only,
+                access_log_health: args.as_flag("access-log-health")
+                    || config.access_log_health,
             })
         }
         "test" => {
@@ -99,27 +483,57 @@ fn arg_parse() -> Result<Arg> {
             args.set_default_env("context", "VM_CTX");
             args.set_default_env("delete", "VM_DELETE");
             def_split_env(&mut args, "ctx-admin", "VM_CTX_ADMIN_TOKENS");
-            args.entry("ctx-admin".into()).or_default();
+            split_list(&mut args, "ctx-admin");
             args.set_default_env("timeout-secs", "VM_TIMEOUT_SECS");
             args.set_default("timeout-secs", "10.0");
             args.set_default_env("max-heap-bytes", "VM_MAX_HEAP_BYTES");
             args.set_default("max-heap-bytes", "33554432");
+            args.set_default_env(
+                "max-fn-request-bytes",
+                "VM_MAX_FN_REQUEST_BYTES",
+            );
+            args.set_default_env("max-code-bytes", "VM_MAX_CODE_BYTES");
+            args.set_default_env("warm-threads", "VM_WARM_THREADS");
+            args.set_default_env(
+                "ctx-admin-file",
+                "VM_CTX_ADMIN_TOKENS_FILE",
+            );
+            let mut ctx_admin = list_str(&args, "ctx-admin");
+            if let Some(path) = args.as_one_path("ctx-admin-file") {
+                ctx_admin.extend(read_token_file(path)?);
+            }
             Ok(Arg::CtxSetup {
                 url: exp!(args, "url").into(),
                 token: exp!(args, "token").into(),
                 context: exp!(args, "context").into(),
                 delete: args.as_flag("delete"),
-                ctx_admin: args
-                    .to_list_str("ctx-admin")
-                    .expect("--sys-admin is required")
-                    .map(|s| s.into())
-                    .collect::<Vec<_>>(),
-                timeout_secs: exp!(args, "timeout-secs")
-                    .parse()
-                    .map_err(Error::other)?,
-                max_heap_bytes: exp!(args, "max-heap-bytes")
-                    .parse()
-                    .map_err(Error::other)?,
+                ctx_admin,
+                timeout_secs: parse_num(
+                    "timeout-secs",
+                    &exp!(args, "timeout-secs"),
+                    0.0,
+                )?,
+                max_heap_bytes: parse_num(
+                    "max-heap-bytes",
+                    &exp!(args, "max-heap-bytes"),
+                    1024 * 1024,
+                )?,
+                max_fn_request_bytes: match args
+                    .to_one_str("max-fn-request-bytes")
+                {
+                    Some(v) => {
+                        Some(parse_num("max-fn-request-bytes", &v, 1)?)
+                    }
+                    None => None,
+                },
+                max_code_bytes: match args.to_one_str("max-code-bytes") {
+                    Some(v) => Some(parse_num("max-code-bytes", &v, 1)?),
+                    None => None,
+                },
+                warm_threads: match args.to_one_str("warm-threads") {
+                    Some(v) => Some(parse_num("warm-threads", &v, 0)?),
+                    None => None,
+                },
             })
         }
         "ctx-config" => {
@@ -127,22 +541,204 @@ fn arg_parse() -> Result<Arg> {
             args.set_default_env("token", "VM_TOKEN");
             args.set_default_env("context", "VM_CTX");
             def_split_env(&mut args, "ctx-admin", "VM_CTX_ADMIN_TOKENS");
-            args.entry("ctx-admin".into()).or_default();
+            split_list(&mut args, "ctx-admin");
             args.set_default_env("code-file", "VM_CODE");
+            args.set_default_env("code-dir", "VM_CODE_DIR");
+            args.set_default_env("code-entry", "VM_CODE_ENTRY");
+            args.set_default("code-entry", "main.js");
             args.set_default_env("code-env", "VM_ENV");
+            args.set_default_env("no-validate", "VM_NO_VALIDATE");
             Ok(Arg::CtxConfig {
                 url: exp!(args, "url").into(),
                 token: exp!(args, "token").into(),
                 context: exp!(args, "context").into(),
+                ctx_admin: list_str(&args, "ctx-admin"),
+                code_file: args.as_one_path("code-file").map(ToOwned::to_owned),
+                code_dir: args.as_one_path("code-dir").map(ToOwned::to_owned),
+                code_entry: exp!(args, "code-entry").into(),
+                // --env-file is the preferred spelling; --code-env
+                // remains as an alias
+                code_env: args
+                    .as_one_path("env-file")
+                    .or_else(|| args.as_one_path("code-env"))
+                    .map(ToOwned::to_owned),
+                validate: !args.as_flag("no-validate"),
+            })
+        }
+        "ctx" => {
+            args.set_default_env("url", "VM_URL");
+            args.set_default_env("token", "VM_TOKEN");
+            args.set_default_env("context", "VM_CTX");
+            args.set_default_env("delete", "VM_DELETE");
+            def_split_env(&mut args, "ctx-admin", "VM_CTX_ADMIN_TOKENS");
+            split_list(&mut args, "ctx-admin");
+            args.set_default_env("timeout-secs", "VM_TIMEOUT_SECS");
+            args.set_default_env("max-heap-bytes", "VM_MAX_HEAP_BYTES");
+            args.set_default_env("code-file", "VM_CODE");
+            args.set_default_env("code-env", "VM_ENV");
+            Ok(Arg::Ctx {
+                url: exp!(args, "url").into(),
+                token: exp!(args, "token").into(),
+                context: exp!(args, "context").into(),
+                delete: args.as_flag("delete"),
                 ctx_admin: args
                     .to_list_str("ctx-admin")
-                    .expect("--sys-admin is required")
-                    .map(|s| s.into())
-                    .collect::<Vec<_>>(),
-                code_file: exp_path!(args, "code-file").into(),
+                    .map(|list| list.map(|s| s.into()).collect()),
+                timeout_secs: match args.to_one_str("timeout-secs") {
+                    Some(v) => Some(parse_num("timeout-secs", &v, 0.0)?),
+                    None => None,
+                },
+                max_heap_bytes: match args.to_one_str("max-heap-bytes") {
+                    Some(v) => {
+                        Some(parse_num("max-heap-bytes", &v, 1024 * 1024)?)
+                    }
+                    None => None,
+                },
+                code_file: args.as_one_path("code-file").map(ToOwned::to_owned),
                 code_env: args.as_one_path("code-env").map(ToOwned::to_owned),
             })
         }
+        "ctx-versions" => {
+            args.set_default_env("url", "VM_URL");
+            args.set_default_env("token", "VM_TOKEN");
+            args.set_default_env("context", "VM_CTX");
+            Ok(Arg::CtxVersions {
+                url: exp!(args, "url").into(),
+                token: exp!(args, "token").into(),
+                context: exp!(args, "context").into(),
+            })
+        }
+        "ctx-rollback" => {
+            args.set_default_env("url", "VM_URL");
+            args.set_default_env("token", "VM_TOKEN");
+            args.set_default_env("context", "VM_CTX");
+            args.set_default_env("version", "VM_VERSION");
+            Ok(Arg::CtxRollback {
+                url: exp!(args, "url").into(),
+                token: exp!(args, "token").into(),
+                context: exp!(args, "context").into(),
+                version: parse_num("version", &exp!(args, "version"), 1)?,
+            })
+        }
+        "ctx-clone" => {
+            args.set_default_env("url", "VM_URL");
+            args.set_default_env("token", "VM_TOKEN");
+            args.set_default_env("src", "VM_SRC_CTX");
+            args.set_default_env("dst", "VM_DST_CTX");
+            Ok(Arg::CtxClone {
+                url: exp!(args, "url").into(),
+                token: exp!(args, "token").into(),
+                src: exp!(args, "src").into(),
+                dst: exp!(args, "dst").into(),
+            })
+        }
+        "ctx-list" => {
+            args.set_default_env("url", "VM_URL");
+            args.set_default_env("token", "VM_TOKEN");
+            Ok(Arg::CtxList {
+                url: exp!(args, "url").into(),
+                token: exp!(args, "token").into(),
+            })
+        }
+        "domain-set" => {
+            args.set_default_env("url", "VM_URL");
+            args.set_default_env("token", "VM_TOKEN");
+            args.set_default_env("context", "VM_CTX");
+            Ok(Arg::DomainSet {
+                url: exp!(args, "url").into(),
+                token: exp!(args, "token").into(),
+                host: exp!(args, "host").into(),
+                // an absent or empty context removes the mapping
+                context: args
+                    .to_one_str("context")
+                    .map(|s| s.as_ref().into())
+                    .unwrap_or_else(|| "".into()),
+            })
+        }
+        "domain-list" => {
+            args.set_default_env("url", "VM_URL");
+            args.set_default_env("token", "VM_TOKEN");
+            Ok(Arg::DomainList {
+                url: exp!(args, "url").into(),
+                token: exp!(args, "token").into(),
+            })
+        }
+        "ctx-admin-add" => {
+            args.set_default_env("url", "VM_URL");
+            args.set_default_env("token", "VM_TOKEN");
+            args.set_default_env("context", "VM_CTX");
+            args.set_default_env("admin", "VM_ADMIN_TOKEN");
+            Ok(Arg::CtxAdminAdd {
+                url: exp!(args, "url").into(),
+                token: exp!(args, "token").into(),
+                context: exp!(args, "context").into(),
+                admin: exp!(args, "admin").into(),
+            })
+        }
+        "ctx-admin-remove" => {
+            args.set_default_env("url", "VM_URL");
+            args.set_default_env("token", "VM_TOKEN");
+            args.set_default_env("context", "VM_CTX");
+            args.set_default_env("admin", "VM_ADMIN_TOKEN");
+            Ok(Arg::CtxAdminRemove {
+                url: exp!(args, "url").into(),
+                token: exp!(args, "token").into(),
+                context: exp!(args, "context").into(),
+                admin: exp!(args, "admin").into(),
+            })
+        }
+        "ctx-tokens" => {
+            args.set_default_env("url", "VM_URL");
+            args.set_default_env("token", "VM_TOKEN");
+            args.set_default_env("context", "VM_CTX");
+            split_list(&mut args, "add");
+            split_list(&mut args, "remove");
+            Ok(Arg::CtxTokens {
+                url: exp!(args, "url").into(),
+                token: exp!(args, "token").into(),
+                context: exp!(args, "context").into(),
+                add: list_str(&args, "add"),
+                remove: list_str(&args, "remove"),
+            })
+        }
+        "ctx-revalidate" => {
+            args.set_default_env("url", "VM_URL");
+            args.set_default_env("token", "VM_TOKEN");
+            args.set_default_env("context", "VM_CTX");
+            args.set_default_env("prefix", "VM_PREFIX");
+            args.set_default("prefix", "");
+            args.set_default_env("limit", "VM_LIMIT");
+            args.set_default("limit", "1000");
+            args.set_default_env("delete-failures", "VM_DELETE_FAILURES");
+            Ok(Arg::CtxRevalidate {
+                url: exp!(args, "url").into(),
+                token: exp!(args, "token").into(),
+                context: exp!(args, "context").into(),
+                prefix: exp!(args, "prefix").into(),
+                limit: parse_num("limit", &exp!(args, "limit"), 1)?,
+                delete_failures: args.as_flag("delete-failures"),
+            })
+        }
+        "bench" => {
+            args.set_default_env("url", "VM_URL");
+            args.set_default_env("token", "VM_TOKEN");
+            args.set_default_env("context", "VM_CTX");
+            args.set_default_env("concurrency", "VM_CONCURRENCY");
+            args.set_default("concurrency", "4");
+            args.set_default_env("requests", "VM_REQUESTS");
+            args.set_default("requests", "100");
+            Ok(Arg::Bench {
+                url: exp!(args, "url").into(),
+                token: exp!(args, "token").into(),
+                context: exp!(args, "context").into(),
+                concurrency: parse_num(
+                    "concurrency",
+                    &exp!(args, "concurrency"),
+                    1,
+                )?,
+                requests: parse_num("requests", &exp!(args, "requests"), 1)?,
+            })
+        }
         "obj-list" => {
             args.set_default_env("url", "VM_URL");
             args.set_default_env("token", "VM_TOKEN");
@@ -151,17 +747,50 @@ fn arg_parse() -> Result<Arg> {
             args.set_default("prefix", "");
             args.set_default_env("created-gt", "VM_CREATED_GT");
             args.set_default("created-gt", "0.0");
+            args.set_default_env("created-lt", "VM_CREATED_LT");
             args.set_default_env("limit", "VM_LIMIT");
             args.set_default("limit", "4294967295");
+            args.set_default_env("state-file", "VM_STATE_FILE");
+            args.set_default_env("detail", "VM_DETAIL");
+            args.set_default_env("desc", "VM_DESC");
+            args.set_default_env("output", "VM_OUTPUT");
+            args.set_default("output", "text");
+            let output = parse_list_output(&exp!(args, "output"))?;
+            let detail = args.as_flag("detail");
+            if detail && output != ListOutput::Text {
+                return Err(Error::invalid(
+                    "Argument Error: --detail only supports --output text",
+                ));
+            }
+            let desc = args.as_flag("desc");
+            let state_file =
+                args.as_one_path("state-file").map(ToOwned::to_owned);
+            // the state-file watermark resumes an ascending walk;
+            // there is no equivalent resume point walking newest first
+            if desc && state_file.is_some() {
+                return Err(Error::invalid(
+                    "Argument Error: --state-file cannot combine with --desc",
+                ));
+            }
             Ok(Arg::ObjList {
                 url: exp!(args, "url").into(),
                 token: exp!(args, "token").into(),
                 context: exp!(args, "context").into(),
                 prefix: exp!(args, "prefix").into(),
-                created_gt: exp!(args, "created-gt")
-                    .parse()
-                    .map_err(Error::other)?,
-                limit: exp!(args, "limit").parse().map_err(Error::other)?,
+                created_gt: parse_num(
+                    "created-gt",
+                    &exp!(args, "created-gt"),
+                    0.0,
+                )?,
+                created_lt: match args.to_one_str("created-lt") {
+                    Some(v) => Some(parse_num("created-lt", &v, 0.0)?),
+                    None => None,
+                },
+                desc,
+                limit: parse_num("limit", &exp!(args, "limit"), 1)?,
+                state_file,
+                detail,
+                output,
             })
         }
         "obj-get" => {
@@ -170,11 +799,14 @@ fn arg_parse() -> Result<Arg> {
             args.set_default_env("context", "VM_CTX");
             args.set_default_env("app-path", "VM_APP_PATH");
             args.set_default("app-path", "");
+            args.set_default_env("format", "VM_FORMAT");
+            args.set_default("format", "msgpack");
             Ok(Arg::ObjGet {
                 url: exp!(args, "url").into(),
                 token: exp!(args, "token").into(),
                 context: exp!(args, "context").into(),
                 app_path: exp!(args, "app-path").into(),
+                format: parse_format(&exp!(args, "format"))?,
             })
         }
         "obj-put" => {
@@ -187,6 +819,9 @@ fn arg_parse() -> Result<Arg> {
             args.set_default("create", safe_now().to_string());
             args.set_default_env("expire", "VM_EXPIRE");
             args.set_default("expire", "0.0");
+            args.set_default_env("format", "VM_FORMAT");
+            args.set_default("format", "msgpack");
+            args.set_default_env("content-type", "VM_CONTENT_TYPE");
             Ok(Arg::ObjPut {
                 url: exp!(args, "url").into(),
                 token: exp!(args, "token").into(),
@@ -194,6 +829,31 @@ fn arg_parse() -> Result<Arg> {
                 app_path: exp!(args, "app-path").into(),
                 create: exp!(args, "create").into(),
                 expire: exp!(args, "expire").into(),
+                format: parse_format(&exp!(args, "format"))?,
+                content_type: args.to_one_str("content-type"),
+            })
+        }
+        "obj-sign" => {
+            args.set_default_env("url", "VM_URL");
+            args.set_default_env("token", "VM_TOKEN");
+            args.set_default_env("context", "VM_CTX");
+            args.set_default_env("app-path", "VM_APP_PATH");
+            args.set_default_env("ttl-secs", "VM_TTL_SECS");
+            args.set_default("ttl-secs", "600.0");
+            Ok(Arg::ObjSign {
+                url: exp!(args, "url").into(),
+                token: exp!(args, "token").into(),
+                context: exp!(args, "context").into(),
+                app_path: exp!(args, "app-path").into(),
+                ttl_secs: parse_num("ttl-secs", &exp!(args, "ttl-secs"), 0.0)?,
+            })
+        }
+        "fsck" => {
+            args.set_default_env("store", "VM_STORE");
+            args.set_default_env("repair", "VM_REPAIR");
+            Ok(Arg::Fsck {
+                store: exp_path!(args, "store").into(),
+                repair: args.as_flag("repair"),
             })
         }
         "obj-backup-full" => {
@@ -223,9 +883,11 @@ fn arg_parse() -> Result<Arg> {
                 url: exp!(args, "url").into(),
                 token: exp!(args, "token").into(),
                 context: exp!(args, "context").into(),
-                created_gt: exp!(args, "created-gt")
-                    .parse()
-                    .map_err(Error::other)?,
+                created_gt: parse_num(
+                    "created-gt",
+                    &exp!(args, "created-gt"),
+                    0.0,
+                )?,
                 zip_file: exp_path!(args, "zip-file").into(),
             })
         }
@@ -241,7 +903,155 @@ fn arg_parse() -> Result<Arg> {
                 zip_file: exp_path!(args, "zip-file").into(),
             })
         }
-        unk => Err(Error::other(format!("unrecognised command: {unk}"))),
+        unk => Err(Error::invalid(
+            match did_you_mean(unk, CMD_SPECS.iter().map(|s| s.cmd)) {
+                Some(s) => format!(
+                    "Argument Error: unrecognized command: {unk} (did you mean {s}?)"
+                ),
+                None => {
+                    format!("Argument Error: unrecognized command: {unk}")
+                }
+            },
+        )),
+    }
+}
+
+/// Global log output format, resolved before the subscriber is
+/// installed (and therefore before normal argument parsing).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LogFormat {
+    /// Newline-delimited json with timestamps, for log aggregation.
+    Json,
+    /// Compact human-readable lines without timestamps, for dev use.
+    Compact,
+}
+
+/// Resolve the log format from the VM_LOG_FORMAT env var, overridden
+/// by a `--log-format` flag anywhere on the command line.
+fn log_format() -> Result<LogFormat> {
+    let mut raw = std::env::var("VM_LOG_FORMAT").ok();
+    let mut argv = std::env::args();
+    while let Some(arg) = argv.next() {
+        if arg == "--log-format" {
+            raw = argv.next();
+        } else if let Some(v) = arg.strip_prefix("--log-format=") {
+            raw = Some(v.to_string());
+        }
+    }
+    match raw.as_deref() {
+        None | Some("json") => Ok(LogFormat::Json),
+        Some("compact") => Ok(LogFormat::Compact),
+        Some(_) => Err(Error::invalid(
+            "Argument Error: --log-format must be 'json' or 'compact'",
+        )),
+    }
+}
+
+/// Output format for http access log events, resolved like
+/// [LogFormat] before the subscriber is installed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AccessLogFormat {
+    /// Access events flow through the normal structured log.
+    Json,
+    /// Access events render as common-log-style lines on stdout,
+    /// excluded from the normal structured log.
+    Common,
+}
+
+/// Resolve the access log format from the VM_ACCESS_LOG_FORMAT env
+/// var, overridden by an `--access-log-format` flag anywhere on the
+/// command line.
+fn access_log_format() -> Result<AccessLogFormat> {
+    let mut raw = std::env::var("VM_ACCESS_LOG_FORMAT").ok();
+    let mut argv = std::env::args();
+    while let Some(arg) = argv.next() {
+        if arg == "--access-log-format" {
+            raw = argv.next();
+        } else if let Some(v) = arg.strip_prefix("--access-log-format=") {
+            raw = Some(v.to_string());
+        }
+    }
+    match raw.as_deref() {
+        None | Some("json") => Ok(AccessLogFormat::Json),
+        Some("common") => Ok(AccessLogFormat::Common),
+        Some(_) => Err(Error::invalid(
+            "Argument Error: --access-log-format must be 'json' or 'common'",
+        )),
+    }
+}
+
+/// Renders target "access" events as common-log-style lines on
+/// stdout: `remote - token [unix-secs] "METHOD path" status bytes`.
+struct AccessLogCommon;
+
+impl<S: tracing::Subscriber> tracing_subscriber::Layer<S>
+    for AccessLogCommon
+{
+    fn on_event(
+        &self,
+        event: &tracing::Event<'_>,
+        _ctx: tracing_subscriber::layer::Context<'_, S>,
+    ) {
+        if event.metadata().target() != "access" {
+            return;
+        }
+        #[derive(Default)]
+        struct V {
+            remote: String,
+            token: String,
+            method: String,
+            path: String,
+            status: u64,
+            bytes: u64,
+        }
+        impl tracing::field::Visit for V {
+            fn record_u64(
+                &mut self,
+                field: &tracing::field::Field,
+                value: u64,
+            ) {
+                match field.name() {
+                    "status" => self.status = value,
+                    "bytes" => self.bytes = value,
+                    _ => (),
+                }
+            }
+
+            fn record_str(
+                &mut self,
+                field: &tracing::field::Field,
+                value: &str,
+            ) {
+                match field.name() {
+                    "remote" => self.remote = value.into(),
+                    "token" => self.token = value.into(),
+                    "path" => self.path = value.into(),
+                    _ => (),
+                }
+            }
+
+            fn record_debug(
+                &mut self,
+                field: &tracing::field::Field,
+                value: &dyn std::fmt::Debug,
+            ) {
+                if field.name() == "method" {
+                    self.method = format!("{value:?}");
+                }
+            }
+        }
+        let mut v = V::default();
+        event.record(&mut v);
+        let remote = if v.remote.is_empty() { "-" } else { &v.remote };
+        let token = if v.token.is_empty() { "-" } else { &v.token };
+        println!(
+            "{remote} - {token} [{}] \"{} {}\" {} {}",
+            safe_now() as u64,
+            v.method,
+            v.path,
+            v.status,
+            v.bytes,
+        );
     }
 }
 
@@ -259,11 +1069,48 @@ async fn main() -> Result<()> {
         )
         .from_env_lossy();
 
-    let fmt_layer = tracing_subscriber::fmt::layer().json();
+    let fmt_layer = match log_format() {
+        Ok(LogFormat::Json) => tracing_subscriber::fmt::layer()
+            .json()
+            .with_current_span(true)
+            .boxed(),
+        Ok(LogFormat::Compact) => tracing_subscriber::fmt::layer()
+            .compact()
+            .without_time()
+            .boxed(),
+        Err(err) => {
+            eprintln!("\n-----\n{err}\n-----");
+            eprintln!("\n`vm --help` for additional info");
+            std::process::exit(1);
+        }
+    };
+
+    let access_log_format = match access_log_format() {
+        Ok(access_log_format) => access_log_format,
+        Err(err) => {
+            eprintln!("\n-----\n{err}\n-----");
+            eprintln!("\n`vm --help` for additional info");
+            std::process::exit(1);
+        }
+    };
+
+    // in common format, access events leave the structured log and
+    // render through the dedicated layer instead
+    let fmt_layer = match access_log_format {
+        AccessLogFormat::Json => fmt_layer,
+        AccessLogFormat::Common => fmt_layer
+            .with_filter(tracing_subscriber::filter::filter_fn(|metadata| {
+                metadata.target() != "access"
+            }))
+            .boxed(),
+    };
+    let access_layer = (access_log_format == AccessLogFormat::Common)
+        .then_some(AccessLogCommon);
 
     let sub = tracing_subscriber::Registry::default()
         .with(filter_layer)
-        .with(fmt_layer);
+        .with(fmt_layer)
+        .with(access_layer);
 
     if std::env::var_os("OTEL_EXPORTER_OTLP_ENDPOINT").is_some() {
         let log_exporter = opentelemetry_otlp::LogExporter::builder()
@@ -319,6 +1166,76 @@ async fn main() -> Result<()> {
     arg.exec().await
 }
 
+/// Data format for obj-get / obj-put content transfer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    /// Raw passthrough of the stored msgpack bytes.
+    Msgpack,
+    /// Transcode between the stored msgpack bytes and human-readable JSON.
+    Json,
+}
+
+fn parse_format(s: &str) -> Result<Format> {
+    match s {
+        "msgpack" => Ok(Format::Msgpack),
+        "json" => Ok(Format::Json),
+        _ => Err(Error::invalid(
+            "Argument Error: --format must be 'msgpack' or 'json'",
+        )),
+    }
+}
+
+/// Output format for obj-list results.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ListOutput {
+    /// Human-readable lines (the historical default).
+    Text,
+    /// A JSON array of meta strings.
+    Json,
+    /// CSV rows of meta path components.
+    Csv,
+}
+
+fn parse_list_output(s: &str) -> Result<ListOutput> {
+    match s {
+        "text" => Ok(ListOutput::Text),
+        "json" => Ok(ListOutput::Json),
+        "csv" => Ok(ListOutput::Csv),
+        _ => Err(Error::invalid(
+            "Argument Error: --output must be 'text', 'json', or 'csv'",
+        )),
+    }
+}
+
+/// Recursively read every `.js` / `.mjs` file under `dir`, keyed by
+/// path relative to `dir` with `/` separators.
+async fn read_code_dir(
+    dir: &std::path::Path,
+) -> Result<std::collections::BTreeMap<Arc<str>, Arc<str>>> {
+    let mut out = std::collections::BTreeMap::new();
+    let mut dirs = vec![dir.to_owned()];
+    while let Some(d) = dirs.pop() {
+        let mut read = tokio::fs::read_dir(&d).await?;
+        while let Some(entry) = read.next_entry().await? {
+            let path = entry.path();
+            if entry.file_type().await?.is_dir() {
+                dirs.push(path);
+            } else if matches!(
+                path.extension().and_then(|e| e.to_str()),
+                Some("js") | Some("mjs"),
+            ) {
+                let rel = path.strip_prefix(dir).map_err(Error::other)?;
+                let spec: Arc<str> =
+                    rel.to_string_lossy().replace('\\', "/").into();
+                let src: Arc<str> =
+                    tokio::fs::read_to_string(&path).await?.into();
+                out.insert(spec, src);
+            }
+        }
+    }
+    Ok(out)
+}
+
 #[derive(Debug)]
 enum Arg {
     Help,
@@ -327,31 +1244,118 @@ enum Arg {
         sys_admin: Vec<Arc<str>>,
         http_addr: String,
         store: Option<std::path::PathBuf>,
+        store_cold: Option<std::path::PathBuf>,
+        request_timeout_secs: f64,
+        rate_limit_rps: Option<f64>,
+        read_only: bool,
+        access_log_health: bool,
+    },
+    Test {
+        http_addr: String,
+        code_file: std::path::PathBuf,
+        code_env: Option<std::path::PathBuf>,
+    },
+    Health {
+        url: String,
+    },
+    CtxSetup {
+        url: String,
+        token: Arc<str>,
+        context: Arc<str>,
+        delete: bool,
+        ctx_admin: Vec<Arc<str>>,
+        timeout_secs: f64,
+        max_heap_bytes: usize,
+        max_fn_request_bytes: Option<u64>,
+        max_code_bytes: Option<u64>,
+        warm_threads: Option<u32>,
+    },
+    CtxConfig {
+        url: String,
+        token: Arc<str>,
+        context: Arc<str>,
+        ctx_admin: Vec<Arc<str>>,
+        code_file: Option<std::path::PathBuf>,
+        code_dir: Option<std::path::PathBuf>,
+        code_entry: Arc<str>,
+        code_env: Option<std::path::PathBuf>,
+        validate: bool,
+    },
+    Ctx {
+        url: String,
+        token: Arc<str>,
+        context: Arc<str>,
+        delete: bool,
+        ctx_admin: Option<Vec<Arc<str>>>,
+        timeout_secs: Option<f64>,
+        max_heap_bytes: Option<usize>,
+        code_file: Option<std::path::PathBuf>,
+        code_env: Option<std::path::PathBuf>,
+    },
+    CtxVersions {
+        url: String,
+        token: Arc<str>,
+        context: Arc<str>,
+    },
+    CtxRollback {
+        url: String,
+        token: Arc<str>,
+        context: Arc<str>,
+        version: u64,
+    },
+    CtxClone {
+        url: String,
+        token: Arc<str>,
+        src: Arc<str>,
+        dst: Arc<str>,
+    },
+    CtxList {
+        url: String,
+        token: Arc<str>,
+    },
+    DomainSet {
+        url: String,
+        token: Arc<str>,
+        host: Arc<str>,
+        context: Arc<str>,
     },
-    Test {
-        http_addr: String,
-        code_file: std::path::PathBuf,
-        code_env: Option<std::path::PathBuf>,
+    DomainList {
+        url: String,
+        token: Arc<str>,
     },
-    Health {
+    CtxAdminAdd {
         url: String,
+        token: Arc<str>,
+        context: Arc<str>,
+        admin: Arc<str>,
     },
-    CtxSetup {
+    CtxAdminRemove {
         url: String,
         token: Arc<str>,
         context: Arc<str>,
-        delete: bool,
-        ctx_admin: Vec<Arc<str>>,
-        timeout_secs: f64,
-        max_heap_bytes: usize,
+        admin: Arc<str>,
     },
-    CtxConfig {
+    CtxTokens {
         url: String,
         token: Arc<str>,
         context: Arc<str>,
-        ctx_admin: Vec<Arc<str>>,
-        code_file: std::path::PathBuf,
-        code_env: Option<std::path::PathBuf>,
+        add: Vec<Arc<str>>,
+        remove: Vec<Arc<str>>,
+    },
+    CtxRevalidate {
+        url: String,
+        token: Arc<str>,
+        context: Arc<str>,
+        prefix: Arc<str>,
+        limit: u32,
+        delete_failures: bool,
+    },
+    Bench {
+        url: String,
+        token: Arc<str>,
+        context: Arc<str>,
+        concurrency: u32,
+        requests: u32,
     },
     ObjList {
         url: String,
@@ -359,13 +1363,19 @@ enum Arg {
         context: Arc<str>,
         prefix: Arc<str>,
         created_gt: f64,
+        created_lt: Option<f64>,
+        desc: bool,
         limit: u32,
+        state_file: Option<std::path::PathBuf>,
+        detail: bool,
+        output: ListOutput,
     },
     ObjGet {
         url: String,
         token: Arc<str>,
         context: Arc<str>,
         app_path: Arc<str>,
+        format: Format,
     },
     ObjPut {
         url: String,
@@ -374,6 +1384,19 @@ enum Arg {
         app_path: String,
         create: String,
         expire: String,
+        format: Format,
+        content_type: Option<String>,
+    },
+    ObjSign {
+        url: String,
+        token: Arc<str>,
+        context: Arc<str>,
+        app_path: String,
+        ttl_secs: f64,
+    },
+    Fsck {
+        store: std::path::PathBuf,
+        repair: bool,
     },
     ObjBackupFull {
         url: String,
@@ -398,28 +1421,96 @@ enum Arg {
     },
 }
 
+/// Build the object store configured by a store value: a filesystem
+/// path for [obj::obj_file::ObjFile], or an `opendal://<scheme>?<k=v>`
+/// url for a remote backend when built with the `opendal` feature.
+async fn create_store(
+    store: Option<std::path::PathBuf>,
+) -> Result<obj::ObjWrap> {
+    if let Some(store) = &store
+        && let Some(url) = store.to_str()
+        && url.starts_with("opendal://")
+    {
+        #[cfg(feature = "opendal")]
+        return obj::obj_opendal::ObjOpendal::from_url(url);
+        #[cfg(not(feature = "opendal"))]
+        return Err(Error::invalid(
+            "Argument Error: opendal:// store urls require a build \
+             with the 'opendal' cargo feature",
+        ));
+    }
+    obj::obj_file::ObjFile::create(store).await
+}
+
 async fn serve(
     s: tokio::sync::oneshot::Sender<std::net::SocketAddr>,
     sys_admin: Vec<Arc<str>>,
     http_addr: String,
     store: Option<std::path::PathBuf>,
+    store_cold: Option<std::path::PathBuf>,
+    request_timeout_secs: f64,
+    rate_limit_rps: Option<f64>,
+    read_only: bool,
+    access_log_health: bool,
 ) -> Result<()> {
     let http_addr: std::net::SocketAddr = http_addr.parse().map_err(|err| {
         Error::other(err).with_info("failed to parse http server bind address")
     })?;
+    http_server::http_server_global_set_request_timeout(
+        std::time::Duration::from_secs_f64(request_timeout_secs),
+    );
+    http_server::http_server_global_set_access_log_health(access_log_health);
+    // a no-op under vm test, which has already claimed mode "test"
+    js::js_global_set_run_mode("serve");
     let runtime = RuntimeHandle::default();
-    runtime.set_obj(obj::obj_file::ObjFile::create(store).await?);
+    let store_obj = if let Some(store_cold) = store_cold {
+        obj::obj_tiered::ObjTiered::create(
+            create_store(store).await?.into_dyn(),
+            create_store(Some(store_cold)).await?.into_dyn(),
+            Default::default(),
+        )
+    } else {
+        create_store(store).await?
+    };
+    runtime.set_obj(store_obj);
     runtime.set_js(js::JsExecMeter::create(js::JsExecDefault::create()));
     runtime.set_msg(msg::MsgMem::create());
 
     let server = server::Server::new(runtime).await?;
     server.set_sys_admin(sys_admin).await?;
+    server.set_read_only(read_only);
+    if let Some(rate_limit_rps) = rate_limit_rps {
+        server.set_rate_limit_rps(rate_limit_rps);
+    }
     http_server::http_server(s, http_addr, server).await
 }
 
 impl Arg {
     async fn exec(self) -> Result<()> {
-        tracing::info!(args = ?self);
+        match &self {
+            // the admin tokens are secrets: log only their count
+            Self::Serve {
+                sys_admin,
+                http_addr,
+                store,
+                store_cold,
+                request_timeout_secs,
+                rate_limit_rps,
+                read_only,
+                access_log_health,
+            } => tracing::info!(
+                args = "serve",
+                sys_admin_count = sys_admin.len(),
+                ?http_addr,
+                ?store,
+                ?store_cold,
+                request_timeout_secs,
+                ?rate_limit_rps,
+                read_only,
+                access_log_health,
+            ),
+            _ => tracing::info!(args = ?self),
+        }
         match self {
             Self::Help => {
                 help();
@@ -437,6 +1528,11 @@ impl Arg {
                 sys_admin,
                 http_addr,
                 store,
+                store_cold,
+                request_timeout_secs,
+                rate_limit_rps,
+                read_only,
+                access_log_health,
             } => {
                 let (s, r) = tokio::sync::oneshot::channel();
                 tokio::task::spawn(async move {
@@ -444,13 +1540,26 @@ impl Arg {
                         eprintln!("#vm#listening#{addr:?}#");
                     }
                 });
-                serve(s, sys_admin, http_addr, store).await
+                serve(
+                    s,
+                    sys_admin,
+                    http_addr,
+                    store,
+                    store_cold,
+                    request_timeout_secs,
+                    rate_limit_rps,
+                    read_only,
+                    access_log_health,
+                )
+                .await
             }
             Self::Test {
                 http_addr,
                 code_file,
                 code_env,
             } => {
+                // report mode "test" to context code via VM.info()
+                js::js_global_set_run_mode("test");
                 let code: Arc<str> =
                     tokio::fs::read_to_string(code_file).await?.into();
                 let code_env: serde_json::Value =
@@ -477,7 +1586,8 @@ impl Arg {
                     // check health
                     let client = voidmerge::http_client::HttpClient::new(
                         Default::default(),
-                    );
+                    )
+                    .expect("failed to construct http client");
                     let mut is_healthy = false;
                     for _ in 0..10 {
                         tokio::time::sleep(std::time::Duration::from_millis(
@@ -506,6 +1616,9 @@ impl Arg {
                                 ctx_admin: vec!["test".into()],
                                 timeout_secs: 10.0,
                                 max_heap_bytes: 33554432,
+                                max_fn_request_bytes: None,
+                                max_code_bytes: None,
+                                warm_threads: None,
                             },
                         )
                         .await
@@ -522,7 +1635,14 @@ impl Arg {
                                 ctx: "test".into(),
                                 ctx_admin: vec!["test".into()],
                                 code,
+                                code_modules: Default::default(),
+                                code_entry: "".into(),
+                                wasm: None,
                                 code_env: code_env.into(),
+                                fn_path_allow: Vec::new(),
+                                hdr_allow: Vec::new(),
+                                hdr_deny: Vec::new(),
+                                validate: true,
                             },
                         )
                         .await
@@ -533,11 +1653,22 @@ impl Arg {
                     // okay, we're running!
                     eprintln!("#vm#listening#{addr:?}#");
                 });
-                serve(s, vec!["test".into()], http_addr, None).await
+                serve(
+                    s,
+                    vec!["test".into()],
+                    http_addr,
+                    None,
+                    None,
+                    60.0,
+                    None,
+                    false,
+                    false,
+                )
+                .await
             }
             Self::Health { url } => {
                 let client =
-                    voidmerge::http_client::HttpClient::new(Default::default());
+                    voidmerge::http_client::HttpClient::new(Default::default())?;
                 client.health(&url).await
             }
             Self::CtxSetup {
@@ -548,6 +1679,9 @@ impl Arg {
                 ctx_admin,
                 timeout_secs,
                 max_heap_bytes,
+                max_fn_request_bytes,
+                max_code_bytes,
+                warm_threads,
             } => {
                 let ctx_setup = crate::server::CtxSetup {
                     ctx: context,
@@ -555,10 +1689,13 @@ impl Arg {
                     ctx_admin,
                     timeout_secs,
                     max_heap_bytes,
+                    max_fn_request_bytes,
+                    max_code_bytes,
+                    warm_threads,
                 };
 
                 let client =
-                    voidmerge::http_client::HttpClient::new(Default::default());
+                    voidmerge::http_client::HttpClient::new(Default::default())?;
                 client.ctx_setup(&url, &token, ctx_setup).await
             }
             Self::CtxConfig {
@@ -567,9 +1704,31 @@ impl Arg {
                 context,
                 ctx_admin,
                 code_file,
+                code_dir,
+                code_entry,
                 code_env,
+                validate,
             } => {
-                let code = tokio::fs::read_to_string(code_file).await?.into();
+                let (code, code_modules): (
+                    Arc<str>,
+                    std::collections::BTreeMap<Arc<str>, Arc<str>>,
+                ) = if let Some(code_dir) = code_dir {
+                    ("".into(), read_code_dir(&code_dir).await?)
+                } else if let Some(code_file) = code_file {
+                    (
+                        tokio::fs::read_to_string(code_file).await?.into(),
+                        Default::default(),
+                    )
+                } else {
+                    return Err(Error::other(
+                        "--code-file or --code-dir is required",
+                    ));
+                };
+                let code_entry: Arc<str> = if code_modules.is_empty() {
+                    "".into()
+                } else {
+                    code_entry
+                };
                 let code_env: serde_json::Value =
                     if let Some(code_env) = code_env {
                         serde_json::from_str(
@@ -583,46 +1742,417 @@ impl Arg {
                     ctx: context,
                     ctx_admin,
                     code,
+                    code_modules: code_modules.into(),
+                    code_entry,
+                    wasm: None,
                     code_env: code_env.into(),
+                    fn_path_allow: Vec::new(),
+                    hdr_allow: Vec::new(),
+                    hdr_deny: Vec::new(),
+                    validate,
                 };
 
                 let client =
-                    voidmerge::http_client::HttpClient::new(Default::default());
+                    voidmerge::http_client::HttpClient::new(Default::default())?;
                 client.ctx_config(&url, &token, ctx_config).await
             }
-            Self::ObjList {
+            Self::Ctx {
+                url,
+                token,
+                context,
+                delete,
+                ctx_admin,
+                timeout_secs,
+                max_heap_bytes,
+                code_file,
+                code_env,
+            } => {
+                let code: Option<Arc<str>> = match code_file {
+                    Some(code_file) => Some(
+                        tokio::fs::read_to_string(code_file).await?.into(),
+                    ),
+                    None => None,
+                };
+                let code_env: Option<Arc<serde_json::Value>> = match code_env
+                {
+                    Some(code_env) => Some(Arc::new(serde_json::from_str(
+                        &tokio::fs::read_to_string(code_env).await?,
+                    )?)),
+                    None => None,
+                };
+
+                let ctx_put = crate::server::CtxPut {
+                    ctx: context,
+                    delete,
+                    ctx_admin,
+                    timeout_secs,
+                    max_heap_bytes,
+                    code,
+                    code_env,
+                };
+
+                let client =
+                    voidmerge::http_client::HttpClient::new(Default::default())?;
+                client.ctx_put(&url, &token, ctx_put).await
+            }
+            Self::CtxVersions {
+                url,
+                token,
+                context,
+            } => {
+                let client =
+                    voidmerge::http_client::HttpClient::new(Default::default())?;
+                let list =
+                    client.ctx_config_versions(&url, &context, &token).await?;
+                let count = list.len();
+                println!(
+                    "{:>8} {:>18} {:>12} codeHash",
+                    "version", "createdSecs", "sizeBytes",
+                );
+                for entry in list {
+                    println!(
+                        "{:>8} {:>18} {:>12} {}",
+                        entry.version,
+                        entry.created_secs,
+                        entry.size_bytes,
+                        entry.code_hash,
+                    );
+                }
+                eprintln!("#vm#version-count#{count}#");
+                Ok(())
+            }
+            Self::CtxRollback {
+                url,
+                token,
+                context,
+                version,
+            } => {
+                let client =
+                    voidmerge::http_client::HttpClient::new(Default::default())?;
+                client
+                    .ctx_config_rollback(&url, &context, &token, version)
+                    .await?;
+                eprintln!("#vm#rollback#{version}#");
+                Ok(())
+            }
+            Self::CtxClone {
+                url,
+                token,
+                src,
+                dst,
+            } => {
+                let client =
+                    voidmerge::http_client::HttpClient::new(Default::default())?;
+                client.ctx_clone(&url, &token, &src, &dst).await
+            }
+            Self::CtxList { url, token } => {
+                let client =
+                    voidmerge::http_client::HttpClient::new(Default::default())?;
+                let res = client.ctx_list(&url, &token).await?;
+                let count = res.len();
+                for entry in res {
+                    println!(
+                        "{} ctxAdminCount={} timeoutSecs={} maxHeapBytes={}",
+                        entry.ctx,
+                        entry.ctx_admin_count,
+                        entry.timeout_secs,
+                        entry.max_heap_bytes,
+                    );
+                }
+                eprintln!("#vm#ctx-count#{count}#");
+                Ok(())
+            }
+            Self::DomainSet {
+                url,
+                token,
+                host,
+                context,
+            } => {
+                let client =
+                    voidmerge::http_client::HttpClient::new(Default::default())?;
+                client.set_domain(&url, &token, &host, &context).await?;
+                eprintln!("#vm#domain-set#{host}#");
+                Ok(())
+            }
+            Self::DomainList { url, token } => {
+                let client =
+                    voidmerge::http_client::HttpClient::new(Default::default())?;
+                let domains = client.list_domains(&url, &token).await?;
+                let count = domains.len();
+                let mut domains: Vec<_> = domains.into_iter().collect();
+                domains.sort();
+                for (host, ctx) in domains {
+                    println!("{host} {ctx}");
+                }
+                eprintln!("#vm#domain-count#{count}#");
+                Ok(())
+            }
+            Self::CtxAdminAdd {
+                url,
+                token,
+                context,
+                admin,
+            } => {
+                let client =
+                    voidmerge::http_client::HttpClient::new(Default::default())?;
+                let list = client
+                    .ctx_admin_add(&url, &token, &context, &admin)
+                    .await?;
+                eprintln!("#vm#ctx-admin-count#{}#", list.len());
+                Ok(())
+            }
+            Self::CtxAdminRemove {
+                url,
+                token,
+                context,
+                admin,
+            } => {
+                let client =
+                    voidmerge::http_client::HttpClient::new(Default::default())?;
+                let list = client
+                    .ctx_admin_remove(&url, &token, &context, &admin)
+                    .await?;
+                eprintln!("#vm#ctx-admin-count#{}#", list.len());
+                Ok(())
+            }
+            Self::CtxTokens {
+                url,
+                token,
+                context,
+                add,
+                remove,
+            } => {
+                let client =
+                    voidmerge::http_client::HttpClient::new(Default::default())?;
+                let list = client
+                    .ctx_tokens(
+                        &url,
+                        &context,
+                        &token,
+                        &voidmerge::server::CtxTokens { add, remove },
+                    )
+                    .await?;
+                eprintln!("#vm#ctx-admin-count#{}#", list.len());
+                Ok(())
+            }
+            Self::CtxRevalidate {
                 url,
                 token,
                 context,
                 prefix,
-                mut created_gt,
-                mut limit,
+                limit,
+                delete_failures,
             } => {
                 let client =
-                    voidmerge::http_client::HttpClient::new(Default::default());
-                let mut count = 0;
-                while limit > 1000 {
-                    let next_count = std::cmp::min(1000, limit);
-                    limit -= next_count;
-                    let res = client
-                        .obj_list(
-                            &url, &context, &token, &prefix, created_gt,
-                            next_count,
+                    voidmerge::http_client::HttpClient::new(Default::default())?;
+                let mut checked: u64 = 0;
+                let mut fail_count: u64 = 0;
+                let mut created_gt = 0.0;
+                loop {
+                    let report = client
+                        .ctx_revalidate(
+                            &url,
+                            &context,
+                            &token,
+                            &prefix,
+                            created_gt,
+                            limit,
+                            delete_failures,
                         )
                         .await?;
-                    if res.is_empty() {
-                        break;
+                    checked += report.checked;
+                    for failure in report.failures {
+                        fail_count += 1;
+                        println!("{} {}", failure.meta, failure.error);
                     }
-                    for r in res {
-                        let created_secs = r.created_secs();
-                        if created_secs > created_gt {
-                            created_gt = created_secs;
+                    match report.continuation {
+                        Some(next) => created_gt = next,
+                        None => break,
+                    }
+                }
+                eprintln!("#vm#revalidate#checked:{checked}#failed:{fail_count}#");
+                Ok(())
+            }
+            Self::Bench {
+                url,
+                token,
+                context,
+                concurrency,
+                requests,
+            } => {
+                let concurrency = concurrency.max(1);
+                let per_task = (requests / concurrency).max(1);
+
+                let client = Arc::new(
+                    voidmerge::http_client::HttpClient::new(
+                        Default::default(),
+                    )?,
+                );
+
+                let start = tokio::time::Instant::now();
+                let mut tasks = Vec::with_capacity(concurrency as usize);
+                for _ in 0..concurrency {
+                    let client = client.clone();
+                    let url = url.clone();
+                    let token = token.clone();
+                    let context = context.clone();
+                    tasks.push(tokio::task::spawn(async move {
+                        let mut durations =
+                            Vec::with_capacity(per_task as usize);
+                        for _ in 0..per_task {
+                            let req_start = tokio::time::Instant::now();
+                            client.fn_get(&url, &token, &context).await?;
+                            durations.push(req_start.elapsed());
+                        }
+                        Ok::<Vec<std::time::Duration>, Error>(durations)
+                    }));
+                }
+
+                let mut durations = Vec::new();
+                for task in tasks {
+                    durations.extend(task.await.map_err(Error::other)??);
+                }
+                let total = start.elapsed();
+
+                durations.sort();
+                let pct = |p: f64| -> f64 {
+                    let idx = ((durations.len() as f64 * p).ceil() as usize)
+                        .saturating_sub(1)
+                        .min(durations.len() - 1);
+                    durations[idx].as_secs_f64() * 1000.0
+                };
+                let rps = durations.len() as f64 / total.as_secs_f64();
+
+                eprintln!(
+                    "#vm#bench#rps={rps:.1}#p50_ms={:.1}#p95_ms={:.1}#p99_ms={:.1}#",
+                    pct(0.5),
+                    pct(0.95),
+                    pct(0.99),
+                );
+                Ok(())
+            }
+            Self::ObjList {
+                url,
+                token,
+                context,
+                prefix,
+                created_gt,
+                created_lt,
+                desc,
+                limit,
+                state_file,
+                detail,
+                output,
+            } => {
+                use futures::TryStreamExt;
+
+                // in incremental mode, resume from the watermark
+                // persisted by the previous run
+                let mut watermark = created_gt;
+                if let Some(state_file) = &state_file
+                    && let Ok(prev) =
+                        tokio::fs::read_to_string(state_file).await
+                    && let Ok(prev) = prev.trim().parse::<f64>()
+                    && prev > watermark
+                {
+                    watermark = prev;
+                }
+
+                let client =
+                    voidmerge::http_client::HttpClient::new(Default::default())?;
+                let mut count: u32 = 0;
+
+                if detail {
+                    const PAGE_SIZE: u32 = 1000;
+
+                    let mut created_lt = created_lt;
+
+                    println!(
+                        "{:<32} {:>18} {:>18} {:>12} contentType",
+                        "appPath", "createdSecs", "expiresSecs", "sizeBytes",
+                    );
+                    while count < limit {
+                        let page_limit =
+                            std::cmp::min(PAGE_SIZE, limit - count);
+                        let page = client
+                            .obj_list_detailed(
+                                &url, &context, &token, &prefix, watermark,
+                                created_lt, desc, page_limit,
+                            )
+                            .await?;
+                        let full = page.len() as u32 == page_limit;
+                        for entry in page {
+                            count += 1;
+                            if desc {
+                                if created_lt
+                                    .is_none_or(|lt| entry.created_secs < lt)
+                                {
+                                    created_lt = Some(entry.created_secs);
+                                }
+                            } else if entry.created_secs > watermark {
+                                watermark = entry.created_secs;
+                            }
+                            println!(
+                                "{:<32} {:>18} {:>18} {:>12} {}",
+                                entry.app_path,
+                                entry.created_secs,
+                                entry.expires_secs,
+                                entry.size_bytes,
+                                entry.content_type.as_deref().unwrap_or("-"),
+                            );
                         }
+                        if !full {
+                            break;
+                        }
+                    }
+                } else {
+                    let mut stream = std::pin::pin!(client.obj_list_all(
+                        &url, &context, &token, &prefix, watermark,
+                        created_lt, desc,
+                    ));
+                    let mut json_rows = Vec::new();
+                    if output == ListOutput::Csv {
+                        println!(
+                            "sys_prefix,ctx,app_path,created_secs,expires_secs"
+                        );
+                    }
+                    while count < limit {
+                        let r = match stream.try_next().await? {
+                            Some(r) => r,
+                            None => break,
+                        };
                         count += 1;
-                        println!("{r}");
+                        let created_secs = r.created_secs();
+                        if created_secs > watermark {
+                            watermark = created_secs;
+                        }
+                        match output {
+                            ListOutput::Text => println!("{r}"),
+                            ListOutput::Json => json_rows.push(r),
+                            ListOutput::Csv => println!(
+                                "{},{},{},{},{}",
+                                r.split('/').next().unwrap_or(""),
+                                r.ctx(),
+                                r.app_path(),
+                                r.created_secs(),
+                                r.expires_secs(),
+                            ),
+                        }
                     }
+                    if output == ListOutput::Json {
+                        println!("{}", serde_json::to_string(&json_rows)?);
+                    }
+                }
+                // only in text mode so json/csv pipe cleanly
+                if output == ListOutput::Text {
+                    eprintln!("#vm#list-count#{count}#");
+                }
+
+                if let Some(state_file) = &state_file {
+                    tokio::fs::write(state_file, watermark.to_string())
+                        .await?;
                 }
-                eprintln!("#vm#list-count#{count}#");
+
                 Ok(())
             }
             Self::ObjGet {
@@ -630,12 +2160,21 @@ impl Arg {
                 token,
                 context,
                 app_path,
+                format,
             } => {
                 let client =
-                    voidmerge::http_client::HttpClient::new(Default::default());
+                    voidmerge::http_client::HttpClient::new(Default::default())?;
                 let (meta, data) =
                     client.obj_get(&url, &context, &token, &app_path).await?;
                 eprintln!("#vm#meta#{meta}#");
+                let data: bytes::Bytes = match format {
+                    Format::Msgpack => data,
+                    Format::Json => {
+                        use voidmerge::bytes_ext::BytesExt;
+                        let val: serde_json::Value = data.to_decode()?;
+                        serde_json::to_string_pretty(&val)?.into()
+                    }
+                };
                 use tokio::io::AsyncWriteExt;
                 tokio::io::stdout().write_all(&data).await?;
                 Ok(())
@@ -647,30 +2186,97 @@ impl Arg {
                 app_path,
                 create,
                 expire,
+                format,
+                content_type,
             } => {
                 use tokio::io::AsyncReadExt;
                 let mut data = Vec::new();
                 tokio::io::stdin().read_to_end(&mut data).await?;
+                let data: bytes::Bytes = match format {
+                    Format::Msgpack => data.into(),
+                    Format::Json => {
+                        use voidmerge::bytes_ext::BytesExt;
+                        let val: serde_json::Value =
+                            serde_json::from_slice(&data)?;
+                        bytes::Bytes::from_encode(&val)?
+                    }
+                };
                 let meta = crate::obj::ObjMeta(
                     format!("c/{context}/{app_path}/{create}/{expire}").into(),
                 );
                 let client =
-                    voidmerge::http_client::HttpClient::new(Default::default());
-                let meta =
-                    client.obj_put(&url, &token, meta, data.into()).await?;
+                    voidmerge::http_client::HttpClient::new(Default::default())?;
+                let meta = client
+                    .obj_put(&url, &token, meta, data, content_type.as_deref())
+                    .await?;
                 eprintln!("#vm#meta#{meta}#");
                 Ok(())
             }
+            Self::ObjSign {
+                url,
+                token,
+                context,
+                app_path,
+                ttl_secs,
+            } => {
+                let client =
+                    voidmerge::http_client::HttpClient::new(Default::default())?;
+                let signed = client
+                    .obj_sign(
+                        &url,
+                        &context,
+                        &token,
+                        &app_path,
+                        safe_now() + ttl_secs,
+                    )
+                    .await?;
+                println!(
+                    "{}/{}",
+                    url.trim_end_matches('/'),
+                    signed.trim_start_matches('/'),
+                );
+                Ok(())
+            }
+            Self::Fsck { store, repair } => {
+                let report = voidmerge::obj::obj_file::ObjFile::verify(
+                    &store, repair,
+                )
+                .await?;
+                for path in &report.hash_mismatches {
+                    println!("hash-mismatch {}", path.display());
+                }
+                for path in &report.orphaned {
+                    println!("orphaned {}", path.display());
+                }
+                eprintln!(
+                    "#vm#fsck#checked:{}#mismatched:{}#orphaned:{}#repaired:{}#reclaimed:{}#",
+                    report.checked,
+                    report.hash_mismatches.len(),
+                    report.orphaned.len(),
+                    report.repaired,
+                    report.bytes_reclaimed,
+                );
+                // exit nonzero even after a repair: problems were
+                // found, and the operator should check the quarantine
+                if !report.hash_mismatches.is_empty()
+                    || !report.orphaned.is_empty()
+                {
+                    return Err(Error::other(
+                        "obj store verification failed",
+                    ));
+                }
+                Ok(())
+            }
             Self::ObjBackupFull { url, token } => {
                 let client =
-                    voidmerge::http_client::HttpClient::new(Default::default());
+                    voidmerge::http_client::HttpClient::new(Default::default())?;
                 client.obj_backup_full(&url, &token).await?;
                 eprintln!("#vm#obj-backup-full#complete#");
                 Ok(())
             }
             Self::ObjRestoreFull { url, token } => {
                 let client =
-                    voidmerge::http_client::HttpClient::new(Default::default());
+                    voidmerge::http_client::HttpClient::new(Default::default())?;
                 client.obj_restore_full(&url, &token).await?;
                 eprintln!("#vm#obj-restore-full#complete#");
                 Ok(())
@@ -689,10 +2295,13 @@ impl Arg {
                 let mut file = zip::ZipWriter::new(file);
 
                 let client =
-                    voidmerge::http_client::HttpClient::new(Default::default());
+                    voidmerge::http_client::HttpClient::new(Default::default())?;
                 loop {
                     let res = client
-                        .obj_list(&url, &token, &context, "", created_gt, 1000)
+                        .obj_list(
+                            &url, &token, &context, "", created_gt, None,
+                            false, 1000,
+                        )
                         .await?;
                     if res.is_empty() {
                         break;
@@ -742,7 +2351,7 @@ impl Arg {
                 let mut file = zip::ZipArchive::new(file)?;
 
                 let client =
-                    voidmerge::http_client::HttpClient::new(Default::default());
+                    voidmerge::http_client::HttpClient::new(Default::default())?;
                 for idx in 0..file.len() {
                     let (tmp, meta, data) =
                         tokio::task::spawn_blocking(move || {
@@ -765,10 +2374,198 @@ impl Arg {
                     if meta.ctx() != &*context {
                         return Err(Error::other("context mismatch"));
                     }
-                    client.obj_put(&url, &token, meta, data).await?;
+                    let content_type = meta.content_type();
+                    client
+                        .obj_put(
+                            &url,
+                            &token,
+                            meta,
+                            data,
+                            content_type.as_deref(),
+                        )
+                        .await?;
                 }
                 Ok(())
             }
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn arg_parse_comma_splits_list_flags() {
+        let args = minimist::Minimist::parse([
+            "ctx-config",
+            "--url",
+            "u",
+            "--token",
+            "t",
+            "--context",
+            "c",
+            "--ctx-admin",
+            "a,b",
+            "--ctx-admin",
+            "c2",
+        ]);
+        match arg_parse_from(args).unwrap() {
+            Arg::CtxConfig { ctx_admin, .. } => {
+                assert_eq!(
+                    vec![Arc::from("a"), Arc::from("b"), Arc::from("c2")],
+                    ctx_admin,
+                );
+            }
+            arg => panic!("unexpected arg: {arg:?}"),
+        }
+    }
+
+    #[test]
+    fn arg_parse_env_file_aliases_code_env() {
+        let args = minimist::Minimist::parse([
+            "ctx-config",
+            "--url",
+            "u",
+            "--token",
+            "t",
+            "--context",
+            "c",
+            "--env-file",
+            "env.json",
+        ]);
+        match arg_parse_from(args).unwrap() {
+            Arg::CtxConfig { code_env, .. } => {
+                assert_eq!(
+                    Some(std::path::PathBuf::from("env.json")),
+                    code_env,
+                );
+            }
+            arg => panic!("unexpected arg: {arg:?}"),
+        }
+    }
+
+    #[test]
+    fn arg_parse_rejects_unknown_flags_with_suggestion() {
+        let args = minimist::Minimist::parse([
+            "bench",
+            "--url",
+            "u",
+            "--token",
+            "t",
+            "--context",
+            "c",
+            "--concurency",
+            "8",
+        ]);
+        let err = arg_parse_from(args).unwrap_err().to_string();
+        assert!(err.contains("--concurency"), "{err}");
+        assert!(err.contains("--concurrency"), "{err}");
+    }
+
+    #[test]
+    fn arg_parse_rejects_unknown_commands_with_suggestion() {
+        let args = minimist::Minimist::parse(["ctx-lst"]);
+        let err = arg_parse_from(args).unwrap_err().to_string();
+        assert!(err.contains("ctx-lst"), "{err}");
+        assert!(err.contains("ctx-list"), "{err}");
+    }
+
+    #[test]
+    fn arg_parse_validates_numeric_flags() {
+        // a non-numeric value names the offending flag
+        let args = minimist::Minimist::parse([
+            "ctx-setup",
+            "--url",
+            "u",
+            "--token",
+            "t",
+            "--context",
+            "c",
+            "--max-heap-bytes",
+            "banana",
+        ]);
+        let err = arg_parse_from(args).unwrap_err().to_string();
+        assert!(err.contains("--max-heap-bytes"), "{err}");
+
+        // an out-of-range value is rejected, not silently accepted
+        let args = minimist::Minimist::parse([
+            "bench",
+            "--url",
+            "u",
+            "--token",
+            "t",
+            "--context",
+            "c",
+            "--requests",
+            "0",
+        ]);
+        let err = arg_parse_from(args).unwrap_err().to_string();
+        assert!(err.contains("--requests"), "{err}");
+        assert!(err.contains("at least 1"), "{err}");
+    }
+
+    #[test]
+    fn token_file_one_per_line() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tokens");
+        std::fs::write(&path, "alpha\n\n  beta  \ngamma").unwrap();
+
+        let tokens = read_token_file(&path).unwrap();
+        assert_eq!(
+            vec![Arc::from("alpha"), Arc::from("beta"), Arc::from("gamma")],
+            tokens,
+        );
+
+        assert!(read_token_file(&dir.path().join("nope")).is_err());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn serve_config_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("vm.toml");
+        std::fs::write(
+            &path,
+            "sys_admin = [\"file-admin\"]\nhttp_addr = \"127.0.0.1:0\"\n",
+        )
+        .unwrap();
+
+        let config = load_serve_config(Some(&path)).unwrap();
+        assert_eq!(vec!["file-admin".to_string()], config.sys_admin);
+        assert_eq!(Some("127.0.0.1:0".to_string()), config.http_addr);
+        assert!(config.store.is_none());
+
+        // a missing file is ignored
+        let missing =
+            load_serve_config(Some(&dir.path().join("nope.toml"))).unwrap();
+        assert!(missing.sys_admin.is_empty());
+        assert!(missing.http_addr.is_none());
+
+        // an invalid file is an error, not silently ignored
+        std::fs::write(&path, "nope = true\n").unwrap();
+        assert!(load_serve_config(Some(&path)).is_err());
+
+        // start a server from the file values and verify the
+        // sysadmin token taken from the file is honored
+        let (s, r) = tokio::sync::oneshot::channel();
+        tokio::task::spawn(serve(
+            s,
+            vec!["file-admin".into()],
+            "127.0.0.1:0".into(),
+            None,
+            None,
+            60.0,
+            None,
+            false,
+            false,
+        ));
+        let addr = r.await.unwrap();
+        let client =
+            voidmerge::http_client::HttpClient::new(Default::default())
+                .unwrap();
+        client
+            .ctx_list(&format!("http://{addr}/"), "file-admin")
+            .await
+            .unwrap();
+    }
+}
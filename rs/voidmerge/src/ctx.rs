@@ -36,7 +36,14 @@ impl Ctx {
             ctx: ctx.clone(),
             timeout: std::time::Duration::from_secs_f64(setup.timeout_secs),
             heap_size: setup.max_heap_bytes,
+            max_code_bytes: setup
+                .max_code_bytes
+                .map(|b| b as usize)
+                .unwrap_or(crate::js::JsSetup::DEF_MAX_CODE_BYTES),
             code: config.code.clone(),
+            modules: config.code_modules.clone(),
+            entry: config.code_entry.clone(),
+            wasm: config.wasm.clone(),
             env: config.code_env.clone(),
         };
         let mut this = Self {
@@ -97,6 +104,17 @@ impl Ctx {
         Ok(())
     }
 
+    /// Pre-warm `count` js threads for this context, trading startup
+    /// memory for predictable first-request latency.
+    pub async fn warm(&self, count: usize) -> Result<()> {
+        self.js_setup
+            .runtime
+            .js()?
+            .warm(self.js_setup.clone(), count)
+            .await;
+        Ok(())
+    }
+
     /// Process an ObjCheck request.
     pub async fn obj_check_req(
         &self,
@@ -4,6 +4,44 @@ use std::error::Error as StdError;
 pub use std::io::{Error, Result};
 use std::sync::Arc;
 
+/// Structured error codes reported in http error response bodies.
+/// These are stable identifiers clients can match on, independent of
+/// the human-readable message.
+pub mod code {
+    /// A generic or internal error.
+    pub const OTHER: u32 = 1000;
+
+    /// An item was not found.
+    pub const NOT_FOUND: u32 = 1001;
+
+    /// Unauthorized access.
+    pub const UNAUTHORIZED: u32 = 1002;
+
+    /// Invalid input.
+    pub const INVALID: u32 = 1003;
+
+    /// A quota or capacity limit was exceeded.
+    pub const QUOTA: u32 = 1004;
+
+    /// A size limit was exceeded.
+    pub const TOO_LARGE: u32 = 1005;
+
+    /// A transient conflict; the operation should be retried.
+    pub const RETRY: u32 = 1006;
+}
+
+/// Error wrapper recording a structured [code] alongside the source.
+#[derive(Debug)]
+struct Coded(u32, Box<dyn StdError + Send + Sync>);
+
+impl std::fmt::Display for Coded {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.1.fmt(f)
+    }
+}
+
+impl StdError for Coded {}
+
 /// Convenience extension trait helpers for error types.
 pub trait ErrorExt: Send + Sync {
     /// Convert to a clonable type.
@@ -15,6 +53,14 @@ pub trait ErrorExt: Send + Sync {
         info: impl Into<Box<dyn StdError + Send + Sync>>,
     ) -> Error;
 
+    /// Record a structured [code] on the error, surfaced in http
+    /// error response bodies. Apply this last; further wrapping
+    /// (e.g. [ErrorExt::with_info]) discards the code.
+    fn with_code(self, code: u32) -> Error;
+
+    /// Get the structured [code] recorded on the error, if any.
+    fn code(&self) -> Option<u32>;
+
     /// An error indicating an operation took too long.
     fn timeout(src: impl Into<Box<dyn StdError + Send + Sync>>) -> Error;
 
@@ -26,6 +72,9 @@ pub trait ErrorExt: Send + Sync {
 
     /// Invalid input.
     fn invalid(src: impl Into<Box<dyn StdError + Send + Sync>>) -> Error;
+
+    /// An error indicating a quota or capacity limit was exceeded.
+    fn quota(src: impl Into<Box<dyn StdError + Send + Sync>>) -> Error;
 }
 
 impl ErrorExt for Error {
@@ -64,6 +113,25 @@ impl ErrorExt for Error {
         std::io::Error::new(kind, err)
     }
 
+    fn with_code(self, code: u32) -> Error {
+        let kind = self.kind();
+        let err =
+            Coded(code, self.into_inner().unwrap_or_else(|| "none".into()));
+        std::io::Error::new(kind, err)
+    }
+
+    fn code(&self) -> Option<u32> {
+        let mut src: Option<&(dyn StdError + 'static)> =
+            self.get_ref().map(|err| err as &(dyn StdError + 'static));
+        while let Some(err) = src {
+            if let Some(Coded(code, _)) = err.downcast_ref::<Coded>() {
+                return Some(*code);
+            }
+            src = err.source();
+        }
+        None
+    }
+
     fn timeout(src: impl Into<Box<dyn StdError + Send + Sync>>) -> Error {
         std::io::Error::new(std::io::ErrorKind::TimedOut, src)
     }
@@ -79,6 +147,10 @@ impl ErrorExt for Error {
     fn invalid(src: impl Into<Box<dyn StdError + Send + Sync>>) -> Error {
         std::io::Error::new(std::io::ErrorKind::InvalidInput, src)
     }
+
+    fn quota(src: impl Into<Box<dyn StdError + Send + Sync>>) -> Error {
+        std::io::Error::new(std::io::ErrorKind::QuotaExceeded, src)
+    }
 }
 
 #[cfg(test)]
@@ -90,4 +162,15 @@ mod test {
         eprintln!("{}", Error::timeout("test1").with_info("hello"));
         eprintln!("{:?}", Error::timeout("test2").with_info("world"));
     }
+
+    #[test]
+    fn error_code() {
+        let err = Error::not_found("missing").with_code(code::NOT_FOUND);
+        assert_eq!(Some(code::NOT_FOUND), err.code());
+        // the kind and message are preserved
+        assert_eq!(std::io::ErrorKind::NotFound, err.kind());
+        assert!(err.to_string().contains("missing"));
+        // errors without an explicit code report none
+        assert_eq!(None, Error::other("plain").code());
+    }
 }
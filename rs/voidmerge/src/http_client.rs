@@ -3,22 +3,188 @@
 use crate::*;
 use bytes::Bytes;
 
+/// Retry configuration for an [HttpClient] instance.
+#[derive(Debug, Clone)]
+pub struct RetryConfig {
+    /// Max number of attempts before giving up. Default: 1 (no retry).
+    pub max_attempts: u32,
+
+    /// Base delay for exponential backoff between attempts.
+    /// Default: 100ms.
+    pub base_delay: std::time::Duration,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: 1,
+            base_delay: std::time::Duration::from_millis(100),
+        }
+    }
+}
+
 /// Configuration for an [HttpClient] instance.
-#[derive(Default)]
+#[derive(Debug, Clone)]
 #[non_exhaustive]
-pub struct HttpClientConfig {}
+pub struct HttpClientConfig {
+    /// Timeout applied to each individual request. Default: 30s.
+    pub request_timeout: std::time::Duration,
+
+    /// Retry configuration. Default: a single attempt, no retry.
+    pub retry: RetryConfig,
+
+    /// Client certificate and private key PEM file paths, presented
+    /// as this client's identity for mutual TLS. Only relevant for
+    /// mTLS setups; has no effect on plain http connections.
+    /// Default: None.
+    pub client_identity: Option<(std::path::PathBuf, std::path::PathBuf)>,
+
+    /// Additional trusted CA certificate PEM file path, e.g. for a
+    /// private CA issuing the server certificates in an mTLS setup.
+    /// Has no effect on plain http connections. Default: None.
+    pub tls_ca_cert: Option<std::path::PathBuf>,
+
+    /// Headers applied to every request, e.g. access tokens required
+    /// by a proxy or WAF in front of the server. Headers set by
+    /// individual api calls (such as `Authorization`) take precedence.
+    /// Default: empty.
+    pub default_headers: std::collections::HashMap<String, String>,
+
+    /// Route all requests through this http proxy url.
+    /// Default: None.
+    pub proxy: Option<String>,
+
+    /// Skip server certificate verification. Only intended for testing
+    /// against internal CAs; prefer [HttpClientConfig::tls_ca_cert]
+    /// where possible. Default: false.
+    pub accept_invalid_certs: bool,
+}
+
+impl Default for HttpClientConfig {
+    fn default() -> Self {
+        Self {
+            request_timeout: std::time::Duration::from_secs(30),
+            retry: RetryConfig::default(),
+            client_identity: None,
+            tls_ca_cert: None,
+            default_headers: std::collections::HashMap::new(),
+            proxy: None,
+            accept_invalid_certs: false,
+        }
+    }
+}
 
 /// VoidMerge http client.
 pub struct HttpClient {
     client: reqwest::Client,
+    retry: RetryConfig,
 }
 
 impl HttpClient {
     /// Construct a new [HttpClient].
-    pub fn new(config: HttpClientConfig) -> Self {
-        let _config = config;
-        Self {
-            client: reqwest::Client::new(),
+    pub fn new(config: HttpClientConfig) -> Result<Self> {
+        let mut headers = reqwest::header::HeaderMap::new();
+        headers.insert(
+            reqwest::header::USER_AGENT,
+            reqwest::header::HeaderValue::from_static(concat!(
+                "voidmerge/",
+                env!("CARGO_PKG_VERSION"),
+            )),
+        );
+        for (k, v) in config.default_headers.iter() {
+            let k = reqwest::header::HeaderName::from_bytes(k.as_bytes())
+                .map_err(|err| {
+                    Error::invalid(format!("invalid header name {k}: {err}"))
+                })?;
+            let v =
+                reqwest::header::HeaderValue::from_str(v).map_err(|err| {
+                    Error::invalid(format!("invalid header value: {err}"))
+                })?;
+            headers.insert(k, v);
+        }
+
+        let mut builder = reqwest::Client::builder()
+            .timeout(config.request_timeout)
+            .default_headers(headers);
+
+        if let Some(proxy) = &config.proxy {
+            let proxy = reqwest::Proxy::all(proxy).map_err(|err| {
+                Error::invalid(format!("invalid proxy {proxy}: {err}"))
+            })?;
+            builder = builder.proxy(proxy);
+        }
+
+        if config.accept_invalid_certs {
+            builder = builder.danger_accept_invalid_certs(true);
+        }
+
+        if let Some((cert, key)) = &config.client_identity {
+            let cert = std::fs::read(cert)?;
+            let key = std::fs::read(key)?;
+            let identity = reqwest::Identity::from_pkcs8_pem(&cert, &key)
+                .map_err(std::io::Error::other)?;
+            builder = builder.identity(identity);
+        }
+
+        if let Some(ca) = &config.tls_ca_cert {
+            let ca = std::fs::read(ca)?;
+            let ca = reqwest::Certificate::from_pem(&ca)
+                .map_err(std::io::Error::other)?;
+            builder = builder.add_root_certificate(ca);
+        }
+
+        let client = builder.build().map_err(std::io::Error::other)?;
+        Ok(Self {
+            client,
+            retry: config.retry,
+        })
+    }
+
+    /// Send a request, retrying failures and server errors with
+    /// exponential backoff and jitter up to `retry.max_attempts`.
+    async fn send_with_retry(
+        &self,
+        req: reqwest::Request,
+    ) -> Result<reqwest::Response> {
+        let max_attempts = std::cmp::max(1, self.retry.max_attempts);
+
+        if req.try_clone().is_none() {
+            // a streaming body cannot be cloned for retry
+            return self
+                .client
+                .execute(req)
+                .await
+                .map_err(std::io::Error::other);
+        }
+
+        let mut attempt = 1;
+        loop {
+            let res = self.client.execute(req.try_clone().unwrap()).await;
+
+            match res {
+                Ok(res) => {
+                    if attempt >= max_attempts
+                        || !res.status().is_server_error()
+                    {
+                        return Ok(res);
+                    }
+                }
+                Err(err) => {
+                    if attempt >= max_attempts {
+                        return Err(std::io::Error::other(err));
+                    }
+                }
+            }
+
+            let backoff = self
+                .retry
+                .base_delay
+                .saturating_mul(1 << std::cmp::min(attempt - 1, 16));
+            use rand::Rng;
+            let jitter = backoff.mul_f64(rand::rng().random_range(0.0..0.5));
+            tokio::time::sleep(backoff + jitter).await;
+
+            attempt += 1;
         }
     }
 
@@ -27,9 +193,39 @@ impl HttpClient {
         let mut url: reqwest::Url =
             url.parse().map_err(std::io::Error::other)?;
         url.set_path("");
+        let req = self
+            .client
+            .get(url)
+            .build()
+            .map_err(std::io::Error::other)?;
+        let res = self.send_with_retry(req).await?;
+        if res.error_for_status_ref().is_err() {
+            return Err(std::io::Error::other(
+                res.text().await.map_err(std::io::Error::other)?,
+            ));
+        }
+        Ok(())
+    }
+
+    /// Execute a function request GET against the root path of a
+    /// context, returning the response body. Function requests are
+    /// not retried, they are not idempotent in general; the caller
+    /// owns any retry semantics.
+    pub async fn fn_get(
+        &self,
+        url: &str,
+        token: &str,
+        ctx: &str,
+    ) -> Result<Bytes> {
+        safe_str(ctx)?;
+        let mut url: reqwest::Url =
+            url.parse().map_err(std::io::Error::other)?;
+        url.set_path(&format!("{ctx}/"));
+        let token = format!("Bearer {}", &token);
         let res = self
             .client
             .get(url)
+            .header("Authorization", token)
             .send()
             .await
             .map_err(std::io::Error::other)?;
@@ -38,7 +234,7 @@ impl HttpClient {
                 res.text().await.map_err(std::io::Error::other)?,
             ));
         }
-        Ok(())
+        res.bytes().await.map_err(std::io::Error::other)
     }
 
     /// Setup a context on a VoidMerge server.
@@ -52,14 +248,14 @@ impl HttpClient {
             url.parse().map_err(std::io::Error::other)?;
         url.set_path("ctx-setup");
         let token = format!("Bearer {}", &token);
-        let res = self
+        let req = self
             .client
             .put(url)
             .header("Authorization", token)
             .body(Bytes::from_encode(&ctx_setup)?)
-            .send()
-            .await
+            .build()
             .map_err(std::io::Error::other)?;
+        let res = self.send_with_retry(req).await?;
         if res.error_for_status_ref().is_err() {
             return Err(std::io::Error::other(
                 res.text().await.map_err(std::io::Error::other)?,
@@ -79,14 +275,413 @@ impl HttpClient {
             url.parse().map_err(std::io::Error::other)?;
         url.set_path(&format!("{}/_vm_/config", &ctx_config.ctx));
         let token = format!("Bearer {}", &token);
-        let res = self
+        let req = self
             .client
             .put(url)
             .header("Authorization", token)
             .body(Bytes::from_encode(&ctx_config)?)
-            .send()
-            .await
+            .build()
+            .map_err(std::io::Error::other)?;
+        let res = self.send_with_retry(req).await?;
+        if res.error_for_status_ref().is_err() {
+            return Err(std::io::Error::other(
+                res.text().await.map_err(std::io::Error::other)?,
+            ));
+        }
+        Ok(())
+    }
+
+    /// Call the admin config versions api on a VoidMerge server,
+    /// listing the stored historical config versions for a context.
+    pub async fn ctx_config_versions(
+        &self,
+        url: &str,
+        ctx: &str,
+        token: &str,
+    ) -> Result<Vec<crate::server::CtxConfigVersion>> {
+        safe_str(ctx)?;
+        let mut url: reqwest::Url =
+            url.parse().map_err(std::io::Error::other)?;
+        url.set_path(&format!("{ctx}/_vm_/config/versions"));
+        let token = format!("Bearer {}", &token);
+        let req = self
+            .client
+            .get(url)
+            .header("Authorization", token)
+            .build()
+            .map_err(std::io::Error::other)?;
+        let res = self.send_with_retry(req).await?;
+        if res.error_for_status_ref().is_err() {
+            return Err(std::io::Error::other(
+                res.text().await.map_err(std::io::Error::other)?,
+            ));
+        }
+        let res = res.bytes().await.map_err(std::io::Error::other)?;
+        #[derive(serde::Deserialize)]
+        struct R {
+            #[serde(rename = "versions")]
+            versions: Vec<crate::server::CtxConfigVersion>,
+        }
+        let res: R = res.to_decode()?;
+        Ok(res.versions)
+    }
+
+    /// Call the admin config rollback api on a VoidMerge server,
+    /// re-applying a previously stored config version.
+    pub async fn ctx_config_rollback(
+        &self,
+        url: &str,
+        ctx: &str,
+        token: &str,
+        version: u64,
+    ) -> Result<()> {
+        safe_str(ctx)?;
+        let mut url: reqwest::Url =
+            url.parse().map_err(std::io::Error::other)?;
+        url.set_path(&format!("{ctx}/_vm_/config/rollback/{version}"));
+        let token = format!("Bearer {}", &token);
+        let req = self
+            .client
+            .put(url)
+            .header("Authorization", token)
+            .build()
+            .map_err(std::io::Error::other)?;
+        let res = self.send_with_retry(req).await?;
+        if res.error_for_status_ref().is_err() {
+            return Err(std::io::Error::other(
+                res.text().await.map_err(std::io::Error::other)?,
+            ));
+        }
+        Ok(())
+    }
+
+    /// Apply a partial update to a context's setup and config on a
+    /// VoidMerge server in a single call. `None` fields in the
+    /// [crate::server::CtxPut] are left unchanged; empty lists clear.
+    pub async fn ctx_put(
+        &self,
+        url: &str,
+        token: &str,
+        ctx_put: crate::server::CtxPut,
+    ) -> Result<()> {
+        safe_str(&ctx_put.ctx)?;
+        let mut url: reqwest::Url =
+            url.parse().map_err(std::io::Error::other)?;
+        url.set_path(&format!("{}/_vm_/context", &ctx_put.ctx));
+        let token = format!("Bearer {}", &token);
+        let req = self
+            .client
+            .put(url)
+            .header("Authorization", token)
+            .body(Bytes::from_encode(&ctx_put)?)
+            .build()
+            .map_err(std::io::Error::other)?;
+        let res = self.send_with_retry(req).await?;
+        if res.error_for_status_ref().is_err() {
+            return Err(std::io::Error::other(
+                res.text().await.map_err(std::io::Error::other)?,
+            ));
+        }
+        Ok(())
+    }
+
+    /// Clone a context from an existing template context on a
+    /// VoidMerge server.
+    pub async fn ctx_clone(
+        &self,
+        url: &str,
+        token: &str,
+        src_ctx: &str,
+        dst_ctx: &str,
+    ) -> Result<()> {
+        safe_str(src_ctx)?;
+        safe_str(dst_ctx)?;
+        let mut url: reqwest::Url =
+            url.parse().map_err(std::io::Error::other)?;
+        url.set_path("ctx-clone");
+        let token = format!("Bearer {}", &token);
+        #[derive(serde::Serialize)]
+        struct I<'lt> {
+            #[serde(rename = "s")]
+            src_ctx: &'lt str,
+            #[serde(rename = "d")]
+            dst_ctx: &'lt str,
+        }
+        let req = self
+            .client
+            .post(url)
+            .header("Authorization", token)
+            .body(Bytes::from_encode(&I { src_ctx, dst_ctx })?)
+            .build()
+            .map_err(std::io::Error::other)?;
+        let res = self.send_with_retry(req).await?;
+        if res.error_for_status_ref().is_err() {
+            return Err(std::io::Error::other(
+                res.text().await.map_err(std::io::Error::other)?,
+            ));
+        }
+        Ok(())
+    }
+
+    /// Atomically add a single context admin token on a VoidMerge
+    /// server, returning the resulting token list.
+    pub async fn ctx_admin_add(
+        &self,
+        url: &str,
+        token: &str,
+        ctx: &str,
+        add: &str,
+    ) -> Result<Vec<Arc<str>>> {
+        self.ctx_admin(url, token, ctx, Some(add), None).await
+    }
+
+    /// Atomically remove a single context admin token on a VoidMerge
+    /// server, returning the resulting token list.
+    pub async fn ctx_admin_remove(
+        &self,
+        url: &str,
+        token: &str,
+        ctx: &str,
+        remove: &str,
+    ) -> Result<Vec<Arc<str>>> {
+        self.ctx_admin(url, token, ctx, None, Some(remove)).await
+    }
+
+    async fn ctx_admin(
+        &self,
+        url: &str,
+        token: &str,
+        ctx: &str,
+        add: Option<&str>,
+        remove: Option<&str>,
+    ) -> Result<Vec<Arc<str>>> {
+        safe_str(ctx)?;
+        let mut url: reqwest::Url =
+            url.parse().map_err(std::io::Error::other)?;
+        url.set_path("ctx-admin");
+        let token = format!("Bearer {}", &token);
+        #[derive(serde::Serialize)]
+        struct I<'lt> {
+            #[serde(rename = "c")]
+            ctx: &'lt str,
+            #[serde(rename = "a")]
+            add: Option<&'lt str>,
+            #[serde(rename = "r")]
+            remove: Option<&'lt str>,
+        }
+        let req = self
+            .client
+            .patch(url)
+            .header("Authorization", token)
+            .body(Bytes::from_encode(&I { ctx, add, remove })?)
+            .build()
+            .map_err(std::io::Error::other)?;
+        let res = self.send_with_retry(req).await?;
+        if res.error_for_status_ref().is_err() {
+            return Err(std::io::Error::other(
+                res.text().await.map_err(std::io::Error::other)?,
+            ));
+        }
+        let res = res.bytes().await.map_err(std::io::Error::other)?;
+        #[derive(serde::Deserialize)]
+        struct R {
+            #[serde(rename = "ctxAdmin")]
+            ctx_admin: Vec<Arc<str>>,
+        }
+        let res: R = res.to_decode()?;
+        Ok(res.ctx_admin)
+    }
+
+    /// Atomically rotate a context's admin tokens on a VoidMerge
+    /// server, adding then removing in a single call, returning the
+    /// resulting token list. Permitted for ctxadmins; removing the
+    /// last remaining token requires a sysadmin token.
+    pub async fn ctx_tokens(
+        &self,
+        url: &str,
+        ctx: &str,
+        token: &str,
+        tokens: &crate::server::CtxTokens,
+    ) -> Result<Vec<Arc<str>>> {
+        safe_str(ctx)?;
+        let mut url: reqwest::Url =
+            url.parse().map_err(std::io::Error::other)?;
+        url.set_path(&format!("{ctx}/_vm_/tokens"));
+        let token = format!("Bearer {}", &token);
+        let req = self
+            .client
+            .put(url)
+            .header("Authorization", token)
+            .body(Bytes::from_encode(tokens)?)
+            .build()
+            .map_err(std::io::Error::other)?;
+        let res = self.send_with_retry(req).await?;
+        if res.error_for_status_ref().is_err() {
+            return Err(std::io::Error::other(
+                res.text().await.map_err(std::io::Error::other)?,
+            ));
+        }
+        let res = res.bytes().await.map_err(std::io::Error::other)?;
+        #[derive(serde::Deserialize)]
+        struct R {
+            #[serde(rename = "ctxAdmin")]
+            ctx_admin: Vec<Arc<str>>,
+        }
+        let res: R = res.to_decode()?;
+        Ok(res.ctx_admin)
+    }
+
+    /// Call the admin revalidate api on a VoidMerge server, running
+    /// one page of stored objects through the context's ObjCheck
+    /// handler. Use the report's continuation as the next
+    /// `created_gt` to resume.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn ctx_revalidate(
+        &self,
+        url: &str,
+        ctx: &str,
+        token: &str,
+        app_path_prefix: &str,
+        created_gt: f64,
+        limit: u32,
+        delete_failures: bool,
+    ) -> Result<crate::server::RevalidateReport> {
+        safe_str(ctx)?;
+        if !app_path_prefix.is_empty() {
+            safe_str(app_path_prefix)?;
+        }
+        let mut url: reqwest::Url =
+            url.parse().map_err(std::io::Error::other)?;
+        url.set_path(&format!("{ctx}/_vm_/revalidate"));
+        let token = format!("Bearer {}", &token);
+        #[derive(serde::Serialize)]
+        struct I<'lt> {
+            #[serde(rename = "p")]
+            prefix: &'lt str,
+            #[serde(rename = "g")]
+            created_gt: f64,
+            #[serde(rename = "l")]
+            limit: u32,
+            #[serde(rename = "d")]
+            delete_failures: bool,
+        }
+        let req = self
+            .client
+            .put(url)
+            .header("Authorization", token)
+            .body(Bytes::from_encode(&I {
+                prefix: app_path_prefix,
+                created_gt,
+                limit,
+                delete_failures,
+            })?)
+            .build()
+            .map_err(std::io::Error::other)?;
+        let res = self.send_with_retry(req).await?;
+        if res.error_for_status_ref().is_err() {
+            return Err(std::io::Error::other(
+                res.text().await.map_err(std::io::Error::other)?,
+            ));
+        }
+        let res = res.bytes().await.map_err(std::io::Error::other)?;
+        res.to_decode()
+    }
+
+    /// Call the admin obj-query api on a VoidMerge server, listing
+    /// objects across multiple app path prefixes in a single round
+    /// trip, optionally with data.
+    pub async fn obj_query(
+        &self,
+        url: &str,
+        ctx: &str,
+        token: &str,
+        query: &crate::server::ObjQuery,
+    ) -> Result<crate::server::ObjQueryResponse> {
+        safe_str(ctx)?;
+        let mut url: reqwest::Url =
+            url.parse().map_err(std::io::Error::other)?;
+        url.set_path(&format!("{ctx}/_vm_/obj-query"));
+        let token = format!("Bearer {}", &token);
+        let req = self
+            .client
+            .put(url)
+            .header("Authorization", token)
+            .body(Bytes::from_encode(query)?)
+            .build()
             .map_err(std::io::Error::other)?;
+        let res = self.send_with_retry(req).await?;
+        if res.error_for_status_ref().is_err() {
+            return Err(std::io::Error::other(
+                res.text().await.map_err(std::io::Error::other)?,
+            ));
+        }
+        let res = res.bytes().await.map_err(std::io::Error::other)?;
+        res.to_decode()
+    }
+
+    /// Call the sysadmin ctx-list api on a VoidMerge server.
+    pub async fn ctx_list(
+        &self,
+        url: &str,
+        token: &str,
+    ) -> Result<Vec<crate::server::CtxListEntry>> {
+        let mut url: reqwest::Url =
+            url.parse().map_err(std::io::Error::other)?;
+        url.set_path("_vm_/ctx-list");
+        let token = format!("Bearer {}", &token);
+        let req = self
+            .client
+            .get(url)
+            .header("Authorization", token)
+            .build()
+            .map_err(std::io::Error::other)?;
+        let res = self.send_with_retry(req).await?;
+        if res.error_for_status_ref().is_err() {
+            return Err(std::io::Error::other(
+                res.text().await.map_err(std::io::Error::other)?,
+            ));
+        }
+        let res = res.bytes().await.map_err(std::io::Error::other)?;
+        #[derive(serde::Deserialize)]
+        struct R {
+            #[serde(rename = "ctxList")]
+            ctx_list: Vec<crate::server::CtxListEntry>,
+        }
+        let res: R = res.to_decode()?;
+        Ok(res.ctx_list)
+    }
+
+    /// Map a hostname to a context for host-based routing on a
+    /// VoidMerge server. An empty ctx removes the mapping.
+    pub async fn set_domain(
+        &self,
+        url: &str,
+        token: &str,
+        host: &str,
+        ctx: &str,
+    ) -> Result<()> {
+        if !ctx.is_empty() {
+            safe_str(ctx)?;
+        }
+        let mut url: reqwest::Url =
+            url.parse().map_err(std::io::Error::other)?;
+        url.set_path("_vm_/domains");
+        let token = format!("Bearer {}", &token);
+        #[derive(serde::Serialize)]
+        struct I<'lt> {
+            #[serde(rename = "h")]
+            host: &'lt str,
+            #[serde(rename = "c")]
+            ctx: &'lt str,
+        }
+        let req = self
+            .client
+            .put(url)
+            .header("Authorization", token)
+            .body(Bytes::from_encode(&I { host, ctx })?)
+            .build()
+            .map_err(std::io::Error::other)?;
+        let res = self.send_with_retry(req).await?;
         if res.error_for_status_ref().is_err() {
             return Err(std::io::Error::other(
                 res.text().await.map_err(std::io::Error::other)?,
@@ -95,6 +690,39 @@ impl HttpClient {
         Ok(())
     }
 
+    /// List the hostname to context routing map on a VoidMerge
+    /// server.
+    pub async fn list_domains(
+        &self,
+        url: &str,
+        token: &str,
+    ) -> Result<std::collections::HashMap<Arc<str>, Arc<str>>> {
+        let mut url: reqwest::Url =
+            url.parse().map_err(std::io::Error::other)?;
+        url.set_path("_vm_/domains");
+        let token = format!("Bearer {}", &token);
+        let req = self
+            .client
+            .get(url)
+            .header("Authorization", token)
+            .build()
+            .map_err(std::io::Error::other)?;
+        let res = self.send_with_retry(req).await?;
+        if res.error_for_status_ref().is_err() {
+            return Err(std::io::Error::other(
+                res.text().await.map_err(std::io::Error::other)?,
+            ));
+        }
+        let res = res.bytes().await.map_err(std::io::Error::other)?;
+        #[derive(serde::Deserialize)]
+        struct R {
+            #[serde(rename = "domains")]
+            domains: std::collections::HashMap<Arc<str>, Arc<str>>,
+        }
+        let res: R = res.to_decode()?;
+        Ok(res.domains)
+    }
+
     /// Call the admin obj-list api on a VoidMerge server.
     pub async fn obj_list(
         &self,
@@ -103,6 +731,8 @@ impl HttpClient {
         token: &str,
         app_path_prefix: &str,
         created_gt: f64,
+        created_lt: Option<f64>,
+        descending: bool,
         limit: u32,
     ) -> Result<Vec<crate::obj::ObjMeta>> {
         safe_str(ctx)?;
@@ -110,18 +740,26 @@ impl HttpClient {
         let mut url: reqwest::Url =
             url.parse().map_err(std::io::Error::other)?;
         url.set_path(&format!("{ctx}/_vm_/obj-list/{app_path_prefix}"));
-        url.query_pairs_mut()
-            .clear()
-            .append_pair("created-gt", &created_gt.to_string())
-            .append_pair("limit", &limit.to_string());
+        {
+            let mut q = url.query_pairs_mut();
+            q.clear()
+                .append_pair("created-gt", &created_gt.to_string())
+                .append_pair("limit", &limit.to_string());
+            if let Some(created_lt) = created_lt {
+                q.append_pair("created-lt", &created_lt.to_string());
+            }
+            if descending {
+                q.append_pair("desc", "true");
+            }
+        }
         let token = format!("Bearer {}", &token);
-        let res = self
+        let req = self
             .client
             .get(url)
             .header("Authorization", token)
-            .send()
-            .await
+            .build()
             .map_err(std::io::Error::other)?;
+        let res = self.send_with_retry(req).await?;
         if res.error_for_status_ref().is_err() {
             return Err(std::io::Error::other(
                 res.text().await.map_err(std::io::Error::other)?,
@@ -137,6 +775,131 @@ impl HttpClient {
         Ok(res.meta_list)
     }
 
+    /// Call the admin obj-list api on a VoidMerge server with
+    /// `detail=true`, returning structured entries instead of raw
+    /// meta path strings.
+    pub async fn obj_list_detailed(
+        &self,
+        url: &str,
+        ctx: &str,
+        token: &str,
+        app_path_prefix: &str,
+        created_gt: f64,
+        created_lt: Option<f64>,
+        descending: bool,
+        limit: u32,
+    ) -> Result<Vec<crate::obj::ObjListEntry>> {
+        safe_str(ctx)?;
+        safe_str(app_path_prefix)?;
+        let mut url: reqwest::Url =
+            url.parse().map_err(std::io::Error::other)?;
+        url.set_path(&format!("{ctx}/_vm_/obj-list/{app_path_prefix}"));
+        {
+            let mut q = url.query_pairs_mut();
+            q.clear()
+                .append_pair("created-gt", &created_gt.to_string())
+                .append_pair("limit", &limit.to_string())
+                .append_pair("detail", "true");
+            if let Some(created_lt) = created_lt {
+                q.append_pair("created-lt", &created_lt.to_string());
+            }
+            if descending {
+                q.append_pair("desc", "true");
+            }
+        }
+        let token = format!("Bearer {}", &token);
+        let req = self
+            .client
+            .get(url)
+            .header("Authorization", token)
+            .build()
+            .map_err(std::io::Error::other)?;
+        let res = self.send_with_retry(req).await?;
+        if res.error_for_status_ref().is_err() {
+            return Err(std::io::Error::other(
+                res.text().await.map_err(std::io::Error::other)?,
+            ));
+        }
+        let res = res.bytes().await.map_err(std::io::Error::other)?;
+        #[derive(serde::Deserialize)]
+        struct R {
+            #[serde(rename = "metaList")]
+            meta_list: Vec<crate::obj::ObjListEntry>,
+        }
+        let res: R = res.to_decode()?;
+        Ok(res.meta_list)
+    }
+
+    /// Call the admin obj-list api on a VoidMerge server, automatically
+    /// issuing follow-up requests to page through the full result set.
+    /// Whenever a response comes back with a full page (1000 items),
+    /// another request is made with `created-gt` advanced past the
+    /// items already seen (`created-lt` lowered below them, when
+    /// `descending`).
+    pub fn obj_list_all<'a>(
+        &'a self,
+        url: &'a str,
+        ctx: &'a str,
+        token: &'a str,
+        app_path_prefix: &'a str,
+        created_gt: f64,
+        created_lt: Option<f64>,
+        descending: bool,
+    ) -> impl futures::Stream<Item = Result<crate::obj::ObjMeta>> + 'a {
+        use futures::TryStreamExt;
+
+        const PAGE_SIZE: u32 = 1000;
+
+        futures::stream::try_unfold(
+            (created_gt, created_lt, false),
+            move |(mut created_gt, mut created_lt, done)| async move {
+                if done {
+                    return Ok(None);
+                }
+
+                let page = self
+                    .obj_list(
+                        url,
+                        ctx,
+                        token,
+                        app_path_prefix,
+                        created_gt,
+                        created_lt,
+                        descending,
+                        PAGE_SIZE,
+                    )
+                    .await?;
+
+                if page.is_empty() {
+                    return Ok(None);
+                }
+
+                // the server over-returns past the limit when the page
+                // boundary lands on a created_secs tie, so an over-full
+                // page still means there may be more: all ties were
+                // emitted, making it safe to resume past the boundary
+                let full = page.len() as u32 >= PAGE_SIZE;
+
+                for meta in page.iter() {
+                    let created_secs = meta.created_secs();
+                    if descending {
+                        if created_lt.is_none_or(|lt| created_secs < lt) {
+                            created_lt = Some(created_secs);
+                        }
+                    } else if created_secs > created_gt {
+                        created_gt = created_secs;
+                    }
+                }
+
+                Ok(Some((
+                    futures::stream::iter(page.into_iter().map(Ok)),
+                    (created_gt, created_lt, !full),
+                )))
+            },
+        )
+        .try_flatten()
+    }
+
     /// Call the admin obj-get api on a VoidMerge server.
     pub async fn obj_get(
         &self,
@@ -151,13 +914,13 @@ impl HttpClient {
             url.parse().map_err(std::io::Error::other)?;
         url.set_path(&format!("{ctx}/_vm_/obj-get/{app_path}"));
         let token = format!("Bearer {}", &token);
-        let res = self
+        let req = self
             .client
             .get(url)
             .header("Authorization", token)
-            .send()
-            .await
+            .build()
             .map_err(std::io::Error::other)?;
+        let res = self.send_with_retry(req).await?;
         if res.error_for_status_ref().is_err() {
             return Err(std::io::Error::other(
                 res.text().await.map_err(std::io::Error::other)?,
@@ -173,7 +936,45 @@ impl HttpClient {
         Ok((res.meta, res.data))
     }
 
-    /// Call the admin obj-put api on a VoidMerge server.
+    /// Call the admin obj-sign api on a VoidMerge server, returning a
+    /// server-relative url granting unauthenticated read access to a
+    /// single object until `expires_secs` (absolute unix epoch
+    /// seconds).
+    pub async fn obj_sign(
+        &self,
+        url: &str,
+        ctx: &str,
+        token: &str,
+        app_path: &str,
+        expires_secs: f64,
+    ) -> Result<String> {
+        safe_str(ctx)?;
+        safe_str(app_path)?;
+        let mut url: reqwest::Url =
+            url.parse().map_err(std::io::Error::other)?;
+        url.set_path(&format!("{ctx}/_vm_/obj-sign/{app_path}"));
+        url.query_pairs_mut()
+            .clear()
+            .append_pair("expires", &expires_secs.to_string());
+        let token = format!("Bearer {}", &token);
+        let req = self
+            .client
+            .post(url)
+            .header("Authorization", token)
+            .build()
+            .map_err(std::io::Error::other)?;
+        let res = self.send_with_retry(req).await?;
+        if res.error_for_status_ref().is_err() {
+            return Err(std::io::Error::other(
+                res.text().await.map_err(std::io::Error::other)?,
+            ));
+        }
+        res.text().await.map_err(std::io::Error::other)
+    }
+
+    /// Call the admin obj-put api on a VoidMerge server. An optional
+    /// content type is recorded with the object and served back by the
+    /// raw api.
     #[allow(clippy::too_many_arguments)]
     pub async fn obj_put(
         &self,
@@ -181,6 +982,7 @@ impl HttpClient {
         token: &str,
         meta: crate::obj::ObjMeta,
         data: bytes::Bytes,
+        content_type: Option<&str>,
     ) -> Result<crate::obj::ObjMeta> {
         let mut url: reqwest::Url =
             url.parse().map_err(std::io::Error::other)?;
@@ -191,14 +993,12 @@ impl HttpClient {
         let rest = iter.next().unwrap_or("");
         url.set_path(&format!("{ctx}/_vm_/obj-put/{rest}"));
         let token = format!("Bearer {}", &token);
-        let res = self
-            .client
-            .put(url)
-            .header("Authorization", token)
-            .body(data)
-            .send()
-            .await
-            .map_err(std::io::Error::other)?;
+        let mut req = self.client.put(url).header("Authorization", token);
+        if let Some(content_type) = content_type {
+            req = req.header("Content-Type", content_type);
+        }
+        let req = req.body(data).build().map_err(std::io::Error::other)?;
+        let res = self.send_with_retry(req).await?;
         if res.error_for_status_ref().is_err() {
             return Err(std::io::Error::other(
                 res.text().await.map_err(std::io::Error::other)?,
@@ -214,13 +1014,13 @@ impl HttpClient {
             url.parse().map_err(std::io::Error::other)?;
         url.set_path("_vm_/obj-backup-full");
         let token = format!("Bearer {}", &token);
-        let res = self
+        let req = self
             .client
             .get(url)
             .header("Authorization", token)
-            .send()
-            .await
+            .build()
             .map_err(std::io::Error::other)?;
+        let res = self.send_with_retry(req).await?;
         if res.error_for_status_ref().is_err() {
             return Err(std::io::Error::other(
                 res.text().await.map_err(std::io::Error::other)?,
@@ -235,13 +1035,13 @@ impl HttpClient {
             url.parse().map_err(std::io::Error::other)?;
         url.set_path("_vm_/obj-restore-full");
         let token = format!("Bearer {}", &token);
-        let res = self
+        let req = self
             .client
             .get(url)
             .header("Authorization", token)
-            .send()
-            .await
+            .build()
             .map_err(std::io::Error::other)?;
+        let res = self.send_with_retry(req).await?;
         if res.error_for_status_ref().is_err() {
             return Err(std::io::Error::other(
                 res.text().await.map_err(std::io::Error::other)?,
@@ -250,3 +1050,359 @@ impl HttpClient {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    /// Spawn a raw tcp server that answers 500 to the first
+    /// `fail_count` requests and 200 after that, returning the
+    /// url to reach it and a count of requests received.
+    async fn mock_server(fail_count: u32) -> (String, Arc<AtomicU32>) {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .unwrap();
+        let addr = listener.local_addr().unwrap();
+        let count = Arc::new(AtomicU32::new(0));
+
+        let count2 = count.clone();
+        tokio::task::spawn(async move {
+            loop {
+                let (mut socket, _) = match listener.accept().await {
+                    Ok(r) => r,
+                    Err(_) => break,
+                };
+                let seen = count2.fetch_add(1, Ordering::SeqCst) + 1;
+                tokio::task::spawn(async move {
+                    let mut buf = [0; 4096];
+                    let _ = socket.read(&mut buf).await;
+                    let res = if seen <= fail_count {
+                        "HTTP/1.1 500 Internal Server Error\r\ncontent-length: 0\r\nconnection: close\r\n\r\n"
+                    } else {
+                        "HTTP/1.1 200 OK\r\ncontent-length: 0\r\nconnection: close\r\n\r\n"
+                    };
+                    let _ = socket.write_all(res.as_bytes()).await;
+                    let _ = socket.shutdown().await;
+                });
+            }
+        });
+
+        (format!("http://{addr:?}/"), count)
+    }
+
+    /// Spawn a raw tcp server that answers the Nth request with the
+    /// Nth supplied obj-list page (and an empty page after that),
+    /// returning the url to reach it and a count of requests received.
+    async fn mock_pages(
+        pages: Vec<Vec<crate::obj::ObjMeta>>,
+    ) -> (String, Arc<AtomicU32>) {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .unwrap();
+        let addr = listener.local_addr().unwrap();
+        let count = Arc::new(AtomicU32::new(0));
+
+        let count2 = count.clone();
+        tokio::task::spawn(async move {
+            loop {
+                let (mut socket, _) = match listener.accept().await {
+                    Ok(r) => r,
+                    Err(_) => break,
+                };
+                let seen = count2.fetch_add(1, Ordering::SeqCst) as usize;
+                let page = pages.get(seen).cloned().unwrap_or_default();
+                tokio::task::spawn(async move {
+                    let mut buf = [0; 4096];
+                    let _ = socket.read(&mut buf).await;
+                    #[derive(serde::Serialize)]
+                    struct R {
+                        #[serde(rename = "metaList")]
+                        meta_list: Vec<crate::obj::ObjMeta>,
+                    }
+                    let body =
+                        Bytes::from_encode(&R { meta_list: page }).unwrap();
+                    let head = format!(
+                        "HTTP/1.1 200 OK\r\ncontent-length: {}\r\nconnection: close\r\n\r\n",
+                        body.len(),
+                    );
+                    let _ = socket.write_all(head.as_bytes()).await;
+                    let _ = socket.write_all(&body).await;
+                    let _ = socket.shutdown().await;
+                });
+            }
+        });
+
+        (format!("http://{addr:?}/"), count)
+    }
+
+    /// Spawn a raw tcp server that answers 200 and captures the raw
+    /// text of the last request received, returning the url to reach
+    /// it and the capture.
+    async fn mock_capture() -> (String, Arc<std::sync::Mutex<String>>) {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .unwrap();
+        let addr = listener.local_addr().unwrap();
+        let seen = Arc::new(std::sync::Mutex::new(String::new()));
+
+        let seen2 = seen.clone();
+        tokio::task::spawn(async move {
+            loop {
+                let (mut socket, _) = match listener.accept().await {
+                    Ok(r) => r,
+                    Err(_) => break,
+                };
+                let seen2 = seen2.clone();
+                tokio::task::spawn(async move {
+                    let mut buf = [0; 4096];
+                    let got = socket.read(&mut buf).await.unwrap_or(0);
+                    *seen2.lock().unwrap() =
+                        String::from_utf8_lossy(&buf[..got]).to_string();
+                    let _ = socket.write_all(
+                        b"HTTP/1.1 200 OK\r\ncontent-length: 0\r\nconnection: close\r\n\r\n",
+                    ).await;
+                    let _ = socket.shutdown().await;
+                });
+            }
+        });
+
+        (format!("http://{addr:?}/"), seen)
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn default_headers_and_user_agent_sent() {
+        let (url, seen) = mock_capture().await;
+
+        let client = HttpClient::new(HttpClientConfig {
+            default_headers: [(
+                "x-custom-access".to_string(),
+                "letmein".to_string(),
+            )]
+            .into_iter()
+            .collect(),
+            ..Default::default()
+        })
+        .unwrap();
+
+        client.health(&url).await.unwrap();
+
+        let seen = seen.lock().unwrap().clone();
+        assert!(seen.contains("x-custom-access: letmein"), "{seen}");
+        assert!(
+            seen.contains(concat!(
+                "user-agent: voidmerge/",
+                env!("CARGO_PKG_VERSION"),
+            )),
+            "{seen}",
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn per_call_headers_override_defaults() {
+        let (url, seen) = mock_capture().await;
+
+        let client = HttpClient::new(HttpClientConfig {
+            default_headers: [(
+                "authorization".to_string(),
+                "Bearer wrong".to_string(),
+            )]
+            .into_iter()
+            .collect(),
+            ..Default::default()
+        })
+        .unwrap();
+
+        client.obj_backup_full(&url, "right").await.unwrap();
+
+        let seen = seen.lock().unwrap().clone();
+        assert!(seen.contains("authorization: Bearer right"), "{seen}");
+        assert!(!seen.contains("Bearer wrong"), "{seen}");
+    }
+
+    #[test]
+    fn invalid_config_fails_construction() {
+        let err = HttpClient::new(HttpClientConfig {
+            proxy: Some("::not a proxy url::".to_string()),
+            ..Default::default()
+        })
+        .unwrap_err();
+        assert_eq!(std::io::ErrorKind::InvalidInput, err.kind());
+
+        let err = HttpClient::new(HttpClientConfig {
+            default_headers: [(
+                "bad header name".to_string(),
+                "value".to_string(),
+            )]
+            .into_iter()
+            .collect(),
+            ..Default::default()
+        })
+        .unwrap_err();
+        assert_eq!(std::io::ErrorKind::InvalidInput, err.kind());
+    }
+
+    #[test]
+    fn missing_identity_files_fail_construction() {
+        assert!(
+            HttpClient::new(HttpClientConfig {
+                client_identity: Some((
+                    "/no/such/cert.pem".into(),
+                    "/no/such/key.pem".into(),
+                )),
+                ..Default::default()
+            })
+            .is_err()
+        );
+        assert!(
+            HttpClient::new(HttpClientConfig {
+                tls_ca_cert: Some("/no/such/ca.pem".into()),
+                ..Default::default()
+            })
+            .is_err()
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn obj_list_all_pages_until_partial_page() {
+        use futures::TryStreamExt;
+
+        let meta = |i: u32| {
+            crate::obj::ObjMeta(format!("c/test/p{i}/{i}/0/0").into())
+        };
+
+        // a full first page triggers a follow-up request,
+        // the partial second page ends the stream
+        let page1: Vec<_> = (1..=1000).map(meta).collect();
+        let page2: Vec<_> = (1001..=1002).map(meta).collect();
+        let (url, count) = mock_pages(vec![page1, page2]).await;
+
+        let client = HttpClient::new(Default::default()).unwrap();
+
+        let list: Vec<_> = client
+            .obj_list_all(&url, "test", "token", "", 0.0, None, false)
+            .try_collect()
+            .await
+            .unwrap();
+
+        assert_eq!(1002, list.len());
+        assert_eq!("c/test/p1/1/0/0", &*list[0]);
+        assert_eq!("c/test/p1002/1002/0/0", &*list[1001]);
+        assert_eq!(2, count.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn obj_list_all_pages_past_over_full_tie_page() {
+        use futures::TryStreamExt;
+
+        let meta = |name: u32, created: u32| {
+            crate::obj::ObjMeta(
+                format!("c/test/p{name}/{created}/0/0").into(),
+            )
+        };
+
+        // the server over-returns when the page boundary lands on a
+        // created_secs tie: an over-full page must still trigger a
+        // follow-up request rather than ending the stream
+        let mut page1: Vec<_> = (1..=1000).map(|i| meta(i, i)).collect();
+        page1.push(meta(1001, 1000));
+        let page2: Vec<_> =
+            (1002..=1003).map(|i| meta(i, i)).collect();
+        let (url, count) = mock_pages(vec![page1, page2]).await;
+
+        let client = HttpClient::new(Default::default()).unwrap();
+
+        let list: Vec<_> = client
+            .obj_list_all(&url, "test", "token", "", 0.0, None, false)
+            .try_collect()
+            .await
+            .unwrap();
+
+        assert_eq!(1003, list.len());
+        assert_eq!("c/test/p1001/1000/0/0", &*list[1000]);
+        assert_eq!("c/test/p1003/1003/0/0", &*list[1002]);
+        assert_eq!(2, count.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn obj_list_all_pages_descending() {
+        use futures::TryStreamExt;
+
+        let meta = |i: u32| {
+            crate::obj::ObjMeta(format!("c/test/p{i}/{i}/0/0").into())
+        };
+
+        // newest first: a full first page triggers a follow-up request
+        // with created-lt lowered below the items already seen
+        let page1: Vec<_> = (3..=1002).rev().map(meta).collect();
+        let page2: Vec<_> = (1..=2).rev().map(meta).collect();
+        let (url, count) = mock_pages(vec![page1, page2]).await;
+
+        let client = HttpClient::new(Default::default()).unwrap();
+
+        let list: Vec<_> = client
+            .obj_list_all(&url, "test", "token", "", 0.0, None, true)
+            .try_collect()
+            .await
+            .unwrap();
+
+        assert_eq!(1002, list.len());
+        assert_eq!("c/test/p1002/1002/0/0", &*list[0]);
+        assert_eq!("c/test/p1/1/0/0", &*list[1001]);
+        assert_eq!(2, count.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn retry_recovers_from_server_errors() {
+        let (url, count) = mock_server(2).await;
+
+        let client = HttpClient::new(HttpClientConfig {
+            retry: RetryConfig {
+                max_attempts: 3,
+                base_delay: std::time::Duration::from_millis(1),
+            },
+            ..Default::default()
+        })
+        .unwrap();
+
+        client.health(&url).await.unwrap();
+
+        assert_eq!(3, count.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn no_retry_by_default() {
+        let (url, count) = mock_server(1).await;
+
+        let client = HttpClient::new(Default::default()).unwrap();
+
+        assert!(client.health(&url).await.is_err());
+
+        assert_eq!(1, count.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn retry_gives_up_after_max_attempts() {
+        let (url, count) = mock_server(u32::MAX).await;
+
+        let client = HttpClient::new(HttpClientConfig {
+            retry: RetryConfig {
+                max_attempts: 2,
+                base_delay: std::time::Duration::from_millis(1),
+            },
+            ..Default::default()
+        })
+        .unwrap();
+
+        assert!(client.health(&url).await.is_err());
+
+        assert_eq!(2, count.load(Ordering::SeqCst));
+    }
+}
@@ -6,6 +6,15 @@ use std::sync::Arc;
 
 struct State {
     server: Arc<server::Server>,
+    rate_buckets: std::sync::Mutex<
+        std::collections::HashMap<std::net::IpAddr, RateBucket>,
+    >,
+}
+
+/// Token bucket state for one source ip.
+struct RateBucket {
+    tokens: f64,
+    last: std::time::Instant,
 }
 
 struct ErrTx(std::io::Error);
@@ -24,23 +33,31 @@ impl From<std::num::ParseFloatError> for ErrTx {
 
 impl axum::response::IntoResponse for ErrTx {
     fn into_response(self) -> axum::response::Response {
-        let str_err = format!("{:?}", self.0);
-
         use axum::http::StatusCode as H;
+        use crate::error::code;
         use std::io::ErrorKind::*;
 
-        match self.0.kind() {
-            NotFound => (H::NOT_FOUND, str_err),
-            PermissionDenied => (H::UNAUTHORIZED, str_err),
-            InvalidInput | InvalidData => (H::BAD_REQUEST, str_err),
-            QuotaExceeded => (H::TOO_MANY_REQUESTS, str_err),
-            FileTooLarge => (H::PAYLOAD_TOO_LARGE, str_err),
+        let (status, default_code) = match self.0.kind() {
+            NotFound => (H::NOT_FOUND, code::NOT_FOUND),
+            PermissionDenied => (H::UNAUTHORIZED, code::UNAUTHORIZED),
+            InvalidInput | InvalidData => (H::BAD_REQUEST, code::INVALID),
+            QuotaExceeded => (H::TOO_MANY_REQUESTS, code::QUOTA),
+            FileTooLarge => (H::PAYLOAD_TOO_LARGE, code::TOO_LARGE),
             // Interrupted->CONFLICT because both of these indicate
             // the user should just try again.
-            Interrupted => (H::CONFLICT, str_err),
-            _ => (H::INTERNAL_SERVER_ERROR, str_err),
-        }
-        .into_response()
+            Interrupted => (H::CONFLICT, code::RETRY),
+            TimedOut => (H::REQUEST_TIMEOUT, code::RETRY),
+            _ => (H::INTERNAL_SERVER_ERROR, code::OTHER),
+        };
+
+        (
+            status,
+            axum::Json(serde_json::json!({
+                "code": self.0.code().unwrap_or(default_code),
+                "message": format!("{:?}", self.0),
+            })),
+        )
+            .into_response()
     }
 }
 
@@ -50,6 +67,7 @@ impl axum::response::IntoResponse for crate::js::JsResponse {
             crate::js::JsResponse::FnResOk {
                 status,
                 body,
+                body_json,
                 headers,
                 ..
             } => {
@@ -59,6 +77,14 @@ impl axum::response::IntoResponse for crate::js::JsResponse {
                 {
                     let hdr = bld.headers_mut().unwrap();
                     for (k, v) in headers.iter() {
+                        // framing / hop-by-hop headers are controlled
+                        // by the server, the actual body length wins
+                        if k.eq_ignore_ascii_case("connection")
+                            || k.eq_ignore_ascii_case("transfer-encoding")
+                            || k.eq_ignore_ascii_case("content-length")
+                        {
+                            continue;
+                        }
                         if let Ok(v) = axum::http::HeaderValue::from_str(v)
                             && let Ok(k) =
                                 axum::http::HeaderName::from_bytes(k.as_bytes())
@@ -68,6 +94,23 @@ impl axum::response::IntoResponse for crate::js::JsResponse {
                     }
                 }
 
+                let body = if body.is_empty()
+                    && let Some(json) = body_json
+                {
+                    let hdr = bld.headers_mut().unwrap();
+                    if !hdr.contains_key(axum::http::header::CONTENT_TYPE) {
+                        hdr.insert(
+                            axum::http::header::CONTENT_TYPE,
+                            axum::http::HeaderValue::from_static(
+                                "application/json",
+                            ),
+                        );
+                    }
+                    serde_json::to_vec(&json).unwrap_or_default().into()
+                } else {
+                    body
+                };
+
                 bld.body(axum::body::Body::from(body)).unwrap()
             }
             _ => unreachable!(),
@@ -77,6 +120,255 @@ impl axum::response::IntoResponse for crate::js::JsResponse {
 
 type AxumResult = std::result::Result<axum::response::Response, ErrTx>;
 
+/// Per-request trace id, taken from an incoming `X-Request-Id` header
+/// or freshly minted, and echoed back in the response.
+#[derive(Clone)]
+struct TraceId(Arc<str>);
+
+async fn trace_id_middleware(
+    mut req: axum::extract::Request,
+    next: axum::middleware::Next,
+) -> axum::response::Response {
+    let trace_id: Arc<str> = match req
+        .headers()
+        .get("x-request-id")
+        .and_then(|v| v.to_str().ok())
+        .filter(|v| {
+            !v.is_empty() && v.len() <= 64 && crate::safe_str(v).is_ok()
+        }) {
+        Some(v) => v.into(),
+        None => {
+            let mut trace_id = [0; 12];
+            use rand::Rng;
+            rand::rng().fill(&mut trace_id);
+            use base64::prelude::*;
+            BASE64_URL_SAFE_NO_PAD.encode(trace_id).into()
+        }
+    };
+
+    req.extensions_mut().insert(TraceId(trace_id.clone()));
+
+    let span = tracing::info_span!("req", %trace_id);
+    use tracing::Instrument;
+    let mut res = next.run(req).instrument(span).await;
+
+    if let Ok(v) = axum::http::HeaderValue::from_str(&trace_id) {
+        res.headers_mut().insert("x-request-id", v);
+    }
+
+    res
+}
+
+/// Browser `EventSource` and some WebSocket clients cannot set an
+/// `Authorization` header. When the header is absent, fall back to an
+/// `access_token` query parameter, synthesizing the bearer header the
+/// routes expect; the header always wins when both are present. Be
+/// aware that tokens passed in the query string can end up in request
+/// logs and proxy caches — prefer the header wherever the client can
+/// set one.
+async fn access_token_middleware(
+    mut req: axum::extract::Request,
+    next: axum::middleware::Next,
+) -> axum::response::Response {
+    if !req.headers().contains_key("authorization")
+        && let Some(token) = req.uri().query().and_then(|query| {
+            query.split('&').find_map(|pair| {
+                let (k, v) = pair.split_once('=')?;
+                if k == "access_token" && !v.is_empty() {
+                    Some(v.to_string())
+                } else {
+                    None
+                }
+            })
+        })
+        && let Ok(v) =
+            axum::http::HeaderValue::from_str(&format!("Bearer {token}"))
+    {
+        req.headers_mut().insert("authorization", v);
+    }
+
+    next.run(req).await
+}
+
+/// Host-based context routing: when the request host is mapped via
+/// [server::Server::domain_set] and the first path segment is not one
+/// of the server's own top-level routes, rewrite the request into the
+/// function route for the mapped context with the full path passed
+/// through. Unmapped hosts fall through to normal routing.
+async fn host_routing_middleware(
+    axum::extract::State(state): axum::extract::State<Arc<State>>,
+    mut req: axum::extract::Request,
+    next: axum::middleware::Next,
+) -> axum::response::Response {
+    // the server's own routes win over domain mappings; the empty
+    // segment is the root health route
+    const RESERVED: &[&str] =
+        &["", "ctx-setup", "ctx-clone", "ctx-admin", "_vm_"];
+
+    let seg = req
+        .uri()
+        .path()
+        .trim_start_matches('/')
+        .split('/')
+        .next()
+        .unwrap_or("");
+    if !RESERVED.contains(&seg)
+        && let Some(host) =
+            req.headers().get("host").and_then(|v| v.to_str().ok())
+        && let Some(ctx) = state.server.domain_resolve(host)
+    {
+        let pq = req
+            .uri()
+            .path_and_query()
+            .map(|pq| pq.as_str())
+            .unwrap_or("/");
+        if let Ok(uri) = format!("/{ctx}{pq}").parse::<axum::http::Uri>() {
+            *req.uri_mut() = uri;
+        }
+    }
+
+    next.run(req).await
+}
+
+/// Cap on tracked rate-limit source ips. When reached, buckets idle
+/// long enough to have fully refilled are dropped before a new ip is
+/// admitted, bounding memory under address-spoofing floods.
+const RATE_LIMIT_MAX_BUCKETS: usize = 8192;
+
+/// Per source ip token-bucket rate limiting, enabled via
+/// [server::Server::set_rate_limit_rps]. Each ip accrues `rps` tokens
+/// per second up to a burst of `rps`; a request costs one token, and a
+/// request finding its bucket empty is refused with 429.
+/// Sysadmin-authenticated requests are exempt so operators can still
+/// administer a flooded server.
+async fn rate_limit_middleware(
+    axum::extract::State(state): axum::extract::State<Arc<State>>,
+    req: axum::extract::Request,
+    next: axum::middleware::Next,
+) -> axum::response::Response {
+    if let Some(rps) = state.server.rate_limit_rps()
+        && let Some(addr) = req
+            .extensions()
+            .get::<axum::extract::ConnectInfo<std::net::SocketAddr>>()
+        && state
+            .server
+            .check_sysadmin(&auth_token(req.headers()))
+            .is_err()
+    {
+        let ip = addr.0.ip();
+        let now = std::time::Instant::now();
+        let mut lock = state.rate_buckets.lock().unwrap();
+        if lock.len() >= RATE_LIMIT_MAX_BUCKETS && !lock.contains_key(&ip) {
+            lock.retain(|_, bucket| {
+                now.duration_since(bucket.last)
+                    < std::time::Duration::from_secs(1)
+            });
+        }
+        let bucket = lock.entry(ip).or_insert(RateBucket {
+            tokens: rps,
+            last: now,
+        });
+        let elapsed = now.duration_since(bucket.last).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * rps).min(rps);
+        bucket.last = now;
+        if bucket.tokens < 1.0 {
+            return ErrTx(Error::quota(format!(
+                "rate limit of {rps} requests per second exceeded"
+            )))
+            .into_response();
+        }
+        bucket.tokens -= 1.0;
+    }
+
+    next.run(req).await
+}
+
+static ACCESS_LOG_HEALTH: std::sync::OnceLock<bool> =
+    std::sync::OnceLock::new();
+
+/// Include health-check requests to `/` in the access log. They are
+/// excluded by default so load-balancer probes do not drown the log.
+/// (Default: false).
+pub fn http_server_global_set_access_log_health(enabled: bool) -> bool {
+    ACCESS_LOG_HEALTH.set(enabled).is_ok()
+}
+
+fn http_server_global_get_access_log_health() -> bool {
+    *ACCESS_LOG_HEALTH.get_or_init(|| false)
+}
+
+/// One structured tracing event at target "access" per request:
+/// standard access-log fields for every request served, not just
+/// errors. The bearer token is redacted to its first six characters.
+/// Operators can silence the log entirely via the VM_LOG filter, or
+/// render it as common log format lines via `--access-log-format`.
+async fn access_log_middleware(
+    req: axum::extract::Request,
+    next: axum::middleware::Next,
+) -> axum::response::Response {
+    let method = req.method().clone();
+    let path = req.uri().path().to_string();
+    let ctx = path
+        .trim_start_matches('/')
+        .split('/')
+        .next()
+        .unwrap_or("")
+        .to_string();
+    let remote = req
+        .extensions()
+        .get::<axum::extract::ConnectInfo<std::net::SocketAddr>>()
+        .map(|addr| addr.0.ip().to_string())
+        .unwrap_or_default();
+    let token: String = auth_token(req.headers()).chars().take(6).collect();
+    let start = std::time::Instant::now();
+
+    let res = next.run(req).await;
+
+    if path != "/" || http_server_global_get_access_log_health() {
+        let bytes = res
+            .headers()
+            .get(axum::http::header::CONTENT_LENGTH)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(0);
+        tracing::info!(
+            target: "access",
+            %method,
+            path,
+            ctx,
+            remote,
+            token,
+            status = res.status().as_u16(),
+            duration_ms = start.elapsed().as_millis() as u64,
+            bytes,
+        );
+    }
+
+    res
+}
+
+static REQUEST_TIMEOUT: std::sync::OnceLock<std::time::Duration> =
+    std::sync::OnceLock::new();
+
+/// Set the max time the server will spend on a single http request
+/// before responding 503. Keep this larger than the max function
+/// timeout and the message long-poll ceiling so legitimate slow
+/// requests can still complete. (Default: 60s).
+pub fn http_server_global_set_request_timeout(
+    timeout: std::time::Duration,
+) -> bool {
+    REQUEST_TIMEOUT.set(timeout).is_ok()
+}
+
+fn http_server_global_get_request_timeout() -> std::time::Duration {
+    *REQUEST_TIMEOUT.get_or_init(|| std::time::Duration::from_secs(60))
+}
+
+/// Max time a client may take to transmit its request headers before
+/// the connection is closed (slowloris protection).
+const HEADER_READ_TIMEOUT: std::time::Duration =
+    std::time::Duration::from_secs(10);
+
 /// Execute a VoidMerge http server process.
 pub async fn http_server(
     running: tokio::sync::oneshot::Sender<std::net::SocketAddr>,
@@ -85,6 +377,7 @@ pub async fn http_server(
 ) -> Result<()> {
     let state = Arc::new(State {
         server: Arc::new(server),
+        rate_buckets: Default::default(),
     });
 
     /*
@@ -121,6 +414,14 @@ pub async fn http_server(
     let app: axum::Router<Arc<State>> = axum::Router::new()
         .route("/", axum::routing::get(route_health_get))
         .route("/ctx-setup", axum::routing::put(route_ctx_setup_put))
+        .route("/ctx-clone", axum::routing::post(route_ctx_clone))
+        .route("/ctx-admin", axum::routing::patch(route_ctx_admin))
+        .route("/_vm_/ctx-list", axum::routing::get(route_ctx_list))
+        .route("/_vm_/ctx-list/", axum::routing::get(route_ctx_list))
+        .route(
+            "/_vm_/domains",
+            axum::routing::put(route_domains_put).get(route_domains_get),
+        )
         .route(
             "/_vm_/obj-backup-full",
             axum::routing::get(route_obj_backup_full),
@@ -141,10 +442,32 @@ pub async fn http_server(
             "/{ctx}/_vm_/config",
             axum::routing::put(route_ctx_config_put),
         )
+        .route(
+            "/{ctx}/_vm_/config/versions",
+            axum::routing::get(route_ctx_config_versions),
+        )
+        .route(
+            "/{ctx}/_vm_/config/rollback/{version}",
+            axum::routing::put(route_ctx_config_rollback),
+        )
+        .route("/{ctx}/_vm_/context", axum::routing::put(route_ctx_put))
+        .route(
+            "/{ctx}/_vm_/tokens",
+            axum::routing::put(route_ctx_tokens),
+        )
         .route(
             "/{ctx}/_vm_/msg-listen/{msg_id}",
             axum::routing::any(route_msg_listen),
         )
+        .route(
+            "/{ctx}/_vm_/msg/{msg_id}/recv",
+            axum::routing::get(route_msg_recv),
+        )
+        .route(
+            "/{ctx}/_vm_/secret/{name}",
+            axum::routing::put(route_ctx_secret_put)
+                .delete(route_ctx_secret_rm),
+        )
         .route(
             "/{ctx}/_vm_/obj-list",
             axum::routing::get(route_ctx_obj_list_all),
@@ -157,6 +480,10 @@ pub async fn http_server(
             "/{ctx}/_vm_/obj-list/{app_path_prefix}",
             axum::routing::get(route_ctx_obj_list),
         )
+        .route(
+            "/{ctx}/_vm_/obj-query",
+            axum::routing::put(route_ctx_obj_query),
+        )
         .route(
             "/{ctx}/_vm_/obj-get/{app_path}",
             axum::routing::get(route_ctx_obj_get),
@@ -165,19 +492,67 @@ pub async fn http_server(
             "/{ctx}/_vm_/obj-put/{*path}",
             axum::routing::put(route_ctx_obj_put),
         )
+        .route(
+            "/{ctx}/_vm_/raw/{app_path}",
+            axum::routing::get(route_ctx_obj_raw),
+        )
+        .route(
+            "/{ctx}/_vm_/obj-sign/{app_path}",
+            axum::routing::post(route_ctx_obj_sign),
+        )
+        .route(
+            "/{ctx}/_vm_/revalidate",
+            axum::routing::put(route_ctx_revalidate),
+        )
+        .route(
+            "/{ctx}/_vm_/reload",
+            axum::routing::post(route_ctx_reload),
+        )
+        .route(
+            "/{ctx}/_vm_/status-stream",
+            axum::routing::get(route_ctx_status_stream),
+        )
         .route("/{ctx}/{*path}", axum::routing::any(route_fn))
         .route("/{ctx}/", axum::routing::any(route_fn_def))
         .route("/{ctx}", axum::routing::any(route_fn_def));
 
     let app = app
         .layer(cors)
+        // innermost of the middlewares, so the access_token fallback
+        // has already synthesized the auth header the sysadmin
+        // exemption checks
+        .layer(axum::middleware::from_fn_with_state(
+            state.clone(),
+            rate_limit_middleware,
+        ))
+        .layer(axum::middleware::from_fn(trace_id_middleware))
+        .layer(axum::middleware::from_fn(access_token_middleware))
+        .layer(axum::middleware::from_fn_with_state(
+            state.clone(),
+            host_routing_middleware,
+        ))
         .layer(axum::extract::DefaultBodyLimit::max(10 * 1024 * 1024))
+        // a request that cannot be served within the ceiling (a
+        // dribbled body, a hung function) is cut off with a 503
+        .layer(tower_http::timeout::TimeoutLayer::with_status_code(
+            axum::http::StatusCode::SERVICE_UNAVAILABLE,
+            http_server_global_get_request_timeout(),
+        ))
+        // outermost so every request is logged, including those
+        // refused by the inner middlewares
+        .layer(axum::middleware::from_fn(access_log_middleware))
         .with_state(state)
         .into_make_service_with_connect_info::<std::net::SocketAddr>();
 
     let handle = axum_server::Handle::new();
 
-    let server = axum_server::bind(bind).handle(handle.clone()).serve(app);
+    let mut server = axum_server::bind(bind).handle(handle.clone());
+    server
+        .http_builder()
+        .http1()
+        .timer(hyper_util::rt::TokioTimer::new())
+        .header_read_timeout(HEADER_READ_TIMEOUT);
+    let server = server.serve(app);
 
     tokio::task::spawn(async move {
         if let Some(bound_addr) = handle.listening().await {
@@ -227,6 +602,129 @@ async fn route_ctx_setup_put(
     Ok("Ok".into_response())
 }
 
+#[derive(serde::Deserialize)]
+struct CtxCloneInput {
+    #[serde(rename = "s")]
+    src_ctx: Arc<str>,
+    #[serde(rename = "d")]
+    dst_ctx: Arc<str>,
+}
+
+async fn route_ctx_clone(
+    headers: axum::http::HeaderMap,
+    axum::extract::ConnectInfo(_addr): axum::extract::ConnectInfo<
+        std::net::SocketAddr,
+    >,
+    axum::extract::State(state): axum::extract::State<Arc<State>>,
+    payload: bytes::Bytes,
+) -> AxumResult {
+    let token = auth_token(&headers);
+    let input: CtxCloneInput = payload.to_decode()?;
+    state
+        .server
+        .ctx_clone(token, input.src_ctx, input.dst_ctx)
+        .await?;
+    Ok("Ok".into_response())
+}
+
+#[derive(serde::Deserialize)]
+struct CtxAdminInput {
+    #[serde(rename = "c")]
+    ctx: Arc<str>,
+    #[serde(rename = "a", default)]
+    add: Option<Arc<str>>,
+    #[serde(rename = "r", default)]
+    remove: Option<Arc<str>>,
+}
+
+#[derive(serde::Serialize)]
+struct CtxAdminOutput {
+    #[serde(rename = "ctxAdmin")]
+    ctx_admin: Vec<Arc<str>>,
+}
+
+async fn route_ctx_admin(
+    headers: axum::http::HeaderMap,
+    axum::extract::ConnectInfo(_addr): axum::extract::ConnectInfo<
+        std::net::SocketAddr,
+    >,
+    axum::extract::State(state): axum::extract::State<Arc<State>>,
+    payload: bytes::Bytes,
+) -> AxumResult {
+    let token = auth_token(&headers);
+    let input: CtxAdminInput = payload.to_decode()?;
+    let ctx_admin = match (input.add, input.remove) {
+        (Some(add), None) => {
+            state
+                .server
+                .ctx_admin_add(token, input.ctx, add)
+                .await?
+        }
+        (None, Some(remove)) => {
+            state
+                .server
+                .ctx_admin_remove(token, input.ctx, remove)
+                .await?
+        }
+        _ => {
+            return Err(Error::invalid(
+                "exactly one of add or remove is required",
+            )
+            .into());
+        }
+    };
+    Ok(
+        bytes::Bytes::from_encode(&CtxAdminOutput { ctx_admin })?
+            .into_response(),
+    )
+}
+
+#[derive(serde::Deserialize)]
+struct DomainSetInput {
+    #[serde(rename = "h")]
+    host: Arc<str>,
+    #[serde(rename = "c", default)]
+    ctx: Arc<str>,
+}
+
+#[derive(serde::Serialize)]
+struct DomainListOutput {
+    #[serde(rename = "domains")]
+    domains: std::collections::HashMap<Arc<str>, Arc<str>>,
+}
+
+async fn route_domains_put(
+    headers: axum::http::HeaderMap,
+    axum::extract::ConnectInfo(_addr): axum::extract::ConnectInfo<
+        std::net::SocketAddr,
+    >,
+    axum::extract::State(state): axum::extract::State<Arc<State>>,
+    payload: bytes::Bytes,
+) -> AxumResult {
+    let token = auth_token(&headers);
+    let input: DomainSetInput = payload.to_decode()?;
+    state
+        .server
+        .domain_set(token, input.host, input.ctx)
+        .await?;
+    Ok("Ok".into_response())
+}
+
+async fn route_domains_get(
+    headers: axum::http::HeaderMap,
+    axum::extract::ConnectInfo(_addr): axum::extract::ConnectInfo<
+        std::net::SocketAddr,
+    >,
+    axum::extract::State(state): axum::extract::State<Arc<State>>,
+) -> AxumResult {
+    let token = auth_token(&headers);
+    let domains = state.server.domain_list(token)?;
+    Ok(
+        bytes::Bytes::from_encode(&DomainListOutput { domains })?
+            .into_response(),
+    )
+}
+
 async fn route_ctx_config_put(
     headers: axum::http::HeaderMap,
     axum::extract::ConnectInfo(_addr): axum::extract::ConnectInfo<
@@ -243,6 +741,83 @@ async fn route_ctx_config_put(
     Ok("Ok".into_response())
 }
 
+#[derive(serde::Serialize)]
+struct CtxConfigVersionsOutput {
+    #[serde(rename = "versions")]
+    versions: Vec<crate::server::CtxConfigVersion>,
+}
+
+async fn route_ctx_config_versions(
+    headers: axum::http::HeaderMap,
+    axum::extract::Path(ctx): axum::extract::Path<String>,
+    axum::extract::ConnectInfo(_addr): axum::extract::ConnectInfo<
+        std::net::SocketAddr,
+    >,
+    axum::extract::State(state): axum::extract::State<Arc<State>>,
+) -> AxumResult {
+    let token = auth_token(&headers);
+    let versions = state
+        .server
+        .ctx_config_versions(token, ctx.into())
+        .await?;
+    Ok(bytes::Bytes::from_encode(&CtxConfigVersionsOutput { versions })?
+        .into_response())
+}
+
+async fn route_ctx_config_rollback(
+    headers: axum::http::HeaderMap,
+    axum::extract::Path((ctx, version)): axum::extract::Path<(String, u64)>,
+    axum::extract::ConnectInfo(_addr): axum::extract::ConnectInfo<
+        std::net::SocketAddr,
+    >,
+    axum::extract::State(state): axum::extract::State<Arc<State>>,
+) -> AxumResult {
+    let token = auth_token(&headers);
+    state
+        .server
+        .ctx_config_rollback(token, ctx.into(), version)
+        .await?;
+    Ok("Ok".into_response())
+}
+
+async fn route_ctx_put(
+    headers: axum::http::HeaderMap,
+    axum::extract::Path(ctx): axum::extract::Path<String>,
+    axum::extract::ConnectInfo(_addr): axum::extract::ConnectInfo<
+        std::net::SocketAddr,
+    >,
+    axum::extract::State(state): axum::extract::State<Arc<State>>,
+    payload: bytes::Bytes,
+) -> AxumResult {
+    let token = auth_token(&headers);
+    let mut input: crate::server::CtxPut = payload.to_decode()?;
+    // the path names the context; any ctx in the body is ignored
+    input.ctx = ctx.into();
+    state.server.ctx_put(token, input).await?;
+    Ok("Ok".into_response())
+}
+
+async fn route_ctx_tokens(
+    headers: axum::http::HeaderMap,
+    axum::extract::Path(ctx): axum::extract::Path<String>,
+    axum::extract::ConnectInfo(_addr): axum::extract::ConnectInfo<
+        std::net::SocketAddr,
+    >,
+    axum::extract::State(state): axum::extract::State<Arc<State>>,
+    payload: bytes::Bytes,
+) -> AxumResult {
+    let token = auth_token(&headers);
+    let input: crate::server::CtxTokens = payload.to_decode()?;
+    let ctx_admin = state
+        .server
+        .ctx_tokens(token, ctx.into(), input)
+        .await?;
+    Ok(
+        bytes::Bytes::from_encode(&CtxAdminOutput { ctx_admin })?
+            .into_response(),
+    )
+}
+
 async fn route_msg_listen(
     ws: axum::extract::ws::WebSocketUpgrade,
     axum::extract::Path((ctx, msg_id)): axum::extract::Path<(String, String)>,
@@ -341,73 +916,202 @@ async fn route_msg_listen(
     }))
 }
 
-fn list_limit_default() -> f64 {
-    1000.0
-}
-
-#[derive(serde::Deserialize)]
-struct ObjListQuery {
-    #[serde(rename = "created-gt", default)]
-    created_gt: f64,
-    #[serde(default = "list_limit_default")]
-    limit: f64,
-}
-
-#[derive(serde::Serialize)]
-struct ObjListOutput {
-    #[serde(rename = "metaList")]
-    meta_list: Vec<crate::obj::ObjMeta>,
-}
-
-async fn route_ctx_obj_list_all(
+async fn route_ctx_secret_put(
     headers: axum::http::HeaderMap,
-    axum::extract::Path(ctx): axum::extract::Path<String>,
-    axum::extract::Query(query): axum::extract::Query<ObjListQuery>,
+    axum::extract::Path((ctx, name)): axum::extract::Path<(String, String)>,
     axum::extract::ConnectInfo(_addr): axum::extract::ConnectInfo<
         std::net::SocketAddr,
     >,
     axum::extract::State(state): axum::extract::State<Arc<State>>,
+    payload: bytes::Bytes,
 ) -> AxumResult {
     let token = auth_token(&headers);
-    let limit = query.limit.clamp(0.0, 1000.0).floor() as u32;
-    let result = state
+    state
         .server
-        .obj_list(token, ctx.into(), "".into(), query.created_gt, limit)
+        .secret_put(token, ctx.into(), name.into(), payload)
         .await?;
-    Ok(
-        bytes::Bytes::from_encode(&ObjListOutput { meta_list: result })?
-            .into_response(),
-    )
+    Ok("Ok".into_response())
 }
 
-async fn route_ctx_obj_list(
+async fn route_ctx_secret_rm(
     headers: axum::http::HeaderMap,
-    axum::extract::Path((ctx, app_path_prefix)): axum::extract::Path<(
-        String,
-        String,
-    )>,
-    axum::extract::Query(query): axum::extract::Query<ObjListQuery>,
+    axum::extract::Path((ctx, name)): axum::extract::Path<(String, String)>,
     axum::extract::ConnectInfo(_addr): axum::extract::ConnectInfo<
         std::net::SocketAddr,
     >,
     axum::extract::State(state): axum::extract::State<Arc<State>>,
 ) -> AxumResult {
     let token = auth_token(&headers);
-    let limit = query.limit.clamp(0.0, 1000.0).floor() as u32;
-    let result = state
+    state
+        .server
+        .secret_rm(token, ctx.into(), name.into())
+        .await?;
+    Ok("Ok".into_response())
+}
+
+fn msg_recv_timeout_default() -> f64 {
+    5000.0
+}
+
+#[derive(serde::Deserialize)]
+struct MsgRecvQuery {
+    #[serde(rename = "timeout_ms", default = "msg_recv_timeout_default")]
+    timeout_ms: f64,
+}
+
+async fn route_msg_recv(
+    axum::extract::Path((ctx, msg_id)): axum::extract::Path<(String, String)>,
+    axum::extract::Query(query): axum::extract::Query<MsgRecvQuery>,
+    axum::extract::ConnectInfo(_addr): axum::extract::ConnectInfo<
+        std::net::SocketAddr,
+    >,
+    axum::extract::State(state): axum::extract::State<Arc<State>>,
+) -> AxumResult {
+    let mut msg_recv =
+        match state.server.msg_listen(ctx.into(), msg_id.into()).await {
+            Some(msg_recv) => msg_recv,
+            None => {
+                return Err(Error::other("Invalid msgId").into());
+            }
+        };
+
+    let timeout_ms = query.timeout_ms.clamp(0.0, 30000.0).floor() as u64;
+
+    match tokio::time::timeout(
+        std::time::Duration::from_millis(timeout_ms),
+        msg_recv.recv(),
+    )
+    .await
+    {
+        // a message arrived
+        Ok(Some(msg)) => {
+            Ok(bytes::Bytes::from_encode(&msg)?.into_response())
+        }
+        // the channel was closed
+        Ok(None) => Ok(axum::http::StatusCode::GONE.into_response()),
+        // the timeout elapsed with no message
+        Err(_) => Ok(axum::http::StatusCode::NO_CONTENT.into_response()),
+    }
+}
+
+fn list_limit_default() -> f64 {
+    1000.0
+}
+
+#[derive(serde::Deserialize)]
+struct ObjListQuery {
+    #[serde(rename = "created-gt", default)]
+    created_gt: f64,
+    #[serde(rename = "created-lt", default)]
+    created_lt: Option<f64>,
+    #[serde(default)]
+    desc: bool,
+    #[serde(default = "list_limit_default")]
+    limit: f64,
+    #[serde(default)]
+    detail: bool,
+}
+
+#[derive(serde::Serialize)]
+struct ObjListOutput {
+    #[serde(rename = "metaList")]
+    meta_list: Vec<crate::obj::ObjMeta>,
+}
+
+#[derive(serde::Serialize)]
+struct ObjListDetailOutput {
+    #[serde(rename = "metaList")]
+    meta_list: Vec<crate::obj::ObjListEntry>,
+}
+
+/// With `detail=true` the listing is returned as structured entries,
+/// otherwise as the raw meta path strings existing clients expect.
+fn obj_list_response(
+    result: Vec<crate::obj::ObjMeta>,
+    detail: bool,
+) -> AxumResult {
+    if detail {
+        let meta_list = result.iter().map(Into::into).collect();
+        Ok(
+            bytes::Bytes::from_encode(&ObjListDetailOutput { meta_list })?
+                .into_response(),
+        )
+    } else {
+        Ok(
+            bytes::Bytes::from_encode(&ObjListOutput { meta_list: result })?
+                .into_response(),
+        )
+    }
+}
+
+async fn route_ctx_obj_list_all(
+    headers: axum::http::HeaderMap,
+    axum::extract::Path(ctx): axum::extract::Path<String>,
+    axum::extract::Query(query): axum::extract::Query<ObjListQuery>,
+    axum::extract::ConnectInfo(_addr): axum::extract::ConnectInfo<
+        std::net::SocketAddr,
+    >,
+    axum::extract::State(state): axum::extract::State<Arc<State>>,
+) -> AxumResult {
+    let token = auth_token(&headers);
+    let limit = query.limit.clamp(0.0, 1000.0).floor() as u32;
+    let result = state
+        .server
+        .obj_list(
+            token,
+            ctx.into(),
+            "".into(),
+            query.created_gt,
+            query.created_lt,
+            query.desc,
+            limit,
+        )
+        .await?;
+    obj_list_response(result, query.detail)
+}
+
+async fn route_ctx_obj_list(
+    headers: axum::http::HeaderMap,
+    axum::extract::Path((ctx, app_path_prefix)): axum::extract::Path<(
+        String,
+        String,
+    )>,
+    axum::extract::Query(query): axum::extract::Query<ObjListQuery>,
+    axum::extract::ConnectInfo(_addr): axum::extract::ConnectInfo<
+        std::net::SocketAddr,
+    >,
+    axum::extract::State(state): axum::extract::State<Arc<State>>,
+) -> AxumResult {
+    let token = auth_token(&headers);
+    let limit = query.limit.clamp(0.0, 1000.0).floor() as u32;
+    let result = state
         .server
         .obj_list(
             token,
             ctx.into(),
             app_path_prefix.into(),
             query.created_gt,
+            query.created_lt,
+            query.desc,
             limit,
         )
         .await?;
-    Ok(
-        bytes::Bytes::from_encode(&ObjListOutput { meta_list: result })?
-            .into_response(),
-    )
+    obj_list_response(result, query.detail)
+}
+
+async fn route_ctx_obj_query(
+    headers: axum::http::HeaderMap,
+    axum::extract::Path(ctx): axum::extract::Path<String>,
+    axum::extract::ConnectInfo(_addr): axum::extract::ConnectInfo<
+        std::net::SocketAddr,
+    >,
+    axum::extract::State(state): axum::extract::State<Arc<State>>,
+    payload: bytes::Bytes,
+) -> AxumResult {
+    let token = auth_token(&headers);
+    let query: crate::server::ObjQuery = payload.to_decode()?;
+    let result = state.server.obj_query(token, ctx.into(), query).await?;
+    Ok(bytes::Bytes::from_encode(&result)?.into_response())
 }
 
 #[derive(serde::Serialize)]
@@ -416,21 +1120,230 @@ struct ObjGetOutput {
     data: bytes::Bytes,
 }
 
+#[derive(serde::Deserialize)]
+struct ObjGetQuery {
+    #[serde(default)]
+    exp: Option<String>,
+    #[serde(default)]
+    sig: Option<String>,
+}
+
+/// Version identifier for an object served over http. The meta path
+/// embeds the created timestamp and byte length, so its hash changes
+/// whenever the object is replaced.
+fn obj_etag(meta: &crate::obj::ObjMeta) -> String {
+    use sha2::{Digest, Sha256};
+    let hash = Sha256::digest(meta.0.as_bytes());
+    format!("\"{}\"", bytes::Bytes::copy_from_slice(&hash).to_b64())
+}
+
 async fn route_ctx_obj_get(
     headers: axum::http::HeaderMap,
     axum::extract::Path((ctx, app_path)): axum::extract::Path<(String, String)>,
+    axum::extract::Query(query): axum::extract::Query<ObjGetQuery>,
     axum::extract::ConnectInfo(_addr): axum::extract::ConnectInfo<
         std::net::SocketAddr,
     >,
     axum::extract::State(state): axum::extract::State<Arc<State>>,
 ) -> AxumResult {
-    let token = auth_token(&headers);
-    let (meta, data) =
-        state.server.obj_get(token, ctx.into(), app_path).await?;
-    Ok(
-        bytes::Bytes::from_encode(&ObjGetOutput { meta, data })?
-            .into_response(),
+    // conditional get: when the client supplies If-None-Match we only
+    // need the meta to decide whether its cached copy is still current
+    if let Some(if_none_match) = headers
+        .get(axum::http::header::IF_NONE_MATCH)
+        .and_then(|v| v.to_str().ok())
+    {
+        let meta = match (&query.exp, &query.sig) {
+            (Some(exp), Some(sig)) => {
+                state
+                    .server
+                    .obj_get_meta_signed(
+                        ctx.clone().into(),
+                        app_path.clone(),
+                        exp,
+                        sig,
+                    )
+                    .await?
+            }
+            _ => {
+                let token = auth_token(&headers);
+                state
+                    .server
+                    .obj_get_meta(token, ctx.clone().into(), app_path.clone())
+                    .await?
+            }
+        };
+        let etag = obj_etag(&meta);
+        if if_none_match == etag {
+            return Ok((
+                axum::http::StatusCode::NOT_MODIFIED,
+                [
+                    (axum::http::header::ETAG, etag),
+                    (
+                        axum::http::header::CACHE_CONTROL,
+                        "no-cache".to_string(),
+                    ),
+                ],
+            )
+                .into_response());
+        }
+    }
+
+    let (meta, data) = match (&query.exp, &query.sig) {
+        // signed url access, no bearer token required
+        (Some(exp), Some(sig)) => {
+            state
+                .server
+                .obj_get_signed(ctx.into(), app_path, exp, sig)
+                .await?
+        }
+        _ => {
+            let token = auth_token(&headers);
+            state.server.obj_get(token, ctx.into(), app_path).await?
+        }
+    };
+    let etag = obj_etag(&meta);
+    Ok((
+        [
+            (axum::http::header::ETAG, etag),
+            (axum::http::header::CACHE_CONTROL, "no-cache".to_string()),
+        ],
+        bytes::Bytes::from_encode(&ObjGetOutput { meta, data })?,
     )
+        .into_response())
+}
+
+#[derive(serde::Deserialize)]
+struct RevalidateInput {
+    #[serde(rename = "p", default)]
+    prefix: String,
+    #[serde(rename = "g", default)]
+    created_gt: f64,
+    #[serde(rename = "l", default = "revalidate_limit_default")]
+    limit: u32,
+    #[serde(rename = "d", default)]
+    delete_failures: bool,
+}
+
+fn revalidate_limit_default() -> u32 {
+    1000
+}
+
+async fn route_ctx_revalidate(
+    headers: axum::http::HeaderMap,
+    axum::extract::Path(ctx): axum::extract::Path<String>,
+    axum::extract::ConnectInfo(_addr): axum::extract::ConnectInfo<
+        std::net::SocketAddr,
+    >,
+    axum::extract::State(state): axum::extract::State<Arc<State>>,
+    payload: bytes::Bytes,
+) -> AxumResult {
+    let token = auth_token(&headers);
+    let input: RevalidateInput = payload.to_decode()?;
+    let report = state
+        .server
+        .ctx_revalidate(
+            token,
+            ctx.into(),
+            input.prefix,
+            input.created_gt,
+            input.limit.min(1000),
+            input.delete_failures,
+        )
+        .await?;
+    Ok(bytes::Bytes::from_encode(&report)?.into_response())
+}
+
+async fn route_ctx_reload(
+    headers: axum::http::HeaderMap,
+    axum::extract::Path(ctx): axum::extract::Path<String>,
+    axum::extract::ConnectInfo(_addr): axum::extract::ConnectInfo<
+        std::net::SocketAddr,
+    >,
+    axum::extract::State(state): axum::extract::State<Arc<State>>,
+) -> AxumResult {
+    let token = auth_token(&headers);
+    state.server.reload_context(token, ctx.into()).await?;
+    Ok("Ok".into_response())
+}
+
+#[derive(serde::Deserialize)]
+struct ObjSignQuery {
+    /// Absolute unix epoch seconds until which the link is valid.
+    expires: f64,
+}
+
+async fn route_ctx_obj_sign(
+    headers: axum::http::HeaderMap,
+    axum::extract::Path((ctx, app_path)): axum::extract::Path<(String, String)>,
+    axum::extract::Query(query): axum::extract::Query<ObjSignQuery>,
+    axum::extract::ConnectInfo(_addr): axum::extract::ConnectInfo<
+        std::net::SocketAddr,
+    >,
+    axum::extract::State(state): axum::extract::State<Arc<State>>,
+) -> AxumResult {
+    let token = auth_token(&headers);
+    let signed = state.server.sign_obj_url(
+        token,
+        ctx.clone().into(),
+        app_path.clone(),
+        query.expires,
+    )?;
+    // the raw route serves the object bytes with its recorded
+    // content type, so signed links work directly in a browser
+    Ok(format!("/{ctx}/_vm_/raw/{app_path}{signed}").into_response())
+}
+
+/// How often the status stream re-checks the context status for
+/// changes.
+const STATUS_POLL: std::time::Duration = std::time::Duration::from_secs(5);
+
+async fn route_ctx_status_stream(
+    headers: axum::http::HeaderMap,
+    axum::extract::Path(ctx): axum::extract::Path<String>,
+    axum::extract::ConnectInfo(_addr): axum::extract::ConnectInfo<
+        std::net::SocketAddr,
+    >,
+    axum::extract::State(state): axum::extract::State<Arc<State>>,
+) -> AxumResult {
+    let token = auth_token(&headers);
+    let ctx: Arc<str> = ctx.into();
+
+    // validate access up front so a bad token fails the request
+    // instead of silently producing an empty stream
+    state.server.ctx_status(token.clone(), ctx.clone())?;
+
+    // the first snapshot is emitted immediately, after that the
+    // status is polled and an event is only emitted when the
+    // serialized snapshot differs from the one previously sent
+    let stream = futures::stream::unfold(
+        (state, token, ctx, None::<String>),
+        |(state, token, ctx, mut last)| async move {
+            loop {
+                if last.is_some() {
+                    tokio::time::sleep(STATUS_POLL).await;
+                }
+                // context deleted or access revoked: end the stream
+                let status = state
+                    .server
+                    .ctx_status(token.clone(), ctx.clone())
+                    .ok()?;
+                let json = serde_json::to_string(&status).ok()?;
+                if last.as_deref() != Some(&json) {
+                    let event =
+                        axum::response::sse::Event::default().data(&json);
+                    last = Some(json);
+                    return Some((
+                        Ok::<_, std::convert::Infallible>(event),
+                        (state, token, ctx, last),
+                    ));
+                }
+            }
+        },
+    );
+
+    Ok(axum::response::Sse::new(stream)
+        .keep_alive(axum::response::sse::KeepAlive::default())
+        .into_response())
 }
 
 async fn route_ctx_obj_put(
@@ -443,11 +1356,193 @@ async fn route_ctx_obj_put(
     payload: bytes::Bytes,
 ) -> AxumResult {
     let token = auth_token(&headers);
+
+    let (path, payload, content_type) = if is_multipart(&headers) {
+        // the first file part (or first unnamed-purpose part) is the
+        // object data; optional "created" / "expire" fields extend the
+        // app path the same way url-encoded components would, and an
+        // optional "content-type" field records the content type
+        let mut data = None;
+        let mut created = None;
+        let mut expire = None;
+        let mut content_type = None;
+        for part in parse_multipart(&headers, payload).await? {
+            match part.name.as_str() {
+                "created" if part.filename.is_none() => {
+                    created =
+                        Some(String::from_utf8_lossy(&part.data).to_string());
+                }
+                "expire" if part.filename.is_none() => {
+                    expire =
+                        Some(String::from_utf8_lossy(&part.data).to_string());
+                }
+                "content-type" if part.filename.is_none() => {
+                    content_type =
+                        Some(String::from_utf8_lossy(&part.data).to_string());
+                }
+                _ => {
+                    if data.is_none() {
+                        if content_type.is_none() {
+                            content_type = part.content_type.clone();
+                        }
+                        data = Some(part.data);
+                    }
+                }
+            }
+        }
+        let data = data.ok_or_else(|| {
+            Error::invalid("multipart obj-put requires a data part")
+        })?;
+        let path = if created.is_some() || expire.is_some() {
+            format!(
+                "{path}/{}/{}",
+                created.unwrap_or_else(|| safe_now().to_string()),
+                expire.unwrap_or_else(|| "0".to_string()),
+            )
+        } else {
+            path
+        };
+        (path, data, content_type)
+    } else {
+        // a content type on the request itself is recorded with the
+        // object
+        let content_type = headers
+            .get(axum::http::header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .map(ToString::to_string);
+        (path, payload, content_type)
+    };
+
     let meta = crate::obj::ObjMeta(format!("c/{ctx}/{path}").into());
-    let meta = state.server.obj_put(token, meta, payload).await?;
+    let meta =
+        state.server.obj_put(token, meta, payload, content_type).await?;
     Ok(meta.0.to_string().into_response())
 }
 
+async fn route_ctx_obj_raw(
+    headers: axum::http::HeaderMap,
+    axum::extract::Path((ctx, app_path)): axum::extract::Path<(String, String)>,
+    axum::extract::Query(query): axum::extract::Query<ObjGetQuery>,
+    axum::extract::ConnectInfo(_addr): axum::extract::ConnectInfo<
+        std::net::SocketAddr,
+    >,
+    axum::extract::State(state): axum::extract::State<Arc<State>>,
+) -> AxumResult {
+    let (meta, data) = match (&query.exp, &query.sig) {
+        // signed url access, no bearer token required
+        (Some(exp), Some(sig)) => {
+            state
+                .server
+                .obj_get_signed(ctx.into(), app_path, exp, sig)
+                .await?
+        }
+        _ => {
+            let token = auth_token(&headers);
+            state.server.obj_get(token, ctx.into(), app_path).await?
+        }
+    };
+    let content_type = meta
+        .content_type()
+        .unwrap_or_else(|| "application/octet-stream".to_string());
+    Ok((
+        [(axum::http::header::CONTENT_TYPE, content_type)],
+        data,
+    )
+        .into_response())
+}
+
+/// Per-part size cap for multipart form uploads.
+const PART_MAX_BYTES: usize = 5 * 1024 * 1024;
+
+/// Total size cap across all multipart form parts.
+const PARTS_MAX_TOTAL_BYTES: usize = 10 * 1024 * 1024;
+
+/// True when the request Content-Type is multipart/form-data.
+fn is_multipart(headers: &axum::http::HeaderMap) -> bool {
+    headers
+        .get(axum::http::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .map(|ct| {
+            ct.to_lowercase()
+                .trim_start()
+                .starts_with("multipart/form-data")
+        })
+        .unwrap_or(false)
+}
+
+/// Parse a buffered multipart/form-data body with axum's multipart
+/// extractor, enforcing per-part and total size caps.
+async fn parse_multipart(
+    headers: &axum::http::HeaderMap,
+    payload: bytes::Bytes,
+) -> Result<Vec<crate::js::FnReqPart>> {
+    use axum::extract::FromRequest;
+
+    let ct = headers
+        .get(axum::http::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("")
+        .to_string();
+
+    let req = axum::http::Request::builder()
+        .header(axum::http::header::CONTENT_TYPE, ct)
+        .body(axum::body::Body::from(payload))
+        .map_err(Error::other)?;
+
+    let mut multipart = axum::extract::Multipart::from_request(req, &())
+        .await
+        .map_err(Error::other)?;
+
+    let mut out = Vec::new();
+    let mut total = 0;
+
+    while let Some(field) =
+        multipart.next_field().await.map_err(Error::other)?
+    {
+        let name = field.name().unwrap_or("").to_string();
+        let filename = field.file_name().map(ToString::to_string);
+        let content_type = field.content_type().map(ToString::to_string);
+        let data = field.bytes().await.map_err(Error::other)?;
+        if data.len() > PART_MAX_BYTES {
+            return Err(Error::quota("multipart part too large"));
+        }
+        total += data.len();
+        if total > PARTS_MAX_TOTAL_BYTES {
+            return Err(Error::quota("multipart body too large"));
+        }
+        out.push(crate::js::FnReqPart {
+            name,
+            filename,
+            content_type,
+            data,
+        });
+    }
+
+    Ok(out)
+}
+
+/// Parse the request body as json when the Content-Type is
+/// application/json. Malformed json never fails the request,
+/// the structured body is just None.
+fn body_json(
+    headers: &axum::http::HeaderMap,
+    body: &Option<bytes::Bytes>,
+) -> Option<serde_json::Value> {
+    let body = body.as_ref()?;
+    let ct = headers
+        .get(axum::http::header::CONTENT_TYPE)?
+        .to_str()
+        .ok()?;
+    if !ct
+        .to_lowercase()
+        .trim_start()
+        .starts_with("application/json")
+    {
+        return None;
+    }
+    serde_json::from_slice(body).ok()
+}
+
 fn hdr(m: &axum::http::HeaderMap) -> std::collections::HashMap<String, String> {
     m.into_iter()
         .map(|(k, v)| {
@@ -459,6 +1554,27 @@ fn hdr(m: &axum::http::HeaderMap) -> std::collections::HashMap<String, String> {
         .collect()
 }
 
+#[derive(serde::Serialize)]
+struct CtxListOutput {
+    #[serde(rename = "ctxList")]
+    ctx_list: Vec<crate::server::CtxListEntry>,
+}
+
+async fn route_ctx_list(
+    headers: axum::http::HeaderMap,
+    axum::extract::ConnectInfo(_addr): axum::extract::ConnectInfo<
+        std::net::SocketAddr,
+    >,
+    axum::extract::State(state): axum::extract::State<Arc<State>>,
+) -> AxumResult {
+    let token = auth_token(&headers);
+    let result = state.server.ctx_list(token).await?;
+    Ok(
+        bytes::Bytes::from_encode(&CtxListOutput { ctx_list: result })?
+            .into_response(),
+    )
+}
+
 async fn route_obj_backup_full(
     headers: axum::http::HeaderMap,
     axum::extract::ConnectInfo(_addr): axum::extract::ConnectInfo<
@@ -488,6 +1604,7 @@ async fn route_fn(
     method: axum::http::Method,
     headers: axum::http::HeaderMap,
     axum::extract::Path((ctx, path)): axum::extract::Path<(String, String)>,
+    axum::Extension(TraceId(trace_id)): axum::Extension<TraceId>,
     axum::extract::ConnectInfo(_addr): axum::extract::ConnectInfo<
         std::net::SocketAddr,
     >,
@@ -499,11 +1616,22 @@ async fn route_fn(
     } else {
         Some(payload)
     };
+    let body_json = body_json(&headers, &body);
+    let parts = match &body {
+        Some(body) if is_multipart(&headers) => {
+            Some(parse_multipart(&headers, body.clone()).await?)
+        }
+        _ => None,
+    };
     let req = crate::js::JsRequest::FnReq {
         method: method.as_str().into(),
         path,
         body,
         headers: hdr(&headers),
+        body_json,
+        trace_id: Some(trace_id),
+        deadline_ms: None,
+        parts,
     };
     Ok(state.server.fn_req(ctx.into(), req).await?.into_response())
 }
@@ -513,6 +1641,7 @@ async fn route_fn_def(
     method: axum::http::Method,
     headers: axum::http::HeaderMap,
     axum::extract::Path(ctx): axum::extract::Path<String>,
+    axum::Extension(TraceId(trace_id)): axum::Extension<TraceId>,
     axum::extract::ConnectInfo(_addr): axum::extract::ConnectInfo<
         std::net::SocketAddr,
     >,
@@ -524,11 +1653,1250 @@ async fn route_fn_def(
     } else {
         Some(payload)
     };
+    let body_json = body_json(&headers, &body);
+    let parts = match &body {
+        Some(body) if is_multipart(&headers) => {
+            Some(parse_multipart(&headers, body.clone()).await?)
+        }
+        _ => None,
+    };
     let req = crate::js::JsRequest::FnReq {
         method: method.as_str().into(),
         path: "".into(),
         body,
         headers: hdr(&headers),
+        body_json,
+        trace_id: Some(trace_id),
+        deadline_ms: None,
+        parts,
     };
     Ok(state.server.fn_req(ctx.into(), req).await?.into_response())
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    async fn test_server() -> (std::net::SocketAddr, Runtime) {
+        let rth = RuntimeHandle::default();
+        rth.set_obj(obj::obj_file::ObjFile::create(None).await.unwrap());
+        rth.set_msg(msg::MsgMem::create());
+        let runtime = rth.runtime();
+        let server = server::Server::new(rth).await.unwrap();
+        let (s, r) = tokio::sync::oneshot::channel();
+        tokio::task::spawn(http_server(
+            s,
+            "127.0.0.1:0".parse().unwrap(),
+            server,
+        ));
+        let addr = r.await.unwrap();
+        (addr, runtime)
+    }
+
+    async fn test_server_with_code(
+        code: &str,
+    ) -> (std::net::SocketAddr, Runtime) {
+        let rth = RuntimeHandle::default();
+        rth.set_obj(obj::obj_file::ObjFile::create(None).await.unwrap());
+        rth.set_js(js::JsExecDefault::create());
+        rth.set_msg(msg::MsgMem::create());
+        let runtime = rth.runtime();
+        let server = server::Server::new(rth).await.unwrap();
+        let admin: Arc<str> = "admin".into();
+        server.set_sys_admin(vec![admin.clone()]).await.unwrap();
+        server
+            .ctx_setup_put(
+                admin.clone(),
+                server::CtxSetup {
+                    ctx: "test".into(),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        server
+            .ctx_config_put(
+                admin,
+                server::CtxConfig {
+                    ctx: "test".into(),
+                    code: code.into(),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        let (s, r) = tokio::sync::oneshot::channel();
+        tokio::task::spawn(http_server(
+            s,
+            "127.0.0.1:0".parse().unwrap(),
+            server,
+        ));
+        let addr = r.await.unwrap();
+        (addr, runtime)
+    }
+
+    const BOUNDARY: &str = "vmtestboundary";
+
+    /// Hand-roll a multipart/form-data body, returning the content
+    /// type header value and the body bytes.
+    fn form(parts: &[(&str, Option<&str>, &str)]) -> (String, Vec<u8>) {
+        let mut body = Vec::new();
+        for (name, filename, data) in parts {
+            body.extend_from_slice(format!("--{BOUNDARY}\r\n").as_bytes());
+            let mut cd =
+                format!("content-disposition: form-data; name=\"{name}\"");
+            if let Some(filename) = filename {
+                cd.push_str(&format!("; filename=\"{filename}\""));
+            }
+            body.extend_from_slice(format!("{cd}\r\n\r\n").as_bytes());
+            body.extend_from_slice(data.as_bytes());
+            body.extend_from_slice(b"\r\n");
+        }
+        body.extend_from_slice(format!("--{BOUNDARY}--\r\n").as_bytes());
+        (format!("multipart/form-data; boundary={BOUNDARY}"), body)
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn fn_req_body_limit() {
+        let rth = RuntimeHandle::default();
+        rth.set_obj(obj::obj_file::ObjFile::create(None).await.unwrap());
+        rth.set_js(js::JsExecDefault::create());
+        rth.set_msg(msg::MsgMem::create());
+        let server = server::Server::new(rth).await.unwrap();
+        let admin: Arc<str> = "admin".into();
+        server.set_sys_admin(vec![admin.clone()]).await.unwrap();
+        server
+            .ctx_setup_put(
+                admin.clone(),
+                server::CtxSetup {
+                    ctx: "test".into(),
+                    max_fn_request_bytes: Some(8),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        server
+            .ctx_config_put(
+                admin,
+                server::CtxConfig {
+                    ctx: "test".into(),
+                    code: "
+async function vm(req) {
+    if (req.type === 'fnReq') {
+        return { type: 'fnResOk' };
+    }
+    throw new Error('unhandled');
+}
+"
+                    .into(),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        let (s, r) = tokio::sync::oneshot::channel();
+        tokio::task::spawn(http_server(
+            s,
+            "127.0.0.1:0".parse().unwrap(),
+            server,
+        ));
+        let addr = r.await.unwrap();
+
+        let client = reqwest::Client::new();
+
+        // a body within the context limit is dispatched
+        let res = client
+            .post(format!("http://{addr}/test/hello"))
+            .body("12345678")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(200, res.status().as_u16());
+
+        // a larger body is rejected before reaching the function
+        let res = client
+            .post(format!("http://{addr}/test/hello"))
+            .body("123456789")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(413, res.status().as_u16());
+        let body: serde_json::Value = res.json().await.unwrap();
+        assert_eq!(
+            crate::error::code::TOO_LARGE,
+            body.get("code").unwrap().as_u64().unwrap() as u32,
+        );
+        assert!(!body.get("message").unwrap().as_str().unwrap().is_empty());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn access_token_query_param_fallback() {
+        let rth = RuntimeHandle::default();
+        rth.set_obj(obj::obj_file::ObjFile::create(None).await.unwrap());
+        rth.set_msg(msg::MsgMem::create());
+        let server = server::Server::new(rth).await.unwrap();
+        server.set_sys_admin(vec!["admin".into()]).await.unwrap();
+        let (s, r) = tokio::sync::oneshot::channel();
+        tokio::task::spawn(http_server(
+            s,
+            "127.0.0.1:0".parse().unwrap(),
+            server,
+        ));
+        let addr = r.await.unwrap();
+
+        let client = reqwest::Client::new();
+
+        // no credentials at all is refused
+        let res = client
+            .get(format!("http://{addr}/_vm_/ctx-list"))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(401, res.status().as_u16());
+
+        // clients that cannot set headers authenticate via the query
+        let res = client
+            .get(format!("http://{addr}/_vm_/ctx-list?access_token=admin"))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(200, res.status().as_u16());
+
+        // the header keeps precedence over the query parameter
+        let res = client
+            .get(format!("http://{addr}/_vm_/ctx-list?access_token=admin"))
+            .header("authorization", "Bearer bogus")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(401, res.status().as_u16());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn rate_limit_refuses_floods_but_exempts_sysadmin() {
+        let rth = RuntimeHandle::default();
+        rth.set_obj(obj::obj_file::ObjFile::create(None).await.unwrap());
+        rth.set_msg(msg::MsgMem::create());
+        let server = server::Server::new(rth).await.unwrap();
+        server.set_sys_admin(vec!["admin".into()]).await.unwrap();
+        server.set_rate_limit_rps(2.0);
+        let (s, r) = tokio::sync::oneshot::channel();
+        tokio::task::spawn(http_server(
+            s,
+            "127.0.0.1:0".parse().unwrap(),
+            server,
+        ));
+        let addr = r.await.unwrap();
+
+        let client = reqwest::Client::new();
+
+        // a quick unauthenticated burst drains the token bucket and
+        // the tail of the burst is refused
+        let mut saw_429 = false;
+        for _ in 0..10 {
+            let res = client
+                .get(format!("http://{addr}/"))
+                .send()
+                .await
+                .unwrap();
+            if res.status().as_u16() == 429 {
+                saw_429 = true;
+            }
+        }
+        assert!(saw_429);
+
+        // sysadmin-authenticated requests are exempt even with the
+        // bucket drained
+        let res = client
+            .get(format!("http://{addr}/_vm_/ctx-list"))
+            .header("authorization", "Bearer admin")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(200, res.status().as_u16());
+    }
+
+    /// Collects events at target "access" for assertions. Events are
+    /// emitted from the server's worker threads, so capture has to go
+    /// through the global dispatcher, not a thread-local one.
+    struct AccessCapture(std::sync::Mutex<Vec<String>>);
+
+    impl tracing::Subscriber for AccessCapture {
+        fn enabled(&self, metadata: &tracing::Metadata<'_>) -> bool {
+            metadata.target() == "access"
+        }
+
+        fn new_span(
+            &self,
+            _: &tracing::span::Attributes<'_>,
+        ) -> tracing::span::Id {
+            tracing::span::Id::from_u64(1)
+        }
+
+        fn record(
+            &self,
+            _: &tracing::span::Id,
+            _: &tracing::span::Record<'_>,
+        ) {
+        }
+
+        fn record_follows_from(
+            &self,
+            _: &tracing::span::Id,
+            _: &tracing::span::Id,
+        ) {
+        }
+
+        fn event(&self, event: &tracing::Event<'_>) {
+            struct V(String);
+            impl tracing::field::Visit for V {
+                fn record_debug(
+                    &mut self,
+                    field: &tracing::field::Field,
+                    value: &dyn std::fmt::Debug,
+                ) {
+                    self.0.push_str(&format!(
+                        "{}={:?} ",
+                        field.name(),
+                        value
+                    ));
+                }
+            }
+            let mut v = V(String::new());
+            event.record(&mut v);
+            self.0.lock().unwrap().push(v.0);
+        }
+
+        fn enter(&self, _: &tracing::span::Id) {}
+
+        fn exit(&self, _: &tracing::span::Id) {}
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn access_log_emits_structured_events() {
+        let capture = Arc::new(AccessCapture(Default::default()));
+        if tracing::subscriber::set_global_default(capture.clone()).is_err()
+        {
+            // another test already owns the global dispatcher
+            return;
+        }
+
+        let (addr, _runtime) = test_server_with_code(
+            "
+async function vm(req) {
+    if (req.type === 'fnReq') {
+        return { type: 'fnResOk' };
+    }
+    if (req.type === 'objCheckReq') {
+        return { type: 'objCheckResOk' };
+    }
+    throw new Error('unhandled');
+}
+",
+        )
+        .await;
+
+        let client = reqwest::Client::new();
+
+        // health checks are excluded by default
+        let res =
+            client.get(format!("http://{addr}/")).send().await.unwrap();
+        assert_eq!(200, res.status().as_u16());
+
+        // a function request, with a token long enough to redact
+        let res = client
+            .post(format!("http://{addr}/test/access-probe"))
+            .header("authorization", "Bearer longtokensecret")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(200, res.status().as_u16());
+
+        // an obj-put
+        let res = client
+            .put(format!("http://{addr}/test/_vm_/obj-put/probe-obj"))
+            .header("authorization", "Bearer admin")
+            .body("hello")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(200, res.status().as_u16());
+
+        let lines = capture.0.lock().unwrap().clone();
+        assert!(lines.iter().any(|l| {
+            l.contains("method=POST")
+                && l.contains("path=\"/test/access-probe\"")
+                && l.contains("ctx=\"test\"")
+                && l.contains("status=200")
+                && l.contains("token=\"longto\"")
+        }));
+        assert!(lines.iter().any(|l| {
+            l.contains("method=PUT")
+                && l.contains("path=\"/test/_vm_/obj-put/probe-obj\"")
+                && l.contains("status=200")
+        }));
+        assert!(lines.iter().all(|l| !l.contains("path=\"/\" ")));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn host_routing_for_mapped_domains() {
+        let (addr, _runtime) = test_server_with_code(
+            "
+async function vm(req) {
+    if (req.type === 'fnReq') {
+        return {
+            type: 'fnResOk',
+            body: new TextEncoder().encode(req.path),
+        };
+    }
+    throw new Error('unhandled');
+}
+",
+        )
+        .await;
+
+        let client = reqwest::Client::new();
+
+        // map a hostname to the test context; case and port are
+        // normalized away
+        let res = client
+            .put(format!("http://{addr}/_vm_/domains"))
+            .header("authorization", "Bearer admin")
+            .body(
+                bytes::Bytes::from_encode(&serde_json::json!({
+                    "h": "MyApp.Example.Com:8443",
+                    "c": "test",
+                }))
+                .unwrap(),
+            )
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(200, res.status().as_u16());
+
+        // unmapped hosts fall through to normal routing, where the
+        // first segment is an unknown context
+        let res = client
+            .get(format!("http://{addr}/deep/link"))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(404, res.status().as_u16());
+
+        // a mapped host routes straight into the context function
+        // with the full path passed through
+        let res = client
+            .get(format!("http://{addr}/deep/link"))
+            .header("host", "myapp.example.com")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(200, res.status().as_u16());
+        assert_eq!("deep/link", res.text().await.unwrap());
+
+        // the server's own routes win over the mapping
+        let res = client
+            .get(format!("http://{addr}/_vm_/ctx-list"))
+            .header("host", "myapp.example.com")
+            .header("authorization", "Bearer admin")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(200, res.status().as_u16());
+
+        // the stored map reflects the normalized hostname
+        let res = client
+            .get(format!("http://{addr}/_vm_/domains"))
+            .header("authorization", "Bearer admin")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(200, res.status().as_u16());
+        let body: serde_json::Value =
+            res.bytes().await.unwrap().to_decode().unwrap();
+        assert_eq!(
+            "test",
+            body.get("domains")
+                .unwrap()
+                .get("myapp.example.com")
+                .unwrap()
+                .as_str()
+                .unwrap(),
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn fn_req_methods_forwarded() {
+        let (addr, _runtime) = test_server_with_code(
+            "
+async function vm(req) {
+    if (req.type === 'fnReq') {
+        return {
+            type: 'fnResOk',
+            body: new TextEncoder().encode(req.method),
+        };
+    }
+    throw new Error('unhandled');
+}
+",
+        )
+        .await;
+
+        let client = reqwest::Client::new();
+        for method in ["GET", "PUT", "POST", "DELETE"] {
+            let res = client
+                .request(
+                    reqwest::Method::from_bytes(method.as_bytes()).unwrap(),
+                    format!("http://{addr}/test/api"),
+                )
+                .send()
+                .await
+                .unwrap();
+            assert_eq!(200, res.status().as_u16());
+            assert_eq!(method, res.text().await.unwrap());
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn config_versions_and_rollback() {
+        const CODE_ONE: &str = "
+async function vm(req) {
+    if (req.type === 'fnReq') {
+        return { type: 'fnResOk', body: new TextEncoder().encode('one') };
+    }
+    throw new Error('unhandled');
+}
+";
+        const CODE_TWO: &str = "
+async function vm(req) {
+    if (req.type === 'fnReq') {
+        return { type: 'fnResOk', body: new TextEncoder().encode('two') };
+    }
+    throw new Error('unhandled');
+}
+";
+
+        #[derive(serde::Deserialize)]
+        struct VersionsOutput {
+            versions: Vec<crate::server::CtxConfigVersion>,
+        }
+
+        let (addr, _runtime) = test_server_with_code(CODE_ONE).await;
+
+        let client = reqwest::Client::new();
+
+        let res = client
+            .post(format!("http://{addr}/test/hello"))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!("one", res.text().await.unwrap());
+
+        // deploy a second config version over http
+        let res = client
+            .put(format!("http://{addr}/test/_vm_/config"))
+            .header("authorization", "Bearer admin")
+            .body(
+                bytes::Bytes::from_encode(&server::CtxConfig {
+                    ctx: "test".into(),
+                    code: CODE_TWO.into(),
+                    ..Default::default()
+                })
+                .unwrap(),
+            )
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(200, res.status().as_u16());
+
+        let res = client
+            .post(format!("http://{addr}/test/hello"))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!("two", res.text().await.unwrap());
+
+        // both deploys are retained as versions
+        let res = client
+            .get(format!("http://{addr}/test/_vm_/config/versions"))
+            .header("authorization", "Bearer admin")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(200, res.status().as_u16());
+        let out: VersionsOutput =
+            res.bytes().await.unwrap().to_decode().unwrap();
+        assert_eq!(2, out.versions.len());
+        assert_eq!(1, out.versions[0].version);
+        assert_eq!(2, out.versions[1].version);
+        assert_ne!(out.versions[0].code_hash, out.versions[1].code_hash);
+        let hash_one = out.versions[0].code_hash.clone();
+
+        // a non-admin cannot roll back
+        let res = client
+            .put(format!("http://{addr}/test/_vm_/config/rollback/1"))
+            .header("authorization", "Bearer nope")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(401, res.status().as_u16());
+
+        // rolling back to version 1 restores the original behavior
+        let res = client
+            .put(format!("http://{addr}/test/_vm_/config/rollback/1"))
+            .header("authorization", "Bearer admin")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(200, res.status().as_u16());
+
+        let res = client
+            .post(format!("http://{addr}/test/hello"))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!("one", res.text().await.unwrap());
+
+        // the rollback itself is recorded as a new version
+        let res = client
+            .get(format!("http://{addr}/test/_vm_/config/versions"))
+            .header("authorization", "Bearer admin")
+            .send()
+            .await
+            .unwrap();
+        let out: VersionsOutput =
+            res.bytes().await.unwrap().to_decode().unwrap();
+        assert_eq!(3, out.versions.len());
+        assert_eq!(hash_one, out.versions[2].code_hash);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn fn_req_multipart_parts() {
+        let (addr, _runtime) = test_server_with_code(
+            "
+async function vm(req) {
+    if (req.type === 'fnReq') {
+        const parts = req.parts.map((p) => [
+            p.name,
+            p.filename || '',
+            new TextDecoder().decode(p.data),
+        ].join(':')).join('|');
+        return { type: 'fnResOk', body: new TextEncoder().encode(parts) };
+    }
+    throw new Error('unhandled');
+}
+",
+        )
+        .await;
+
+        let (ct, body) = form(&[
+            ("alpha", None, "hello"),
+            ("beta", Some("b.txt"), "world"),
+        ]);
+
+        let res = reqwest::Client::new()
+            .post(format!("http://{addr}/test/form"))
+            .header("content-type", ct)
+            .body(body)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(200, res.status().as_u16());
+        assert_eq!(
+            "alpha::hello|beta:b.txt:world",
+            res.text().await.unwrap()
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn obj_put_multipart() {
+        let (addr, runtime) = test_server_with_code(
+            "
+async function vm(req) {
+    if (req.type === 'objCheckReq') {
+        return { type: 'objCheckResOk' };
+    }
+    throw new Error('unhandled');
+}
+",
+        )
+        .await;
+
+        let (ct, body) = form(&[
+            ("expire", None, "0"),
+            ("file", Some("upload.bin"), "hello world"),
+        ]);
+
+        let res = reqwest::Client::new()
+            .put(format!("http://{addr}/test/_vm_/obj-put/myfile"))
+            .header("authorization", "Bearer admin")
+            .header("content-type", ct)
+            .body(body)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(200, res.status().as_u16());
+        let meta = crate::obj::ObjMeta(res.text().await.unwrap().into());
+        assert_eq!("myfile", meta.app_path());
+
+        let (_, data) = runtime.obj().unwrap().get(meta).await.unwrap();
+        assert_eq!(b"hello world", data.as_ref());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn status_stream_first_event_immediate() {
+        let (addr, _runtime) = test_server_with_code(
+            "
+async function vm(req) {
+    throw new Error('unhandled');
+}
+",
+        )
+        .await;
+
+        let mut res = reqwest::Client::new()
+            .get(format!("http://{addr}/test/_vm_/status-stream"))
+            .header("authorization", "Bearer admin")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(200, res.status().as_u16());
+        assert!(
+            res.headers()
+                .get(reqwest::header::CONTENT_TYPE)
+                .unwrap()
+                .to_str()
+                .unwrap()
+                .starts_with("text/event-stream"),
+        );
+
+        // the first snapshot must arrive well before the poll interval
+        let chunk = tokio::time::timeout(
+            std::time::Duration::from_secs(2),
+            res.chunk(),
+        )
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap();
+        let chunk = String::from_utf8_lossy(&chunk).to_string();
+        assert!(chunk.starts_with("data:"), "{chunk}");
+        assert!(chunk.contains("\"ctx\":\"test\""), "{chunk}");
+
+        // a bad token fails the request outright
+        let res = reqwest::Client::new()
+            .get(format!("http://{addr}/test/_vm_/status-stream"))
+            .header("authorization", "Bearer nope")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(401, res.status().as_u16());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn obj_sign_grants_unauthenticated_download() {
+        let (addr, _runtime) = test_server_with_code(
+            "
+async function vm(req) {
+    if (req.type === 'objCheckReq') {
+        return { type: 'objCheckResOk' };
+    }
+    throw new Error('unhandled');
+}
+",
+        )
+        .await;
+
+        let client = reqwest::Client::new();
+
+        let res = client
+            .put(format!("http://{addr}/test/_vm_/obj-put/report"))
+            .header("authorization", "Bearer admin")
+            .header("content-type", "text/plain")
+            .body("the report")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(200, res.status().as_u16());
+
+        // a ctxadmin can mint a signed link
+        let expires = safe_now() + 60.0;
+        let res = client
+            .post(format!(
+                "http://{addr}/test/_vm_/obj-sign/report?expires={expires}"
+            ))
+            .header("authorization", "Bearer admin")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(200, res.status().as_u16());
+        let link = res.text().await.unwrap();
+        assert!(link.starts_with("/test/_vm_/raw/report?exp="), "{link}");
+
+        // the link works without any bearer token
+        let res = client
+            .get(format!("http://{addr}{link}"))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(200, res.status().as_u16());
+        assert_eq!(
+            "text/plain",
+            res.headers()
+                .get(reqwest::header::CONTENT_TYPE)
+                .unwrap()
+                .to_str()
+                .unwrap(),
+        );
+        assert_eq!(b"the report", res.bytes().await.unwrap().as_ref());
+
+        // the signature does not transfer to another path
+        let tampered = link.replace("/raw/report", "/raw/other");
+        let res = client
+            .get(format!("http://{addr}{tampered}"))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(401, res.status().as_u16());
+
+        // an expired link is rejected
+        let expires = safe_now() - 60.0;
+        let res = client
+            .post(format!(
+                "http://{addr}/test/_vm_/obj-sign/report?expires={expires}"
+            ))
+            .header("authorization", "Bearer admin")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(200, res.status().as_u16());
+        let link = res.text().await.unwrap();
+        let res = client
+            .get(format!("http://{addr}{link}"))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(401, res.status().as_u16());
+
+        // signing requires a ctxadmin token
+        let res = client
+            .post(format!(
+                "http://{addr}/test/_vm_/obj-sign/report?expires=9999999999"
+            ))
+            .header("authorization", "Bearer nope")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(401, res.status().as_u16());
+        let body: serde_json::Value = res.json().await.unwrap();
+        assert_eq!(
+            crate::error::code::UNAUTHORIZED,
+            body.get("code").unwrap().as_u64().unwrap() as u32,
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn obj_list_detail_entries() {
+        let (addr, _runtime) = test_server_with_code(
+            "
+async function vm(req) {
+    if (req.type === 'objCheckReq') {
+        return { type: 'objCheckResOk' };
+    }
+    throw new Error('unhandled');
+}
+",
+        )
+        .await;
+
+        let client = reqwest::Client::new();
+
+        let res = client
+            .put(format!("http://{addr}/test/_vm_/obj-put/withct"))
+            .header("authorization", "Bearer admin")
+            .header("content-type", "text/plain")
+            .body("hello")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(200, res.status().as_u16());
+
+        let res = client
+            .put(format!("http://{addr}/test/_vm_/obj-put/without"))
+            .header("authorization", "Bearer admin")
+            .body("hi")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(200, res.status().as_u16());
+
+        // the default listing remains raw meta path strings
+        let res = client
+            .get(format!("http://{addr}/test/_vm_/obj-list"))
+            .header("authorization", "Bearer admin")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(200, res.status().as_u16());
+        #[derive(serde::Deserialize)]
+        struct Raw {
+            #[serde(rename = "metaList")]
+            meta_list: Vec<crate::obj::ObjMeta>,
+        }
+        let raw: Raw = res.bytes().await.unwrap().to_decode().unwrap();
+        assert_eq!(2, raw.meta_list.len());
+
+        // detail=true returns structured entries
+        let res = client
+            .get(format!("http://{addr}/test/_vm_/obj-list?detail=true"))
+            .header("authorization", "Bearer admin")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(200, res.status().as_u16());
+        #[derive(serde::Deserialize)]
+        struct Detail {
+            #[serde(rename = "metaList")]
+            meta_list: Vec<crate::obj::ObjListEntry>,
+        }
+        let detail: Detail = res.bytes().await.unwrap().to_decode().unwrap();
+        assert_eq!(2, detail.meta_list.len());
+        for entry in detail.meta_list {
+            match entry.app_path.as_str() {
+                "withct" => {
+                    assert_eq!(5, entry.size_bytes);
+                    assert_eq!(
+                        Some("text/plain".to_string()),
+                        entry.content_type,
+                    );
+                }
+                "without" => {
+                    assert_eq!(2, entry.size_bytes);
+                    assert_eq!(None, entry.content_type);
+                }
+                oth => panic!("unexpected entry: {oth}"),
+            }
+            assert!(entry.created_secs > 0.0);
+            assert_eq!(0.0, entry.expires_secs);
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn obj_raw_served_with_content_type() {
+        let (addr, _runtime) = test_server_with_code(
+            "
+async function vm(req) {
+    if (req.type === 'objCheckReq') {
+        return { type: 'objCheckResOk' };
+    }
+    throw new Error('unhandled');
+}
+",
+        )
+        .await;
+
+        let client = reqwest::Client::new();
+
+        // the content type on the put request is recorded in the meta
+        let res = client
+            .put(format!("http://{addr}/test/_vm_/obj-put/pic"))
+            .header("authorization", "Bearer admin")
+            .header("content-type", "image/svg+xml")
+            .body("<svg/>")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(200, res.status().as_u16());
+        let meta = crate::obj::ObjMeta(res.text().await.unwrap().into());
+        assert_eq!(Some("image/svg+xml".to_string()), meta.content_type());
+
+        // and served back by the raw route
+        let res = client
+            .get(format!("http://{addr}/test/_vm_/raw/pic"))
+            .header("authorization", "Bearer admin")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(200, res.status().as_u16());
+        assert_eq!(
+            "image/svg+xml",
+            res.headers()
+                .get(reqwest::header::CONTENT_TYPE)
+                .unwrap()
+                .to_str()
+                .unwrap(),
+        );
+        assert_eq!(b"<svg/>", res.bytes().await.unwrap().as_ref());
+
+        // objects stored without a content type fall back to
+        // octet-stream
+        let res = client
+            .put(format!("http://{addr}/test/_vm_/obj-put/blob"))
+            .header("authorization", "Bearer admin")
+            .body(b"raw".to_vec())
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(200, res.status().as_u16());
+
+        let res = client
+            .get(format!("http://{addr}/test/_vm_/raw/blob"))
+            .header("authorization", "Bearer admin")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(200, res.status().as_u16());
+        assert_eq!(
+            "application/octet-stream",
+            res.headers()
+                .get(reqwest::header::CONTENT_TYPE)
+                .unwrap()
+                .to_str()
+                .unwrap(),
+        );
+        assert_eq!(b"raw", res.bytes().await.unwrap().as_ref());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn obj_get_conditional_etag() {
+        let (addr, _runtime) = test_server_with_code(
+            "
+async function vm(req) {
+    if (req.type === 'objCheckReq') {
+        return { type: 'objCheckResOk' };
+    }
+    throw new Error('unhandled');
+}
+",
+        )
+        .await;
+
+        let client = reqwest::Client::new();
+
+        let res = client
+            .put(format!("http://{addr}/test/_vm_/obj-put/myfile"))
+            .header("authorization", "Bearer admin")
+            .body("hello world")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(200, res.status().as_u16());
+
+        // first fetch carries the etag and a no-cache directive
+        let res = client
+            .get(format!("http://{addr}/test/_vm_/obj-get/myfile"))
+            .header("authorization", "Bearer admin")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(200, res.status().as_u16());
+        let etag = res
+            .headers()
+            .get(reqwest::header::ETAG)
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .to_string();
+        assert!(etag.starts_with('"') && etag.ends_with('"'));
+        assert_eq!(
+            "no-cache",
+            res.headers()
+                .get(reqwest::header::CACHE_CONTROL)
+                .unwrap()
+                .to_str()
+                .unwrap(),
+        );
+        assert!(!res.bytes().await.unwrap().is_empty());
+
+        // second fetch with If-None-Match is a bodiless 304
+        let res = client
+            .get(format!("http://{addr}/test/_vm_/obj-get/myfile"))
+            .header("authorization", "Bearer admin")
+            .header(reqwest::header::IF_NONE_MATCH, &etag)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(304, res.status().as_u16());
+        assert_eq!(
+            Some(&etag),
+            res.headers()
+                .get(reqwest::header::ETAG)
+                .map(|v| v.to_str().unwrap())
+                .map(|v| v.to_string())
+                .as_ref(),
+        );
+        assert!(res.bytes().await.unwrap().is_empty());
+
+        // replace the object, the old etag must no longer match
+        let res = client
+            .put(format!("http://{addr}/test/_vm_/obj-put/myfile"))
+            .header("authorization", "Bearer admin")
+            .body("hello again")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(200, res.status().as_u16());
+
+        let res = client
+            .get(format!("http://{addr}/test/_vm_/obj-get/myfile"))
+            .header("authorization", "Bearer admin")
+            .header(reqwest::header::IF_NONE_MATCH, &etag)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(200, res.status().as_u16());
+        assert!(!res.bytes().await.unwrap().is_empty());
+    }
+
+    #[test]
+    fn body_json_parse() {
+        let mut headers = axum::http::HeaderMap::new();
+        let body = Some(bytes::Bytes::from_static(b"{\"a\":1}"));
+
+        // no content type
+        assert_eq!(None, body_json(&headers, &body));
+
+        // json content type
+        headers.insert(
+            axum::http::header::CONTENT_TYPE,
+            axum::http::HeaderValue::from_static("application/json"),
+        );
+        assert_eq!(
+            Some(serde_json::json!({ "a": 1 })),
+            body_json(&headers, &body),
+        );
+
+        // malformed json must not fail the request
+        let bad = Some(bytes::Bytes::from_static(b"{nope"));
+        assert_eq!(None, body_json(&headers, &bad));
+
+        // non-json content type
+        headers.insert(
+            axum::http::header::CONTENT_TYPE,
+            axum::http::HeaderValue::from_static("application/octet-stream"),
+        );
+        assert_eq!(None, body_json(&headers, &body));
+    }
+
+    #[test]
+    fn fn_res_body_json_response() {
+        let res = crate::js::JsResponse::FnResOk {
+            status: 200.0,
+            body: Default::default(),
+            body_json: Some(serde_json::json!({ "ok": true })),
+            headers: Default::default(),
+        }
+        .into_response();
+
+        assert_eq!(
+            "application/json",
+            res.headers()
+                .get(axum::http::header::CONTENT_TYPE)
+                .unwrap(),
+        );
+    }
+
+    #[test]
+    fn fn_res_framing_headers_dropped() {
+        let mut headers = std::collections::HashMap::new();
+        headers.insert("Connection".to_string(), "close".to_string());
+        headers.insert("transfer-encoding".to_string(), "chunked".to_string());
+        headers.insert("content-length".to_string(), "9999".to_string());
+        headers.insert("x-custom".to_string(), "ok".to_string());
+
+        let res = crate::js::JsResponse::FnResOk {
+            status: 200.0,
+            body: bytes::Bytes::from_static(b"hello"),
+            body_json: None,
+            headers,
+        }
+        .into_response();
+
+        assert!(!res.headers().contains_key("connection"));
+        assert!(!res.headers().contains_key("transfer-encoding"));
+        assert_eq!("ok", res.headers().get("x-custom").unwrap());
+        // the actual body length wins
+        assert_ne!(
+            Some(&axum::http::HeaderValue::from_static("9999")),
+            res.headers().get(axum::http::header::CONTENT_LENGTH),
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn trace_id_echo() {
+        let (addr, _runtime) = test_server().await;
+
+        // a provided X-Request-Id is echoed back
+        let res = reqwest::Client::new()
+            .get(format!("http://{addr}/"))
+            .header("x-request-id", "test-trace-id")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(
+            "test-trace-id",
+            res.headers().get("x-request-id").unwrap()
+        );
+
+        // one is minted when not provided
+        let res = reqwest::get(format!("http://{addr}/")).await.unwrap();
+        assert!(!res
+            .headers()
+            .get("x-request-id")
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn msg_recv_poll() {
+        let (addr, runtime) = test_server().await;
+
+        let ctx: Arc<str> = "testctx".into();
+        let msg_id =
+            runtime.msg().unwrap().create(ctx.clone()).await.unwrap();
+
+        {
+            let runtime = runtime.clone();
+            let ctx = ctx.clone();
+            let msg_id = msg_id.clone();
+            tokio::task::spawn(async move {
+                tokio::time::sleep(std::time::Duration::from_millis(100))
+                    .await;
+                runtime
+                    .msg()
+                    .unwrap()
+                    .send(
+                        ctx,
+                        msg_id,
+                        crate::msg::MessagePayload::App {
+                            msg: bytes::Bytes::from_static(b"hello"),
+                        },
+                    )
+                    .await
+                    .unwrap();
+            });
+        }
+
+        let res = reqwest::get(format!(
+            "http://{addr}/{ctx}/_vm_/msg/{msg_id}/recv?timeout_ms=5000"
+        ))
+        .await
+        .unwrap();
+
+        assert_eq!(200, res.status().as_u16());
+
+        let msg: crate::msg::Message =
+            res.bytes().await.unwrap().to_decode().unwrap();
+
+        assert_eq!(1, msg.seq);
+        match msg.payload {
+            crate::msg::MessagePayload::App { msg } => {
+                assert_eq!(b"hello", msg.as_ref());
+            }
+            _ => panic!("unexpected message type"),
+        }
+    }
+}
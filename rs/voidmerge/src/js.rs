@@ -27,7 +27,7 @@ pub enum JsRequest {
     },
     /// Incoming function request.
     FnReq {
-        /// The method ("GET" or "PUT").
+        /// The method ("GET", "PUT", "POST", or "DELETE").
         method: String,
         /// The request url.
         path: String,
@@ -35,7 +35,70 @@ pub enum JsRequest {
         body: Option<Bytes>,
         /// Any sent headers.
         headers: HashMap<String, String>,
+        /// The body content parsed as json, populated when the request
+        /// Content-Type is application/json and parsing succeeds.
+        /// The raw bytes are still present in `body`.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        body_json: Option<serde_json::Value>,
+        /// Trace id correlating this request across the stack.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        trace_id: Option<Arc<str>>,
+        /// Remaining time budget in milliseconds before the execution
+        /// is timed out, so handlers can bail early. Populated by the
+        /// executor just before the function is invoked.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        deadline_ms: Option<f64>,
+        /// Form parts parsed from a multipart/form-data request body.
+        /// The raw bytes are still present in `body`.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        parts: Option<Vec<FnReqPart>>,
     },
+    /// Benign request used to validate that context code compiles and
+    /// exports a callable `vm` function, before the code is committed.
+    /// User code need not handle it: a thrown error still counts as
+    /// proof the function is callable.
+    Ping,
+}
+
+/// A single form part of a multipart/form-data function request.
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FnReqPart {
+    /// The form field name.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub name: String,
+
+    /// The original file name, when supplied.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub filename: Option<String>,
+
+    /// The part content type, when supplied.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_type: Option<String>,
+
+    /// The part payload.
+    pub data: Bytes,
+}
+
+impl std::fmt::Debug for FnReqPart {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FnReqPart")
+            .field("name", &self.name)
+            .field("filename", &self.filename)
+            .field("content_type", &self.content_type)
+            .field("data_len", &self.data.len())
+            .finish()
+    }
+}
+
+impl JsRequest {
+    /// Get the trace id associated with this request, if any.
+    pub fn trace_id(&self) -> Option<Arc<str>> {
+        match self {
+            Self::FnReq { trace_id, .. } => trace_id.clone(),
+            _ => None,
+        }
+    }
 }
 
 impl std::fmt::Debug for JsRequest {
@@ -50,13 +113,21 @@ impl std::fmt::Debug for JsRequest {
                 .field("meta", meta)
                 .finish(),
             Self::FnReq {
-                method, path, body, ..
+                method,
+                path,
+                body,
+                body_json,
+                trace_id,
+                ..
             } => f
                 .debug_struct("JsRequest::FnReq")
                 .field("method", method)
                 .field("path", path)
                 .field("body_len", &body.as_ref().map(|b| b.len()).unwrap_or(0))
+                .field("body_json", &body_json.is_some())
+                .field("trace_id", trace_id)
                 .finish(),
+            Self::Ping => f.debug_struct("JsRequest::Ping").finish(),
         }
     }
 }
@@ -94,10 +165,18 @@ pub enum JsResponse {
         /// The body content.
         #[serde(default)]
         body: Bytes,
+        /// The body content as json. When `body` is empty, the server
+        /// serializes this with an application/json Content-Type.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        body_json: Option<serde_json::Value>,
         /// Any headers to send.
         #[serde(default)]
         headers: HashMap<String, String>,
     },
+
+    /// Response to a [JsRequest::Ping] validation request, synthesized
+    /// by the executor regardless of what user code returned.
+    Pong,
 }
 
 impl std::fmt::Debug for JsResponse {
@@ -116,6 +195,7 @@ impl std::fmt::Debug for JsResponse {
                 .field("status", status)
                 .field("body_len", &body.len())
                 .finish(),
+            Self::Pong => f.debug_struct("JsResponse::Pong").finish(),
         }
     }
 }
@@ -142,6 +222,52 @@ fn js_global_get_max_ram() -> usize {
     *MAX_RAM.get_or_init(|| 768 * 1024 * 1024)
 }
 
+static MAX_QUEUE: std::sync::OnceLock<usize> = std::sync::OnceLock::new();
+
+/// Set the max count of executions that may queue waiting for a free
+/// js thread. Additional executions fail fast with a QuotaExceeded
+/// error. (Default: 1024).
+pub fn js_global_set_max_queue(count: usize) -> bool {
+    MAX_QUEUE.set(count).is_ok()
+}
+
+fn js_global_get_max_queue() -> usize {
+    *MAX_QUEUE.get_or_init(|| 1024)
+}
+
+static ACQUIRE_TIMEOUT: std::sync::OnceLock<std::time::Duration> =
+    std::sync::OnceLock::new();
+
+/// Set the max time an execution may wait for a free js thread before
+/// failing with a QuotaExceeded error. (Default: 10s).
+pub fn js_global_set_acquire_timeout(timeout: std::time::Duration) -> bool {
+    ACQUIRE_TIMEOUT.set(timeout).is_ok()
+}
+
+fn js_global_get_acquire_timeout() -> std::time::Duration {
+    *ACQUIRE_TIMEOUT.get_or_init(|| std::time::Duration::from_secs(10))
+}
+
+static STARTED_SECS: std::sync::OnceLock<f64> = std::sync::OnceLock::new();
+
+fn js_global_get_started_secs() -> f64 {
+    *STARTED_SECS.get_or_init(crate::safe_now)
+}
+
+static RUN_MODE: std::sync::OnceLock<&'static str> = std::sync::OnceLock::new();
+
+/// Set the run mode reported to context code by `VM.info()`, e.g.
+/// "test" under `vm test`, and capture the process start time
+/// reported alongside it. (Default: "serve", started at first query).
+pub fn js_global_set_run_mode(mode: &'static str) -> bool {
+    let _ = STARTED_SECS.set(crate::safe_now());
+    RUN_MODE.set(mode).is_ok()
+}
+
+fn js_global_get_run_mode() -> &'static str {
+    RUN_MODE.get_or_init(|| "serve")
+}
+
 /// Javascript setup info.
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct JsSetup {
@@ -157,9 +283,26 @@ pub struct JsSetup {
     /// Max heap size for the context. Default: 32 MiB.
     pub heap_size: usize,
 
+    /// Max total byte length of [JsSetup::code] plus all module
+    /// sources. Default: 1 MiB.
+    pub max_code_bytes: usize,
+
     /// Javascript code to initialize.
     pub code: Arc<str>,
 
+    /// Javascript es module sources keyed by module specifier.
+    /// When non-empty, [JsSetup::entry] is loaded as the main module
+    /// instead of evaluating [JsSetup::code].
+    pub modules: Arc<std::collections::BTreeMap<Arc<str>, Arc<str>>>,
+
+    /// Entry module specifier within [JsSetup::modules]. The entry
+    /// module must export the `vm` function.
+    pub entry: Arc<str>,
+
+    /// Wasm module for the context. Only dispatched when the executor
+    /// chain includes a wasm executor; see the `wasm` feature.
+    pub wasm: Option<Bytes>,
+
     /// Javascript env to make available.
     pub env: Arc<serde_json::Value>,
 }
@@ -171,6 +314,29 @@ impl JsSetup {
 
     /// Default heap size.
     pub const DEF_HEAP_SIZE: usize = 1024 * 1024 * 32;
+
+    /// Default max code bytes.
+    pub const DEF_MAX_CODE_BYTES: usize = 1024 * 1024;
+
+    /// Total byte length of [JsSetup::code] plus all module sources.
+    pub fn code_bytes(&self) -> usize {
+        self.code.len()
+            + self.modules.values().map(|source| source.len()).sum::<usize>()
+    }
+}
+
+/// A point-in-time snapshot of js executor load.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct JsStats {
+    /// Thread permits currently in use.
+    pub threads_in_use: usize,
+
+    /// Requests currently waiting in the bounded queue for a permit.
+    pub queue_len: usize,
+
+    /// Total requests rejected because the queue was full or the
+    /// acquire wait timed out.
+    pub rejected: u64,
 }
 
 static JS: std::sync::OnceLock<Js> = std::sync::OnceLock::new();
@@ -183,6 +349,19 @@ pub trait JsExec: 'static + Send + Sync {
         setup: JsSetup,
         request: JsRequest,
     ) -> BoxFut<'_, Result<JsResponse>>;
+
+    /// Pre-warm `count` execution threads for this setup, if the
+    /// executor pools threads. The default implementation is a no-op.
+    fn warm(&self, setup: JsSetup, count: usize) -> BoxFut<'_, ()> {
+        drop((setup, count));
+        Box::pin(async {})
+    }
+
+    /// Get a load snapshot, if the executor pools threads. The
+    /// default implementation reports an empty snapshot.
+    fn stats(&self) -> JsStats {
+        JsStats::default()
+    }
 }
 
 /// Dyn [JsExec] type.
@@ -214,6 +393,18 @@ impl JsExec for JsExecDefault {
                 .await
         })
     }
+
+    fn warm(&self, setup: JsSetup, count: usize) -> BoxFut<'_, ()> {
+        Box::pin(async move {
+            JS.get_or_init(Js::new)
+                .warm(setup, count, self.0.clone())
+                .await
+        })
+    }
+
+    fn stats(&self) -> JsStats {
+        JS.get_or_init(Js::new).stats()
+    }
 }
 
 /// Javascript Executor Wrapper Adding Metering.
@@ -253,19 +444,46 @@ impl JsExec for JsExecMeter {
             res
         })
     }
+
+    // warmup is operator-initiated provisioning, not request traffic,
+    // so it passes through unmetered
+    fn warm(&self, setup: JsSetup, count: usize) -> BoxFut<'_, ()> {
+        self.0.warm(setup, count)
+    }
+
+    fn stats(&self) -> JsStats {
+        self.0.stats()
+    }
 }
 
 /// Javascript execution.
 struct Js {
+    max_threads: usize,
+    max_queue: usize,
     thread_limit: Arc<tokio::sync::Semaphore>,
     ram_mib_limit: Arc<tokio::sync::Semaphore>,
-    pool: Arc<Mutex<JsPool>>,
+    queue_limit: Arc<tokio::sync::Semaphore>,
+    acquire_timeout: std::time::Duration,
+    rejected: std::sync::atomic::AtomicU64,
+    pool: ShardedJsPool,
 }
 
 impl Js {
     pub fn new() -> Self {
-        let max_threads = js_global_get_max_thread();
-        let max_ram = js_global_get_max_ram();
+        Self::with_limits(
+            js_global_get_max_thread(),
+            js_global_get_max_ram(),
+            js_global_get_max_queue(),
+            js_global_get_acquire_timeout(),
+        )
+    }
+
+    fn with_limits(
+        max_threads: usize,
+        max_ram: usize,
+        max_queue: usize,
+        acquire_timeout: std::time::Duration,
+    ) -> Self {
         if max_ram < 1024 * 1024 {
             panic!("max ram cannot be less that 1MiB");
         }
@@ -274,10 +492,55 @@ impl Js {
             panic!("max ram is too large in MiB for a u32");
         }
         Self {
+            max_threads,
+            max_queue,
             thread_limit: Arc::new(tokio::sync::Semaphore::new(max_threads)),
             ram_mib_limit: Arc::new(tokio::sync::Semaphore::new(max_ram_mib)),
-            pool: Arc::new(Mutex::new(JsPool::new(max_threads))),
+            queue_limit: Arc::new(tokio::sync::Semaphore::new(max_queue)),
+            acquire_timeout,
+            rejected: std::sync::atomic::AtomicU64::new(0),
+            pool: ShardedJsPool::new(),
+        }
+    }
+
+    /// Get a point-in-time load snapshot. A queue permit is held for
+    /// the duration of a permit wait, so unavailable queue permits
+    /// count requests currently waiting.
+    pub fn stats(&self) -> JsStats {
+        JsStats {
+            threads_in_use: self
+                .max_threads
+                .saturating_sub(self.thread_limit.available_permits()),
+            queue_len: self
+                .max_queue
+                .saturating_sub(self.queue_limit.available_permits()),
+            rejected: self.rejected.load(std::sync::atomic::Ordering::Relaxed),
+        }
+    }
+
+    fn mark_rejected(&self, ctx: &Arc<str>) {
+        self.rejected
+            .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        crate::meter::meter_fn_rejected(ctx);
+    }
+
+    /// Pre-create up to `count` parked ready threads for this setup,
+    /// paying the runtime creation and code init cost now instead of
+    /// on the first request. Threads are warmed by running the same
+    /// code-config request every context already issues at startup;
+    /// once complete the threads sit ready in the pool.
+    pub async fn warm(&self, setup: JsSetup, count: usize, weak: WeakJsExec) {
+        let mut all = Vec::with_capacity(count);
+        for _ in 0..count {
+            all.push(self.exec(
+                setup.clone(),
+                JsRequest::CodeConfigReq,
+                weak.clone(),
+            ));
         }
+        // user code need not handle the request; a js-level error
+        // still leaves a warm parked thread behind
+        let _ = futures::future::join_all(all).await;
     }
 
     pub async fn exec(
@@ -286,27 +549,72 @@ impl Js {
         request: JsRequest,
         weak: WeakJsExec,
     ) -> Result<JsResponse> {
-        let avail = self.ram_mib_limit.available_permits() * 1024 * 1024;
-        let want = setup.heap_size;
-        let clear = want.saturating_sub(avail);
-        let mut found = self.pool.lock().unwrap().get_thread(&setup, clear);
+        let mut found = self.pool.get_thread(&setup);
 
         if found.is_none() {
-            let t_fut = self.thread_limit.clone().acquire_owned();
-
             if setup.heap_size < 1024 * 1024 {
                 panic!("heap_size cannot be less than 1 MiB");
             }
 
-            let r_fut = self
-                .ram_mib_limit
-                .clone()
-                .acquire_many_owned((setup.heap_size / (1024 * 1024)) as u32);
+            // drop enough cached idle threads to free the ram this
+            // request needs before trying to acquire permits
+            let avail = self.ram_mib_limit.available_permits() * 1024 * 1024;
+            let clear = setup.heap_size.saturating_sub(avail);
+            if clear > 0 {
+                self.pool.clear_heap(clear);
+            }
 
-            let (thread_permit, ram_permit) =
-                tokio::try_join!(t_fut, r_fut).expect("permit error");
+            let want_mib = (setup.heap_size / (1024 * 1024)) as u32;
+
+            // fast path: capacity is immediately available
+            let fast = match self.thread_limit.clone().try_acquire_owned() {
+                Ok(t) => match self
+                    .ram_mib_limit
+                    .clone()
+                    .try_acquire_many_owned(want_mib)
+                {
+                    Ok(r) => Some((t, r)),
+                    Err(_) => None,
+                },
+                Err(_) => None,
+            };
+
+            let (thread_permit, ram_permit) = match fast {
+                Some(permits) => permits,
+                None => {
+                    // capacity is exhausted: wait in a bounded queue,
+                    // for a bounded amount of time, so bursts fail
+                    // fast instead of building unbounded latency
+                    let _queue_permit = self
+                        .queue_limit
+                        .clone()
+                        .try_acquire_owned()
+                        .map_err(|_| {
+                            self.mark_rejected(&setup.ctx);
+                            Error::quota("js exec queue is full")
+                        })?;
+
+                    let t_fut = self.thread_limit.clone().acquire_owned();
+                    let r_fut = self
+                        .ram_mib_limit
+                        .clone()
+                        .acquire_many_owned(want_mib);
+
+                    tokio::time::timeout(self.acquire_timeout, async move {
+                        tokio::try_join!(t_fut, r_fut)
+                            .expect("permit error")
+                    })
+                    .await
+                    .map_err(|_| {
+                        self.mark_rejected(&setup.ctx);
+                        Error::quota(
+                            "timed out waiting for a free js thread",
+                        )
+                    })?
+                }
+            };
 
-            found = Some(self.pool.lock().unwrap().get_or_create_thread(
+            found = Some(self.pool.get_or_create_thread(
                 thread_permit,
                 ram_permit,
                 &setup,
@@ -320,34 +628,94 @@ impl Js {
         // if the thread errored, don't return it
         // if we are out of permits, don't return it
         if thread.is_ready() && self.ram_mib_limit.available_permits() > 0 {
-            self.pool.lock().unwrap().put_thread(setup, thread);
+            self.pool.put_thread(setup, thread);
         }
 
         out
     }
 }
 
+const POOL_SHARD_COUNT: usize = 16;
+
+/// A pool of idle js threads sharded by setup hash, so concurrent
+/// executions for different setups don't serialize on one mutex.
+/// All threads for a given setup live in one shard.
+struct ShardedJsPool {
+    shards: Vec<Mutex<JsPool>>,
+}
+
+impl ShardedJsPool {
+    pub fn new() -> Self {
+        Self {
+            shards: (0..POOL_SHARD_COUNT)
+                .map(|_| Mutex::new(JsPool::new()))
+                .collect(),
+        }
+    }
+
+    fn shard(&self, setup: &JsSetup) -> &Mutex<JsPool> {
+        use std::hash::{Hash, Hasher};
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        setup.hash(&mut hasher);
+        &self.shards[hasher.finish() as usize % self.shards.len()]
+    }
+
+    /// Take a cached ready thread matching this setup, if any.
+    pub fn get_thread(&self, want_setup: &JsSetup) -> Option<JsThread> {
+        self.shard(want_setup).lock().unwrap().get_thread(want_setup)
+    }
+
+    /// Drop cached idle threads until at least `clear_heap` bytes of
+    /// their reserved heap have been released, walking every shard.
+    pub fn clear_heap(&self, clear_heap: usize) {
+        let mut remaining = clear_heap;
+        for shard in self.shards.iter() {
+            remaining =
+                remaining.saturating_sub(shard.lock().unwrap().clear_heap(
+                    remaining,
+                ));
+            if remaining == 0 {
+                return;
+            }
+        }
+    }
+
+    pub fn get_or_create_thread(
+        &self,
+        thread_permit: tokio::sync::OwnedSemaphorePermit,
+        ram_permit: tokio::sync::OwnedSemaphorePermit,
+        setup: &JsSetup,
+    ) -> JsThread {
+        // another exec with the same setup may have returned a thread
+        // while we were waiting on permits
+        match self.get_thread(setup) {
+            Some(thread) => thread,
+            None => JsThread::new(thread_permit, ram_permit),
+        }
+    }
+
+    pub fn put_thread(&self, setup: JsSetup, thread: JsThread) {
+        self.shard(&setup)
+            .lock()
+            .unwrap()
+            .put_thread(setup, thread);
+    }
+}
+
 struct JsPool {
-    #[allow(dead_code)]
-    max_threads: usize,
     last_prune: std::time::Instant,
     threads: HashMap<JsSetup, Vec<JsThread>>,
 }
 
 impl JsPool {
-    pub fn new(max_threads: usize) -> Self {
+    pub fn new() -> Self {
         Self {
-            max_threads,
             last_prune: std::time::Instant::now(),
             threads: Default::default(),
         }
     }
 
-    pub fn get_thread(
-        &mut self,
-        want_setup: &JsSetup,
-        clear_heap: usize,
-    ) -> Option<JsThread> {
+    pub fn get_thread(&mut self, want_setup: &JsSetup) -> Option<JsThread> {
         if self.last_prune.elapsed() > std::time::Duration::from_secs(5) {
             self.last_prune = std::time::Instant::now();
             self.threads.retain(|_, list| !list.is_empty());
@@ -363,7 +731,13 @@ impl JsPool {
             }
         }
 
-        // otherwise, try to clear enough space for the request
+        None
+    }
+
+    /// Drop cached idle threads until at least `clear_heap` bytes of
+    /// their reserved heap have been released, returning the amount
+    /// actually cleared.
+    pub fn clear_heap(&mut self, clear_heap: usize) -> usize {
         let mut clear_amount = 0;
         self.threads.retain(|setup, list| {
             list.retain(|_| {
@@ -376,22 +750,7 @@ impl JsPool {
             });
             !list.is_empty()
         });
-
-        None
-    }
-
-    pub fn get_or_create_thread(
-        &mut self,
-        thread_permit: tokio::sync::OwnedSemaphorePermit,
-        ram_permit: tokio::sync::OwnedSemaphorePermit,
-        setup: &JsSetup,
-    ) -> JsThread {
-        // we can set a clear heap size of zero here,
-        // since we already got the permit.
-        match self.get_thread(setup, 0) {
-            Some(thread) => thread,
-            None => JsThread::new(thread_permit, ram_permit),
-        }
+        clear_amount
     }
 
     pub fn put_thread(&mut self, setup: JsSetup, thread: JsThread) {
@@ -406,11 +765,20 @@ use std::rc::Rc;
 struct TState {
     pub setup: JsSetup,
     pub weak: WeakJsExec,
+    pub trace_id: Option<Arc<str>>,
 }
 
 impl TState {
-    pub fn new(setup: JsSetup, weak: WeakJsExec) -> Self {
-        TState { setup, weak }
+    pub fn new(
+        setup: JsSetup,
+        weak: WeakJsExec,
+        trace_id: Option<Arc<str>>,
+    ) -> Self {
+        TState {
+            setup,
+            weak,
+            trace_id,
+        }
     }
 }
 
@@ -446,6 +814,63 @@ mod deno_ext {
         }
     }
 
+    #[deno_core::op2]
+    #[serde]
+    fn op_trace_id(
+        state: Rc<RefCell<OpState>>,
+    ) -> std::result::Result<Option<Arc<str>>, deno_core::error::CoreError>
+    {
+        match state.borrow().try_borrow::<TState>() {
+            Some(TState { trace_id, .. }) => Ok(trace_id.clone()),
+            _ => Err(deno_core::error::CoreErrorKind::Io(Error::other(
+                "bad state",
+            ))
+            .into()),
+        }
+    }
+
+    // additive-only: older entry.js bundles ignore fields they do not
+    // know about, so new fields may be appended but none removed
+    #[derive(Debug, serde::Serialize)]
+    struct VmInfo {
+        ctx: Arc<str>,
+
+        #[serde(rename = "timeoutSecs")]
+        timeout_secs: f64,
+
+        #[serde(rename = "maxHeapBytes")]
+        max_heap_bytes: usize,
+
+        #[serde(rename = "serverVersion")]
+        server_version: &'static str,
+
+        #[serde(rename = "startedSecs")]
+        started_secs: f64,
+
+        mode: &'static str,
+    }
+
+    #[deno_core::op2]
+    #[serde]
+    fn op_vm_info(
+        state: Rc<RefCell<OpState>>,
+    ) -> std::result::Result<VmInfo, deno_core::error::CoreError> {
+        match state.borrow().try_borrow::<TState>() {
+            Some(TState { setup, .. }) => Ok(VmInfo {
+                ctx: setup.ctx.clone(),
+                timeout_secs: setup.timeout.as_secs_f64(),
+                max_heap_bytes: setup.heap_size,
+                server_version: env!("CARGO_PKG_VERSION"),
+                started_secs: js_global_get_started_secs(),
+                mode: js_global_get_run_mode(),
+            }),
+            _ => Err(deno_core::error::CoreErrorKind::Io(Error::other(
+                "bad state",
+            ))
+            .into()),
+        }
+    }
+
     #[deno_core::op2]
     #[buffer]
     fn op_to_utf8(#[string] input: &str) -> Vec<u8> {
@@ -458,6 +883,143 @@ mod deno_ext {
         String::from_utf8_lossy(input).to_string()
     }
 
+    #[deno_core::op2]
+    #[buffer]
+    fn op_derive_key(
+        #[buffer] secret: &[u8],
+        #[buffer] info: &[u8],
+    ) -> Vec<u8> {
+        crate::secret::derive_key(secret, info).to_vec()
+    }
+
+    #[deno_core::op2]
+    #[buffer]
+    fn op_hash_sha512(#[buffer] input: &[u8]) -> Vec<u8> {
+        use sha2::Digest;
+        sha2::Sha512::digest(input).to_vec()
+    }
+
+    #[deno_core::op2(fast)]
+    fn op_wasm_validate(#[buffer] input: &[u8]) -> bool {
+        #[cfg(feature = "wasm")]
+        {
+            crate::wasm::validate(input)
+        }
+        #[cfg(not(feature = "wasm"))]
+        {
+            // without the wasm feature, only the magic and version
+            // header is checked; instantiation still fails cleanly on
+            // a deeper-broken module
+            input.starts_with(b"\0asm\x01\0\0\0")
+        }
+    }
+
+    /// Escape an arbitrary string into the characters accepted in
+    /// app path components, reversible via op_unescape_path. Worst
+    /// case the output is three times the utf-8 byte length of the
+    /// input, up to twelve characters per unicode scalar, so size
+    /// any path length budgets against the escaped form.
+    #[deno_core::op2]
+    #[string]
+    fn op_escape_path(#[string] input: &str) -> String {
+        crate::obj::escape_path_component(input)
+    }
+
+    /// Reverse op_escape_path. A truncated or non-hex escape
+    /// sequence reports an error.
+    #[deno_core::op2]
+    #[string]
+    fn op_unescape_path(
+        #[string] input: &str,
+    ) -> std::result::Result<String, deno_core::error::CoreError> {
+        crate::obj::unescape_path_component(input)
+            .map_err(|err| deno_core::error::CoreErrorKind::Io(err).into())
+    }
+
+    /// Resolve an rfc 6901 json pointer (`/foo/0/bar`) against
+    /// msgpack-encoded data, e.g. object bytes inside validation
+    /// logic, without decoding the whole structure into user code.
+    /// A pointer that does not resolve returns null; malformed
+    /// msgpack input reports an error.
+    #[deno_core::op2]
+    #[serde]
+    fn op_value_pointer_get(
+        #[string] ptr: &str,
+        #[buffer] data: &[u8],
+    ) -> std::result::Result<serde_json::Value, deno_core::error::CoreError>
+    {
+        let value: serde_json::Value =
+            rmp_serde::from_slice(data).map_err(|err| {
+                deno_core::error::CoreError::from(
+                    deno_core::error::CoreErrorKind::Io(Error::invalid(err)),
+                )
+            })?;
+        Ok(value.pointer(ptr).cloned().unwrap_or(serde_json::Value::Null))
+    }
+
+    /// Verify a detached signature over `data`. Key and signature
+    /// lengths are validated up front and malformed inputs report an
+    /// error; a well-formed but wrong signature returns false.
+    #[deno_core::op2]
+    fn op_sign_verify(
+        #[string] alg: &str,
+        #[buffer] pub_key: &[u8],
+        #[buffer] signature: &[u8],
+        #[buffer] data: &[u8],
+    ) -> std::result::Result<bool, deno_core::error::CoreError> {
+        use aws_lc_rs::signature as s;
+        let (algorithm, key_len): (&'static dyn s::VerificationAlgorithm, _) =
+            match alg {
+                "ed25519" => (&s::ED25519, 32),
+                // uncompressed sec1 public key, fixed r||s signature,
+                // matching what crypto.subtle exports and produces
+                "p256" => (&s::ECDSA_P256_SHA256_FIXED, 65),
+                _ => {
+                    return Err(deno_core::error::CoreErrorKind::Io(
+                        Error::invalid(format!(
+                            "unsupported signature algorithm: {alg}"
+                        )),
+                    )
+                    .into());
+                }
+            };
+        if pub_key.len() != key_len {
+            return Err(deno_core::error::CoreErrorKind::Io(Error::invalid(
+                format!("{alg} public key must be {key_len} bytes"),
+            ))
+            .into());
+        }
+        if signature.len() != 64 {
+            return Err(deno_core::error::CoreErrorKind::Io(Error::invalid(
+                format!("{alg} signature must be 64 bytes"),
+            ))
+            .into());
+        }
+        Ok(s::UnparsedPublicKey::new(algorithm, pub_key)
+            .verify(data, signature)
+            .is_ok())
+    }
+
+    #[deno_core::op2]
+    #[string]
+    fn op_random_uuid() -> String {
+        use rand::Rng;
+        let mut b = [0u8; 16];
+        rand::rng().fill(&mut b);
+        // rfc 9562 version 4 / variant 1 bits
+        b[6] = (b[6] & 0x0f) | 0x40;
+        b[8] = (b[8] & 0x3f) | 0x80;
+        let hex: String = b.iter().map(|b| format!("{b:02x}")).collect();
+        format!(
+            "{}-{}-{}-{}-{}",
+            &hex[0..8],
+            &hex[8..12],
+            &hex[12..16],
+            &hex[16..20],
+            &hex[20..32],
+        )
+    }
+
     #[derive(Debug, serde::Serialize)]
     struct MsgNewOutput {
         #[serde(rename = "msgId")]
@@ -539,13 +1101,60 @@ mod deno_ext {
             .send(
                 setup.ctx,
                 input.msg_id,
-                crate::msg::Message::App { msg: input.msg },
+                crate::msg::MessagePayload::App { msg: input.msg },
             )
             .await?;
 
         Ok(())
     }
 
+    #[deno_core::op2(async)]
+    async fn op_msg_broadcast(
+        state: Rc<RefCell<OpState>>,
+        #[buffer(copy)] data: bytes::Bytes,
+    ) -> std::result::Result<(), deno_core::error::CoreError> {
+        let setup = match state.borrow().try_borrow::<TState>() {
+            Some(TState { setup, .. }) => setup.clone(),
+            _ => {
+                return Err(deno_core::error::CoreErrorKind::Io(Error::other(
+                    "bad state",
+                ))
+                .into());
+            }
+        };
+
+        setup
+            .runtime
+            .msg()?
+            .broadcast(setup.ctx, crate::msg::MessagePayload::App { msg: data })
+            .await?;
+
+        Ok(())
+    }
+
+    #[deno_core::op2(async)]
+    #[buffer]
+    async fn op_secret_get(
+        state: Rc<RefCell<OpState>>,
+        #[string] name: String,
+    ) -> std::result::Result<Vec<u8>, deno_core::error::CoreError> {
+        let setup = match state.borrow().try_borrow::<TState>() {
+            Some(TState { setup, .. }) => setup.clone(),
+            _ => {
+                return Err(deno_core::error::CoreErrorKind::Io(Error::other(
+                    "bad state",
+                ))
+                .into());
+            }
+        };
+
+        let value =
+            crate::secret::get(&setup.runtime.obj()?, &setup.ctx, &name)
+                .await?;
+
+        Ok(value.to_vec())
+    }
+
     #[derive(Debug, serde::Deserialize)]
     struct ObjPutInput {
         #[serde(default)]
@@ -553,6 +1162,16 @@ mod deno_ext {
 
         #[serde(default)]
         data: bytes::Bytes,
+
+        /// Absolute expiry as unix epoch seconds. Overrides any
+        /// expiry encoded in `meta`.
+        #[serde(default, rename = "expiresSecs")]
+        expires_secs: Option<f64>,
+
+        /// Relative time-to-live in seconds from now. `expiresSecs`
+        /// wins if both are provided.
+        #[serde(default, rename = "ttlSecs")]
+        ttl_secs: Option<f64>,
     }
 
     #[derive(Debug, serde::Serialize)]
@@ -567,7 +1186,7 @@ mod deno_ext {
         #[serde] input: ObjPutInput,
     ) -> std::result::Result<ObjPutOutput, deno_core::error::CoreError> {
         let (setup, weak) = match state.borrow().try_borrow::<TState>() {
-            Some(TState { setup, weak }) => (setup.clone(), weak.clone()),
+            Some(TState { setup, weak, .. }) => (setup.clone(), weak.clone()),
             _ => {
                 return Err(deno_core::error::CoreErrorKind::Io(Error::other(
                     "bad state",
@@ -576,13 +1195,29 @@ mod deno_ext {
             }
         };
 
+        // read-only mode covers js-initiated writes too, so a GET
+        // function cannot mutate the store around the route gate
+        if setup.runtime.read_only() {
+            return Err(deno_core::error::CoreErrorKind::Io(
+                Error::unauthorized("server is in read-only mode"),
+            )
+            .into());
+        }
+
         let input_meta = crate::obj::ObjMeta(input.meta);
 
+        let now = safe_now();
+        let expires_secs = match (input.expires_secs, input.ttl_secs) {
+            (Some(abs), _) => abs,
+            (None, Some(ttl)) => now + ttl,
+            (None, None) => input_meta.expires_secs(),
+        };
+
         let meta = crate::obj::ObjMeta::new_context(
             &setup.ctx,
             input_meta.app_path(),
-            safe_now(),
-            input_meta.expires_secs(),
+            now,
+            expires_secs,
             input.data.len() as f64,
         );
 
@@ -700,6 +1335,14 @@ mod deno_ext {
             }
         };
 
+        // read-only mode covers js-initiated deletes too
+        if setup.runtime.read_only() {
+            return Err(deno_core::error::CoreErrorKind::Io(
+                Error::unauthorized("server is in read-only mode"),
+            )
+            .into());
+        }
+
         let meta = crate::obj::ObjMeta(input.meta);
         if meta.sys_prefix() != crate::obj::ObjMeta::SYS_CTX {
             return Err(deno_core::error::CoreErrorKind::Io(Error::other(
@@ -734,7 +1377,13 @@ mod deno_ext {
         #[serde(rename = "createdGt", default)]
         created_gt: f64,
 
-        #[serde(default = "f64_1000")]
+        #[serde(rename = "createdLt", default)]
+        created_lt: Option<f64>,
+
+        #[serde(default)]
+        descending: bool,
+
+        #[serde(default = "f64_1000")]
         limit: f64,
     }
 
@@ -772,7 +1421,13 @@ mod deno_ext {
         let result = setup
             .runtime
             .obj()?
-            .list(&path, input.created_gt, limit)
+            .list_range(
+                &path,
+                input.created_gt,
+                input.created_lt,
+                input.descending,
+                limit,
+            )
             .await
             .map_err(|err| {
                 deno_core::error::CoreError::from(
@@ -783,21 +1438,157 @@ mod deno_ext {
         Ok(ObjListOutput { meta_list: result })
     }
 
+    fn f64_mib() -> f64 {
+        1024.0 * 1024.0
+    }
+
+    #[derive(Debug, serde::Deserialize)]
+    struct ObjQueryInput {
+        #[serde(rename = "appPathPrefixes", default)]
+        app_path_prefixes: Vec<Arc<str>>,
+
+        #[serde(rename = "createdGt", default)]
+        created_gt: f64,
+
+        #[serde(rename = "createdLt", default)]
+        created_lt: Option<f64>,
+
+        #[serde(default = "f64_1000")]
+        limit: f64,
+
+        #[serde(rename = "returnData", default)]
+        return_data: bool,
+
+        #[serde(rename = "maxDataBytes", default = "f64_mib")]
+        max_data_bytes: f64,
+    }
+
+    #[derive(Debug, serde::Serialize)]
+    struct ObjQueryEntry {
+        meta: Arc<str>,
+
+        #[serde(skip_serializing_if = "Option::is_none")]
+        data: Option<Bytes>,
+    }
+
+    #[derive(Debug, serde::Serialize)]
+    struct ObjQueryOutput {
+        entries: Vec<ObjQueryEntry>,
+        truncated: bool,
+    }
+
+    #[deno_core::op2(async)]
+    #[serde]
+    async fn op_obj_query(
+        state: Rc<RefCell<OpState>>,
+        #[serde] input: ObjQueryInput,
+    ) -> std::result::Result<ObjQueryOutput, deno_core::error::CoreError> {
+        let setup = match state.borrow().try_borrow::<TState>() {
+            Some(TState { setup, .. }) => setup.clone(),
+            _ => {
+                return Err(deno_core::error::CoreErrorKind::Io(Error::other(
+                    "bad state",
+                ))
+                .into());
+            }
+        };
+
+        let limit = input.limit.clamp(0.0, 1000.0) as u32;
+
+        let obj = setup.runtime.obj()?;
+
+        // merge every prefix, dropping duplicates where prefixes
+        // overlap
+        let mut seen = std::collections::HashSet::new();
+        let mut meta_list = Vec::new();
+        for prefix in input.app_path_prefixes.iter() {
+            let path = format!(
+                "{}/{}/{prefix}",
+                crate::obj::ObjMeta::SYS_CTX,
+                setup.ctx,
+            );
+            let result = obj
+                .list_range(
+                    &path,
+                    input.created_gt,
+                    input.created_lt,
+                    false,
+                    limit,
+                )
+                .await
+                .map_err(|err| {
+                    deno_core::error::CoreError::from(
+                        deno_core::error::CoreErrorKind::Io(err),
+                    )
+                })?;
+            for meta in result {
+                if seen.insert(meta.0.clone()) {
+                    meta_list.push(meta);
+                }
+            }
+        }
+
+        meta_list
+            .sort_by(|a, b| a.created_secs().total_cmp(&b.created_secs()));
+        meta_list.truncate(limit as usize);
+
+        let mut budget = input.max_data_bytes.max(0.0) as u64;
+        let mut truncated = false;
+        let mut entries = Vec::with_capacity(meta_list.len());
+        for meta in meta_list {
+            let data = if input.return_data && !truncated {
+                let (_, data) =
+                    obj.get(meta.clone()).await.map_err(|err| {
+                        deno_core::error::CoreError::from(
+                            deno_core::error::CoreErrorKind::Io(err),
+                        )
+                    })?;
+                if data.len() as u64 > budget {
+                    // from here on, entries come back meta-only
+                    truncated = true;
+                    None
+                } else {
+                    budget -= data.len() as u64;
+                    Some(data)
+                }
+            } else {
+                None
+            };
+
+            entries.push(ObjQueryEntry { meta: meta.0, data });
+        }
+
+        Ok(ObjQueryOutput { entries, truncated })
+    }
+
     deno_core::extension!(
         vm,
         deps = [deno_console],
         ops = [
             op_get_ctx,
             op_get_env,
+            op_trace_id,
+            op_vm_info,
             op_to_utf8,
             op_from_utf8,
+            op_derive_key,
+            op_hash_sha512,
+            op_wasm_validate,
+            op_escape_path,
+            op_unescape_path,
+            op_value_pointer_get,
+            op_sign_verify,
+            op_random_uuid,
             op_msg_new,
             op_msg_list,
             op_msg_send,
+            op_msg_broadcast,
+            op_secret_get,
             op_obj_put,
             op_obj_get,
             op_obj_rm,
             op_obj_list,
+            op_obj_query,
         ],
         esm_entry_point = "ext:vm/entry.js",
         esm = [ dir "src/js", "entry.js" ],
@@ -815,6 +1606,24 @@ enum Cmd {
     },
 }
 
+/// Convert a rustyscript error into an io error, appending up to the
+/// top five v8 stack frames of a js exception so logic authors can
+/// locate the failing line in their context code.
+fn js_error_with_stack(err: rustyscript::Error) -> std::io::Error {
+    let rustyscript::Error::JsError(js) = &err else {
+        return std::io::Error::other(err);
+    };
+    let mut msg = js.exception_message.clone();
+    for frame in js.frames.iter().take(5) {
+        let function = frame.function_name.as_deref().unwrap_or("<anon>");
+        let file = frame.file_name.as_deref().unwrap_or("<anon>");
+        let line = frame.line_number.unwrap_or(0);
+        let col = frame.column_number.unwrap_or(0);
+        msg.push_str(&format!("\n  at {function} ({file}:{line}:{col})"));
+    }
+    std::io::Error::other(msg)
+}
+
 struct JsThread {
     _thread_permit: tokio::sync::OwnedSemaphorePermit,
     _ram_permit: tokio::sync::OwnedSemaphorePermit,
@@ -933,6 +1742,23 @@ impl JsThread {
             }
 
             loop {
+                // defense in depth: the config put path already
+                // rejects oversized code before it is stored, but
+                // setups can also reach a thread directly
+                let code_bytes = cur_setup.code_bytes();
+                if code_bytes > cur_setup.max_code_bytes {
+                    on_drop.not_ready();
+                    let _ = cur_output.send(Err(std::io::Error::new(
+                        std::io::ErrorKind::FileTooLarge,
+                        format!(
+                            "js code is {code_bytes} bytes, exceeding \
+                             the {} byte limit",
+                            cur_setup.max_code_bytes,
+                        ),
+                    )));
+                    return;
+                }
+
                 let extensions = vec![deno_ext::vm::init()];
 
                 let opts = rustyscript::RuntimeOptions {
@@ -944,36 +1770,133 @@ impl JsThread {
 
                 let mut rust = rustyscript::Runtime::new(opts).unwrap();
 
-                rust.put(TState::new(cur_setup.clone(), cur_weak.clone()))
-                    .unwrap();
-
-                if let Err(err) = rust.eval::<()>(&cur_setup.code) {
-                    on_drop.not_ready();
-                    let _ = cur_output.send(Err(std::io::Error::other(err)));
-                    return;
-                }
+                rust.put(TState::new(
+                    cur_setup.clone(),
+                    cur_weak.clone(),
+                    cur_request.trace_id(),
+                ))
+                .unwrap();
+
+                let module_handle = if cur_setup.modules.is_empty() {
+                    if let Err(err) = rust.eval::<()>(&cur_setup.code) {
+                        on_drop.not_ready();
+                        let _ =
+                            cur_output.send(Err(js_error_with_stack(err)));
+                        return;
+                    }
+                    None
+                } else {
+                    let mut entry = None;
+                    let mut side = Vec::new();
+                    for (specifier, source) in cur_setup.modules.iter() {
+                        let module =
+                            rustyscript::Module::new(&**specifier, source);
+                        if **specifier == *cur_setup.entry {
+                            entry = Some(module);
+                        } else {
+                            side.push(module);
+                        }
+                    }
+                    let Some(entry) = entry else {
+                        on_drop.not_ready();
+                        let _ = cur_output.send(Err(std::io::Error::other(
+                            format!(
+                                "entry module not found: {}",
+                                cur_setup.entry,
+                            ),
+                        )));
+                        return;
+                    };
+                    // any import not satisfied by the provided module
+                    // map fails here, before a request is processed
+                    match rust.load_modules(&entry, side.iter().collect()) {
+                        Ok(handle) => Some(handle),
+                        Err(err) => {
+                            on_drop.not_ready();
+                            let _ = cur_output
+                                .send(Err(js_error_with_stack(err)));
+                            return;
+                        }
+                    }
+                };
 
                 loop {
                     tracing::trace!(js_request = ?cur_request);
 
+                    // refresh the per-request state so ops see the
+                    // trace id of the request currently executing
+                    rust.put(TState::new(
+                        cur_setup.clone(),
+                        cur_weak.clone(),
+                        cur_request.trace_id(),
+                    ))
+                    .unwrap();
+
+                    // let handlers know their remaining time budget
+                    if let JsRequest::FnReq { deadline_ms, .. } =
+                        &mut cur_request
+                        && deadline_ms.is_none()
+                    {
+                        *deadline_ms =
+                            Some(cur_setup.timeout.as_millis() as f64);
+                    }
+
                     let res: Result<JsResponse> = match rust
                         .tokio_runtime()
                         .block_on(async {
-                            tokio::time::timeout(
-                                cur_setup.timeout,
-                                rust.call_function_async(
-                                    None,
-                                    "vm",
-                                    rustyscript::json_args!(cur_request),
-                                ),
-                            )
-                            .await
+                            tokio::select! {
+                                // the client went away: the oneshot
+                                // receiver for this exec was dropped
+                                _ = cur_output.closed() => None,
+                                r = tokio::time::timeout(
+                                    cur_setup.timeout,
+                                    // json_args is a misnomer: it is a
+                                    // no-op tuple wrapper, and the args
+                                    // cross into v8 through serde_v8,
+                                    // so Bytes fields like the request
+                                    // body arrive as real Uint8Arrays
+                                    rust.call_function_async(
+                                        module_handle.as_ref(),
+                                        "vm",
+                                        rustyscript::json_args!(cur_request),
+                                    ),
+                                ) => Some(r),
+                            }
                         }) {
-                        Ok(Ok(r)) => Ok(r),
-                        Ok(Err(err @ rustyscript::Error::JsError(_))) => {
-                            Err(std::io::Error::other(err))
+                        None => {
+                            tracing::debug!(
+                                "JS exec cancelled, Aborting v8 isolate"
+                            );
+                            crate::meter::meter_fn_cancelled(&cur_setup.ctx);
+                            on_drop.not_ready();
+                            return;
                         }
-                        Ok(Err(err)) => {
+                        Some(Ok(Ok(_)))
+                            if matches!(cur_request, JsRequest::Ping) =>
+                        {
+                            Ok(JsResponse::Pong)
+                        }
+                        Some(Ok(Ok(r))) => Ok(r),
+                        // a ping only proves the vm export is callable:
+                        // user code throwing on the unknown request
+                        // type, or returning something that is not a
+                        // JsResponse, still counts as success
+                        Some(Ok(Err(
+                            rustyscript::Error::JsError(_)
+                            | rustyscript::Error::JsonDecode(_),
+                        ))) if matches!(cur_request, JsRequest::Ping) => {
+                            Ok(JsResponse::Pong)
+                        }
+                        Some(Ok(Err(err @ rustyscript::Error::JsError(_)))) => {
+                            let err = js_error_with_stack(err);
+                            Err(match cur_request.trace_id() {
+                                Some(tid) => {
+                                    err.with_info(format!("traceId={tid}"))
+                                }
+                                None => err,
+                            })
+                        }
+                        Some(Ok(Err(err))) => {
                             let err = if matches!(
                                 err,
                                 rustyscript::Error::Runtime(_)
@@ -993,7 +1916,7 @@ impl JsThread {
                             let _ = cur_output.send(Err(err));
                             return;
                         }
-                        Err(_) => {
+                        Some(Err(_)) => {
                             tracing::debug!(
                                 "JS Timeout Error, Aborting v8 isolate"
                             );
@@ -1044,6 +1967,284 @@ mod unit_tests;
 mod test {
     use super::*;
 
+    #[tokio::test(flavor = "multi_thread")]
+    async fn js_exec_backpressure() {
+        fn setup() -> JsSetup {
+            JsSetup {
+                runtime: RuntimeHandle::default().runtime(),
+                ctx: "test".into(),
+                env: Arc::new(serde_json::Value::Null),
+                modules: Default::default(),
+                entry: "".into(),
+                wasm: None,
+                code: "".into(),
+                timeout: JsSetup::DEF_TIMEOUT,
+                heap_size: JsSetup::DEF_HEAP_SIZE,
+                max_code_bytes: JsSetup::DEF_MAX_CODE_BYTES,
+            }
+        }
+
+        let req = JsRequest::FnReq {
+            method: "GET".into(),
+            path: "".into(),
+            body: None,
+            headers: Default::default(),
+            body_json: None,
+            trace_id: None,
+            deadline_ms: None,
+            parts: None,
+        };
+
+        let weak: WeakJsExec = std::sync::Weak::<JsExecDefault>::new();
+
+        // all thread permits in use and the queue full:
+        // exec must fail fast with QuotaExceeded
+        let js = Js::with_limits(
+            1,
+            JsSetup::DEF_HEAP_SIZE,
+            0,
+            std::time::Duration::from_millis(10),
+        );
+        let _thread = js.thread_limit.clone().acquire_owned().await.unwrap();
+        let err = js
+            .exec(setup(), req.clone(), weak.clone())
+            .await
+            .unwrap_err();
+        assert_eq!(std::io::ErrorKind::QuotaExceeded, err.kind());
+        let stats = js.stats();
+        assert_eq!(1, stats.threads_in_use);
+        assert_eq!(0, stats.queue_len);
+        assert_eq!(1, stats.rejected);
+
+        // all thread permits in use but queue space available:
+        // exec must fail with QuotaExceeded after the acquire timeout
+        let js = Js::with_limits(
+            1,
+            JsSetup::DEF_HEAP_SIZE,
+            8,
+            std::time::Duration::from_millis(10),
+        );
+        let _thread = js.thread_limit.clone().acquire_owned().await.unwrap();
+        let err = js.exec(setup(), req, weak).await.unwrap_err();
+        assert_eq!(std::io::ErrorKind::QuotaExceeded, err.kind());
+        assert_eq!(1, js.stats().rejected);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn js_exec_cancel_on_client_drop() {
+        let js = Js::with_limits(
+            1,
+            JsSetup::DEF_HEAP_SIZE,
+            8,
+            JsSetup::DEF_TIMEOUT,
+        );
+        let t = js.thread_limit.clone().acquire_owned().await.unwrap();
+        let r = js
+            .ram_mib_limit
+            .clone()
+            .acquire_many_owned((JsSetup::DEF_HEAP_SIZE / (1024 * 1024)) as u32)
+            .await
+            .unwrap();
+
+        let setup = JsSetup {
+            runtime: RuntimeHandle::default().runtime(),
+            ctx: "test".into(),
+            env: Arc::new(serde_json::Value::Null),
+            modules: Default::default(),
+            entry: "".into(),
+            wasm: None,
+            code: "async function vm(req) { await new Promise(() => {}); }"
+                .into(),
+            timeout: JsSetup::DEF_TIMEOUT,
+            heap_size: JsSetup::DEF_HEAP_SIZE,
+            max_code_bytes: JsSetup::DEF_MAX_CODE_BYTES,
+        };
+
+        let req = JsRequest::FnReq {
+            method: "GET".into(),
+            path: "".into(),
+            body: None,
+            headers: Default::default(),
+            body_json: None,
+            trace_id: None,
+            deadline_ms: None,
+            parts: None,
+        };
+
+        let weak: WeakJsExec = std::sync::Weak::<JsExecDefault>::new();
+
+        let thread = JsThread::new(t, r);
+
+        {
+            let fut = thread.exec(setup, req, weak);
+            // give the never-resolving handler time to start,
+            // then drop the "client" by dropping the exec future
+            tokio::select! {
+                _ = fut => panic!("handler should not complete"),
+                _ = tokio::time::sleep(
+                    std::time::Duration::from_secs(1),
+                ) => (),
+            }
+        }
+
+        // the js thread must go down well before the 10s exec timeout
+        let start = std::time::Instant::now();
+        while thread.is_ready() {
+            if start.elapsed() > std::time::Duration::from_secs(5) {
+                panic!("js thread was not cancelled");
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn js_pool_handoff() {
+        fn setup(ctx: &str, runtime: Runtime) -> JsSetup {
+            JsSetup {
+                runtime,
+                ctx: ctx.into(),
+                env: Arc::new(serde_json::Value::Null),
+                modules: Default::default(),
+                entry: "".into(),
+                wasm: None,
+                code: "".into(),
+                timeout: JsSetup::DEF_TIMEOUT,
+                heap_size: JsSetup::DEF_HEAP_SIZE,
+                max_code_bytes: JsSetup::DEF_MAX_CODE_BYTES,
+            }
+        }
+
+        let rth = RuntimeHandle::default();
+        let pool = ShardedJsPool::new();
+
+        let sem = Arc::new(tokio::sync::Semaphore::new(64));
+
+        let a = setup("aaaa", rth.runtime());
+        let b = setup("bbbb", rth.runtime());
+
+        // empty pool: nothing to hand out
+        assert!(pool.get_thread(&a).is_none());
+
+        let t = JsThread::new(
+            sem.clone().try_acquire_owned().unwrap(),
+            sem.clone().try_acquire_owned().unwrap(),
+        );
+        pool.put_thread(a.clone(), t);
+
+        // a cached thread only matches its own setup
+        assert!(pool.get_thread(&b).is_none());
+
+        // and is handed out exactly once
+        let t = pool.get_thread(&a).unwrap();
+        assert!(pool.get_thread(&a).is_none());
+        pool.put_thread(a.clone(), t);
+
+        // clearing heap drops the cached thread
+        pool.clear_heap(1);
+        assert!(pool.get_thread(&a).is_none());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn js_warm_parks_threads() {
+        let rth = RuntimeHandle::default();
+        let setup = JsSetup {
+            runtime: rth.runtime(),
+            ctx: "warm".into(),
+            env: Arc::new(serde_json::Value::Null),
+            modules: Default::default(),
+            entry: "".into(),
+            wasm: None,
+            code: "
+async function vm(req) {
+    if (req.type === 'codeConfigReq') {
+        return { type: 'codeConfigResOk' };
+    }
+    throw new Error('unhandled');
+}
+"
+            .into(),
+            timeout: JsSetup::DEF_TIMEOUT,
+            heap_size: JsSetup::DEF_HEAP_SIZE,
+            max_code_bytes: JsSetup::DEF_MAX_CODE_BYTES,
+        };
+
+        let weak: WeakJsExec = std::sync::Weak::<JsExecDefault>::new();
+
+        let js = Js::with_limits(
+            4,
+            JsSetup::DEF_HEAP_SIZE * 4,
+            8,
+            JsSetup::DEF_TIMEOUT,
+        );
+
+        js.warm(setup.clone(), 2, weak).await;
+
+        // both warmed threads are parked ready in the pool
+        let a = js.pool.get_thread(&setup).unwrap();
+        assert!(a.is_ready());
+        let b = js.pool.get_thread(&setup).unwrap();
+        assert!(b.is_ready());
+        assert!(js.pool.get_thread(&setup).is_none());
+    }
+
+    #[ignore = "Run this test in isolation via `cargo test -- --ignored js_throughput`"]
+    #[tokio::test(flavor = "multi_thread")]
+    async fn js_throughput() {
+        // a few thousand tiny fn requests spread over a handful of
+        // setups: guards against pool lock contention collapsing
+        // throughput now that the pool is sharded
+        const SETUPS: usize = 8;
+        const REQS: usize = 2048;
+
+        let rth = RuntimeHandle::default();
+        let obj = obj::obj_file::ObjFile::create(None).await.unwrap();
+        rth.set_obj(obj);
+
+        let mut setups = Vec::with_capacity(SETUPS);
+        for id in 0..SETUPS {
+            setups.push(JsSetup {
+                runtime: rth.runtime(),
+                ctx: format!("ctx-{id}").into(),
+                env: Arc::new(serde_json::Value::Null),
+                modules: Default::default(),
+                entry: "".into(),
+                wasm: None,
+                code: "
+async function vm(req) {
+    return { type: 'fnResOk' };
+}
+"
+                .into(),
+                timeout: JsSetup::DEF_TIMEOUT,
+                heap_size: JsSetup::DEF_HEAP_SIZE,
+                max_code_bytes: JsSetup::DEF_MAX_CODE_BYTES,
+            });
+        }
+
+        let req = JsRequest::FnReq {
+            method: "GET".into(),
+            path: "".into(),
+            body: None,
+            headers: Default::default(),
+            body_json: None,
+            trace_id: None,
+            deadline_ms: None,
+            parts: None,
+        };
+
+        let js = JsExecDefault::create();
+
+        let start = std::time::Instant::now();
+        let mut all = Vec::with_capacity(REQS);
+        for id in 0..REQS {
+            all.push(js.exec(setups[id % SETUPS].clone(), req.clone()));
+        }
+        let res = futures::future::try_join_all(all).await.unwrap();
+        assert_eq!(REQS, res.len());
+        println!("{REQS} requests in {:?}", start.elapsed());
+        assert!(start.elapsed() < std::time::Duration::from_secs(60));
+    }
+
     #[ignore = "Run this test in isolation via `cargo test -- --ignored js_stress`"]
     #[tokio::test(flavor = "multi_thread")]
     async fn js_stress() {
@@ -1056,6 +2257,9 @@ mod test {
                 runtime,
                 ctx: format!("ctx-{id}").into(),
                 env: Arc::new(serde_json::Value::Null),
+                modules: Default::default(),
+                entry: "".into(),
+                wasm: None,
                 code: format!(
                     "
 async function vm(req) {{
@@ -1070,6 +2274,7 @@ async function vm(req) {{
                 .into(),
                 timeout: JsSetup::DEF_TIMEOUT,
                 heap_size: JsSetup::DEF_HEAP_SIZE * 5,
+                max_code_bytes: JsSetup::DEF_MAX_CODE_BYTES,
             }
         }
 
@@ -1087,6 +2292,10 @@ async function vm(req) {{
             path: "".into(),
             body: None,
             headers: Default::default(),
+            body_json: None,
+            trace_id: None,
+            deadline_ms: None,
+            parts: None,
         };
 
         for r in 1..=10 {
@@ -1110,36 +2319,612 @@ async function vm(req) {{
     }
 
     #[tokio::test(flavor = "multi_thread")]
-    async fn js_simple() {
+    async fn js_msg_broadcast() {
         let rth = RuntimeHandle::default();
         let obj = obj::obj_file::ObjFile::create(None).await.unwrap();
         rth.set_obj(obj);
+        rth.set_msg(msg::MsgMem::create());
+
+        let ctx: Arc<str> = "bobbo".into();
+
+        let msg_id = rth
+            .runtime()
+            .msg()
+            .unwrap()
+            .create(ctx.clone())
+            .await
+            .unwrap();
+        let mut recv = rth
+            .runtime()
+            .msg()
+            .unwrap()
+            .get_recv(ctx.clone(), msg_id)
+            .await
+            .unwrap();
 
         let setup = JsSetup {
             runtime: rth.runtime(),
-            ctx: "bobbo".into(),
+            ctx,
             env: Arc::new(serde_json::Value::Null),
+            modules: Default::default(),
+            entry: "".into(),
+            wasm: None,
             code: "
 async function vm(req) {
-    if (req.type === 'objCheckReq') {
-        return { type: 'objCheckResOk' };
-    } else if (req.type === 'fnReq') {
-        const b = (new TextEncoder()).encode('hello');
-        console.log('encode', b, b instanceof Uint8Array);
-        const s = (new TextDecoder()).decode(b);
-        console.log('decode', s);
+    if (req.type === 'fnReq') {
+        await VM.msgBroadcast(new TextEncoder().encode('hello'));
+        return { type: 'fnResOk' };
+    }
+    throw new Error('unhandled');
+}
+"
+            .into(),
+            timeout: JsSetup::DEF_TIMEOUT,
+            heap_size: JsSetup::DEF_HEAP_SIZE,
+            max_code_bytes: JsSetup::DEF_MAX_CODE_BYTES,
+        };
 
-        const { meta } = await VM.objPut({
-            meta: 'c/A/test',
-            data: new TextEncoder().encode('hello'),
-        });
-        console.log(`put returned meta: ${meta}`);
+        let req = JsRequest::FnReq {
+            method: "GET".into(),
+            path: "".into(),
+            body: None,
+            headers: Default::default(),
+            body_json: None,
+            trace_id: None,
+            deadline_ms: None,
+            parts: None,
+        };
 
-        const { data } = await VM.objGet({ meta });
-        const res = new TextDecoder().decode(data);
-        console.log(`fetched: ${res}`);
+        let js = JsExecDefault::create();
 
-        const { metaList } = await VM.objList({
+        js.exec(setup, req).await.unwrap();
+
+        let got = recv.recv().await.unwrap();
+        assert_eq!(1, got.seq);
+        match got.payload {
+            msg::MessagePayload::App { msg } => {
+                assert_eq!(b"hello", msg.as_ref());
+            }
+            _ => panic!("unexpected message type"),
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn js_msg_send_list() {
+        let rth = RuntimeHandle::default();
+        let obj = obj::obj_file::ObjFile::create(None).await.unwrap();
+        rth.set_obj(obj);
+        rth.set_msg(msg::MsgMem::create());
+
+        let ctx: Arc<str> = "bobbo".into();
+
+        let msg_id = rth
+            .runtime()
+            .msg()
+            .unwrap()
+            .create(ctx.clone())
+            .await
+            .unwrap();
+        let mut recv = rth
+            .runtime()
+            .msg()
+            .unwrap()
+            .get_recv(ctx.clone(), msg_id.clone())
+            .await
+            .unwrap();
+
+        let setup = JsSetup {
+            runtime: rth.runtime(),
+            ctx,
+            env: Arc::new(serde_json::json!({ "msgId": &*msg_id })),
+            modules: Default::default(),
+            entry: "".into(),
+            wasm: None,
+            code: "
+async function vm(req) {
+    if (req.type === 'fnReq') {
+        const msgId = VM.env().msgId;
+        const { msgIdList } = await VM.msgList();
+        if (!msgIdList.includes(msgId)) {
+            throw new Error(`msg id not listed: ${msgIdList}`);
+        }
+        await VM.msgSend({
+            msgId,
+            msg: new TextEncoder().encode('direct'),
+        });
+        return { type: 'fnResOk' };
+    }
+    throw new Error('unhandled');
+}
+"
+            .into(),
+            timeout: JsSetup::DEF_TIMEOUT,
+            heap_size: JsSetup::DEF_HEAP_SIZE,
+            max_code_bytes: JsSetup::DEF_MAX_CODE_BYTES,
+        };
+
+        let req = JsRequest::FnReq {
+            method: "GET".into(),
+            path: "".into(),
+            body: None,
+            headers: Default::default(),
+            body_json: None,
+            trace_id: None,
+            deadline_ms: None,
+            parts: None,
+        };
+
+        let js = JsExecDefault::create();
+
+        js.exec(setup, req).await.unwrap();
+
+        let got = recv.recv().await.unwrap();
+        match got.payload {
+            msg::MessagePayload::App { msg } => {
+                assert_eq!(b"direct", msg.as_ref());
+            }
+            _ => panic!("unexpected message type"),
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn js_exit_attempts_rejected() {
+        let rth = RuntimeHandle::default();
+
+        let setup = JsSetup {
+            runtime: rth.runtime(),
+            ctx: "test".into(),
+            env: Arc::new(serde_json::Value::Null),
+            modules: Default::default(),
+            entry: "".into(),
+            wasm: None,
+            code: "
+async function vm(req) {
+    if (req.type === 'fnReq') {
+        if (req.path === 'deno') {
+            Deno.exit(0);
+        }
+        if (req.path === 'process') {
+            process.exit(0);
+        }
+        return { type: 'fnResOk' };
+    }
+    throw new Error('unhandled');
+}
+"
+            .into(),
+            timeout: JsSetup::DEF_TIMEOUT,
+            heap_size: JsSetup::DEF_HEAP_SIZE,
+            max_code_bytes: JsSetup::DEF_MAX_CODE_BYTES,
+        };
+
+        let req = |path: &str| JsRequest::FnReq {
+            method: "GET".into(),
+            path: path.into(),
+            body: None,
+            headers: Default::default(),
+            body_json: None,
+            trace_id: None,
+            deadline_ms: None,
+            parts: None,
+        };
+
+        let js = JsExecDefault::create();
+
+        // exit attempts surface as js errors instead of killing the
+        // process
+        let err = js.exec(setup.clone(), req("deno")).await.unwrap_err();
+        assert!(
+            err.to_string().contains("Deno.exit is not allowed"),
+            "{err:?}",
+        );
+        let err = js.exec(setup.clone(), req("process")).await.unwrap_err();
+        assert!(
+            err.to_string().contains("process.exit is not allowed"),
+            "{err:?}",
+        );
+
+        // and the executor keeps serving afterward
+        js.exec(setup, req("ok")).await.unwrap();
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn js_exec_meter_records_fn_usage() {
+        let rth = RuntimeHandle::default();
+
+        // observe meter updates for this test's ctx only, so parallel
+        // tests cannot interfere
+        let metered = Arc::new(std::sync::atomic::AtomicU64::new(0));
+        let metered2 = metered.clone();
+        crate::meter::meter_register_hook(Arc::new(
+            move |ctx, meter, value| {
+                if &**ctx == "meter-test" && meter == "fn_mib_milli" {
+                    metered2.fetch_add(
+                        value as u64,
+                        std::sync::atomic::Ordering::SeqCst,
+                    );
+                }
+            },
+        ));
+
+        let setup = JsSetup {
+            runtime: rth.runtime(),
+            ctx: "meter-test".into(),
+            env: Arc::new(serde_json::Value::Null),
+            modules: Default::default(),
+            entry: "".into(),
+            wasm: None,
+            code: "
+async function vm(req) {
+    return { type: 'fnResOk' };
+}
+"
+            .into(),
+            timeout: JsSetup::DEF_TIMEOUT,
+            heap_size: JsSetup::DEF_HEAP_SIZE,
+            max_code_bytes: JsSetup::DEF_MAX_CODE_BYTES,
+        };
+
+        let req = JsRequest::FnReq {
+            method: "GET".into(),
+            path: "".into(),
+            body: None,
+            headers: Default::default(),
+            body_json: None,
+            trace_id: None,
+            deadline_ms: None,
+            parts: None,
+        };
+
+        let js = JsExecMeter::create(JsExecDefault::create());
+        js.exec(setup, req).await.unwrap();
+
+        // heap_size times the elapsed millis (floored at 100ms)
+        // guarantees a non-zero reading even for a trivial function
+        assert!(metered.load(std::sync::atomic::Ordering::SeqCst) > 0);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn js_error_includes_stack_frames() {
+        let rth = RuntimeHandle::default();
+
+        let setup = JsSetup {
+            runtime: rth.runtime(),
+            ctx: "test".into(),
+            env: Arc::new(serde_json::Value::Null),
+            modules: Default::default(),
+            entry: "".into(),
+            wasm: None,
+            code: "
+function inner() {
+    throw new Error('boom');
+}
+async function vm(req) {
+    inner();
+}
+"
+            .into(),
+            timeout: JsSetup::DEF_TIMEOUT,
+            heap_size: JsSetup::DEF_HEAP_SIZE,
+            max_code_bytes: JsSetup::DEF_MAX_CODE_BYTES,
+        };
+
+        let req = JsRequest::FnReq {
+            method: "GET".into(),
+            path: "".into(),
+            body: None,
+            headers: Default::default(),
+            body_json: None,
+            trace_id: None,
+            deadline_ms: None,
+            parts: None,
+        };
+
+        let js = JsExecDefault::create();
+
+        let err = js.exec(setup, req).await.unwrap_err();
+        let msg = err.to_string();
+        assert!(msg.contains("boom"), "{msg}");
+        // the throwing frame and the calling frame are both reported
+        // with their source locations
+        assert!(msg.contains("\n  at inner ("), "{msg}");
+        assert!(msg.contains("\n  at vm ("), "{msg}");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn js_secret_get() {
+        let _ = crate::secret::secret_global_set_master_key("test-master-key");
+
+        let rth = RuntimeHandle::default();
+        let obj = obj::obj_file::ObjFile::create(None).await.unwrap();
+        rth.set_obj(obj);
+
+        let ctx: Arc<str> = "bobbo".into();
+
+        crate::secret::put(
+            &rth.runtime().obj().unwrap(),
+            &ctx,
+            "apikey",
+            b"shhh",
+        )
+        .await
+        .unwrap();
+
+        let setup = JsSetup {
+            runtime: rth.runtime(),
+            ctx,
+            env: Arc::new(serde_json::Value::Null),
+            modules: Default::default(),
+            entry: "".into(),
+            wasm: None,
+            code: "
+async function vm(req) {
+    if (req.type === 'fnReq') {
+        const body = await VM.secretGet('apikey');
+        return { type: 'fnResOk', body };
+    }
+    throw new Error('unhandled');
+}
+"
+            .into(),
+            timeout: JsSetup::DEF_TIMEOUT,
+            heap_size: JsSetup::DEF_HEAP_SIZE,
+            max_code_bytes: JsSetup::DEF_MAX_CODE_BYTES,
+        };
+
+        let req = JsRequest::FnReq {
+            method: "GET".into(),
+            path: "".into(),
+            body: None,
+            headers: Default::default(),
+            body_json: None,
+            trace_id: None,
+            deadline_ms: None,
+            parts: None,
+        };
+
+        let js = JsExecDefault::create();
+
+        match js.exec(setup, req).await.unwrap() {
+            JsResponse::FnResOk { body, .. } => {
+                assert_eq!(b"shhh", body.as_ref());
+            }
+            oth => panic!("unexpected result: {oth:?}"),
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn js_body_json() {
+        let rth = RuntimeHandle::default();
+        let obj = obj::obj_file::ObjFile::create(None).await.unwrap();
+        rth.set_obj(obj);
+
+        let setup = JsSetup {
+            runtime: rth.runtime(),
+            ctx: "bobbo".into(),
+            env: Arc::new(serde_json::Value::Null),
+            modules: Default::default(),
+            entry: "".into(),
+            wasm: None,
+            code: "
+async function vm(req) {
+    if (req.type === 'fnReq') {
+        if (req.bodyJson) {
+            return { type: 'fnResOk', bodyJson: { echo: req.bodyJson } };
+        }
+        // non-json passthrough
+        return { type: 'fnResOk', body: req.body };
+    }
+    throw new Error('unhandled');
+}
+"
+            .into(),
+            timeout: JsSetup::DEF_TIMEOUT,
+            heap_size: JsSetup::DEF_HEAP_SIZE,
+            max_code_bytes: JsSetup::DEF_MAX_CODE_BYTES,
+        };
+
+        let js = JsExecDefault::create();
+
+        // json in, json out
+        let req = JsRequest::FnReq {
+            method: "PUT".into(),
+            path: "".into(),
+            body: Some(Bytes::from_static(b"{\"hello\":\"world\"}")),
+            headers: Default::default(),
+            body_json: Some(serde_json::json!({ "hello": "world" })),
+            trace_id: None,
+            deadline_ms: None,
+            parts: None,
+        };
+        match js.exec(setup.clone(), req).await.unwrap() {
+            JsResponse::FnResOk { body_json, .. } => {
+                assert_eq!(
+                    Some(serde_json::json!({
+                        "echo": { "hello": "world" },
+                    })),
+                    body_json,
+                );
+            }
+            oth => panic!("unexpected result: {oth:?}"),
+        }
+
+        // binary passthrough is untouched
+        let req = JsRequest::FnReq {
+            method: "PUT".into(),
+            path: "".into(),
+            body: Some(Bytes::from_static(b"\x00\x01\x02")),
+            headers: Default::default(),
+            body_json: None,
+            trace_id: None,
+            deadline_ms: None,
+            parts: None,
+        };
+        match js.exec(setup, req).await.unwrap() {
+            JsResponse::FnResOk {
+                body, body_json, ..
+            } => {
+                assert_eq!(b"\x00\x01\x02", body.as_ref());
+                assert_eq!(None, body_json);
+            }
+            oth => panic!("unexpected result: {oth:?}"),
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn js_vm_info() {
+        let setup = JsSetup {
+            runtime: RuntimeHandle::default().runtime(),
+            ctx: "info-test".into(),
+            env: Arc::new(serde_json::Value::Null),
+            modules: Default::default(),
+            entry: "".into(),
+            wasm: None,
+            code: "
+async function vm(req) {
+    if (req.type === 'fnReq') {
+        return { type: 'fnResOk', bodyJson: VM.info() };
+    }
+    throw new Error('unhandled');
+}
+"
+            .into(),
+            timeout: std::time::Duration::from_secs(7),
+            heap_size: JsSetup::DEF_HEAP_SIZE,
+            max_code_bytes: JsSetup::DEF_MAX_CODE_BYTES,
+        };
+
+        let req = JsRequest::FnReq {
+            method: "GET".into(),
+            path: "".into(),
+            body: None,
+            headers: Default::default(),
+            body_json: None,
+            trace_id: None,
+            deadline_ms: None,
+            parts: None,
+        };
+
+        let js = JsExecDefault::create();
+
+        match js.exec(setup, req).await.unwrap() {
+            JsResponse::FnResOk { body_json, .. } => {
+                let info = body_json.unwrap();
+                assert_eq!("info-test", info["ctx"]);
+                assert_eq!(7.0, info["timeoutSecs"]);
+                assert_eq!(
+                    JsSetup::DEF_HEAP_SIZE as u64,
+                    info["maxHeapBytes"].as_u64().unwrap(),
+                );
+                assert_eq!(env!("CARGO_PKG_VERSION"), info["serverVersion"]);
+                assert!(info["startedSecs"].as_f64().unwrap() > 0.0);
+                // no command sets a mode in the lib test process
+                assert_eq!("serve", info["mode"]);
+            }
+            oth => panic!("unexpected result: {oth:?}"),
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn js_webassembly_instantiate() {
+        let setup = JsSetup {
+            runtime: RuntimeHandle::default().runtime(),
+            ctx: "test".into(),
+            env: Arc::new(serde_json::Value::Null),
+            modules: Default::default(),
+            entry: "".into(),
+            wasm: None,
+            // (module (func (export "answer") (result i32) i32.const 42))
+            code: "
+const WASM = new Uint8Array([
+    0, 97, 115, 109, 1, 0, 0, 0,
+    1, 5, 1, 96, 0, 1, 127,
+    3, 2, 1, 0,
+    7, 10, 1, 6, 97, 110, 115, 119, 101, 114, 0, 0,
+    10, 6, 1, 4, 0, 65, 42, 11,
+]);
+
+async function vm(req) {
+    if (req.type === 'fnReq') {
+        if (!VM.wasmValidate(WASM)) {
+            throw new Error('wasm validation failed');
+        }
+        if (VM.wasmValidate(new Uint8Array([1, 2, 3]))) {
+            throw new Error('garbage validated');
+        }
+        const { instance } = await WebAssembly.instantiate(WASM);
+        return {
+            type: 'fnResOk',
+            bodyJson: { answer: instance.exports.answer() },
+        };
+    }
+    throw new Error('unhandled');
+}
+"
+            .into(),
+            timeout: JsSetup::DEF_TIMEOUT,
+            heap_size: JsSetup::DEF_HEAP_SIZE,
+            max_code_bytes: JsSetup::DEF_MAX_CODE_BYTES,
+        };
+
+        let req = JsRequest::FnReq {
+            method: "GET".into(),
+            path: "".into(),
+            body: None,
+            headers: Default::default(),
+            body_json: None,
+            trace_id: None,
+            deadline_ms: None,
+            parts: None,
+        };
+
+        let js = JsExecDefault::create();
+
+        match js.exec(setup, req).await.unwrap() {
+            JsResponse::FnResOk { body_json, .. } => {
+                assert_eq!(
+                    Some(serde_json::json!({ "answer": 42 })),
+                    body_json,
+                );
+            }
+            oth => panic!("unexpected result: {oth:?}"),
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn js_simple() {
+        let rth = RuntimeHandle::default();
+        let obj = obj::obj_file::ObjFile::create(None).await.unwrap();
+        rth.set_obj(obj);
+
+        let setup = JsSetup {
+            runtime: rth.runtime(),
+            ctx: "bobbo".into(),
+            env: Arc::new(serde_json::Value::Null),
+            modules: Default::default(),
+            entry: "".into(),
+            wasm: None,
+            code: "
+async function vm(req) {
+    if (req.type === 'objCheckReq') {
+        return { type: 'objCheckResOk' };
+    } else if (req.type === 'fnReq') {
+        const b = (new TextEncoder()).encode('hello');
+        console.log('encode', b, b instanceof Uint8Array);
+        const s = (new TextDecoder()).decode(b);
+        console.log('decode', s);
+
+        const { meta } = await VM.objPut({
+            meta: 'c/A/test',
+            data: new TextEncoder().encode('hello'),
+        });
+        console.log(`put returned meta: ${meta}`);
+
+        const { data } = await VM.objGet({ meta });
+        const res = new TextDecoder().decode(data);
+        console.log(`fetched: ${res}`);
+
+        const { metaList } = await VM.objList({
             appPathPrefix: 't',
             createdGt: 0.0,
             limit: 42,
@@ -1164,6 +2949,7 @@ async function vm(req) {
             .into(),
             timeout: JsSetup::DEF_TIMEOUT,
             heap_size: JsSetup::DEF_HEAP_SIZE,
+            max_code_bytes: JsSetup::DEF_MAX_CODE_BYTES,
         };
 
         let req = JsRequest::FnReq {
@@ -1171,6 +2957,10 @@ async function vm(req) {
             path: "foo/bar".into(),
             body: None,
             headers: Default::default(),
+            body_json: None,
+            trace_id: None,
+            deadline_ms: None,
+            parts: None,
         };
 
         let js = JsExecDefault::create();
@@ -1192,4 +2982,111 @@ async function vm(req) {
             println!("GOT: {meta:?}");
         }
     }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn js_es_modules() {
+        let rth = RuntimeHandle::default();
+
+        let mut modules: std::collections::BTreeMap<Arc<str>, Arc<str>> =
+            Default::default();
+        modules.insert(
+            "main.js".into(),
+            "
+import { greet } from './util.js';
+
+export async function vm(req) {
+    const body = new TextEncoder().encode(greet());
+    return { type: 'fnResOk', body };
+}
+"
+            .into(),
+        );
+        modules.insert(
+            "util.js".into(),
+            "export function greet() { return 'hello from module'; }".into(),
+        );
+
+        let setup = JsSetup {
+            runtime: rth.runtime(),
+            ctx: "test".into(),
+            env: Arc::new(serde_json::Value::Null),
+            modules: Arc::new(modules),
+            entry: "main.js".into(),
+            wasm: None,
+            code: "".into(),
+            timeout: JsSetup::DEF_TIMEOUT,
+            heap_size: JsSetup::DEF_HEAP_SIZE,
+            max_code_bytes: JsSetup::DEF_MAX_CODE_BYTES,
+        };
+
+        let req = JsRequest::FnReq {
+            method: "GET".into(),
+            path: "".into(),
+            body: None,
+            headers: Default::default(),
+            body_json: None,
+            trace_id: None,
+            deadline_ms: None,
+            parts: None,
+        };
+
+        let js = JsExecDefault::create();
+
+        match js.exec(setup, req).await.unwrap() {
+            JsResponse::FnResOk { body, .. } => {
+                assert_eq!(b"hello from module", body.as_ref());
+            }
+            oth => panic!("unexpected result: {oth:?}"),
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn js_es_modules_missing_import() {
+        let rth = RuntimeHandle::default();
+
+        let mut modules: std::collections::BTreeMap<Arc<str>, Arc<str>> =
+            Default::default();
+        modules.insert(
+            "main.js".into(),
+            "
+import { nope } from './nope.js';
+
+export async function vm(req) {
+    return { type: 'fnResOk' };
+}
+"
+            .into(),
+        );
+
+        let setup = JsSetup {
+            runtime: rth.runtime(),
+            ctx: "test".into(),
+            env: Arc::new(serde_json::Value::Null),
+            modules: Arc::new(modules),
+            entry: "main.js".into(),
+            wasm: None,
+            code: "".into(),
+            timeout: JsSetup::DEF_TIMEOUT,
+            heap_size: JsSetup::DEF_HEAP_SIZE,
+            max_code_bytes: JsSetup::DEF_MAX_CODE_BYTES,
+        };
+
+        let req = JsRequest::FnReq {
+            method: "GET".into(),
+            path: "".into(),
+            body: None,
+            headers: Default::default(),
+            body_json: None,
+            trace_id: None,
+            deadline_ms: None,
+            parts: None,
+        };
+
+        let js = JsExecDefault::create();
+
+        // the unresolvable import must surface as an error at module
+        // load, before any request is processed
+        let err = js.exec(setup, req).await.unwrap_err();
+        println!("got expected error: {err:?}");
+    }
 }
@@ -17,6 +17,41 @@ globalThis.TextDecoder = class TextDecoder {
   }
 };
 
+// user code only ever crosses the op boundary as json, so a json
+// round-trip is a faithful clone for everything we support
+if (!globalThis.structuredClone) {
+  globalThis.structuredClone = (v) => {
+    if (v === undefined) {
+      return undefined;
+    }
+    return JSON.parse(JSON.stringify(v));
+  };
+}
+
+// fail soft on inadvertent Deno api calls from vendored code
+if (!globalThis.Deno) {
+  globalThis.Deno = {};
+}
+if (!globalThis.Deno.env) {
+  globalThis.Deno.env = {
+    get() {
+      return null;
+    }
+  };
+}
+
+// but hard-fail on exit attempts: user code must never be able to
+// take the whole server process down
+globalThis.Deno.exit = () => {
+  throw new Error("Deno.exit is not allowed");
+};
+if (!globalThis.process) {
+  globalThis.process = {};
+}
+globalThis.process.exit = () => {
+  throw new Error("process.exit is not allowed");
+};
+
 function frz(obj) {
   Object.freeze(obj);
 
@@ -36,6 +71,7 @@ function getCache() {
     cache = frz({
       ctx: vm.op_get_ctx(),
       env: vm.op_get_env(),
+      info: vm.op_vm_info(),
     });
   }
   return cache;
@@ -44,11 +80,24 @@ function getCache() {
 globalThis.VM = {
   ctx: () => { return getCache().ctx; },
   env: () => { return getCache().env; },
+  info: () => { return getCache().info; },
+  traceId: vm.op_trace_id,
+  deriveKey: vm.op_derive_key,
+  hashSha512: vm.op_hash_sha512,
+  wasmValidate: vm.op_wasm_validate,
+  escapePath: vm.op_escape_path,
+  unescapePath: vm.op_unescape_path,
+  valuePointerGet: vm.op_value_pointer_get,
+  signVerify: vm.op_sign_verify,
+  randomUuid: vm.op_random_uuid,
   msgNew: vm.op_msg_new,
   msgList: vm.op_msg_list,
   msgSend: vm.op_msg_send,
+  msgBroadcast: vm.op_msg_broadcast,
+  secretGet: vm.op_secret_get,
   objPut: vm.op_obj_put,
   objGet: vm.op_obj_get,
   objRm: vm.op_obj_rm,
-  objList: vm.op_obj_list
+  objList: vm.op_obj_list,
+  objQuery: vm.op_obj_query
 };
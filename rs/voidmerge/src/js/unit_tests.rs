@@ -1,6 +1,10 @@
 use super::*;
 
 async fn exec(test_code: &str) {
+    exec_with_body(test_code, None).await;
+}
+
+async fn exec_with_body(test_code: &str, body: Option<Bytes>) -> JsResponse {
     let rth = RuntimeHandle::default();
     let obj = obj::obj_file::ObjFile::create(None).await.unwrap();
     rth.set_obj(obj);
@@ -9,9 +13,16 @@ async fn exec(test_code: &str) {
         runtime: rth.runtime(),
         ctx: "test".into(),
         env: Arc::new(serde_json::Value::Null),
+        modules: Default::default(),
+        entry: "".into(),
+        wasm: None,
         code: format!(
             r#"async function vm(req) {{
-                const res = await test();
+                const res = await test(req);
+
+                if (res && res.type === 'fnResOk') {{
+                    return res;
+                }}
 
                 if (res !== "TestPass") {{
                     throw new Error("Test Did Not Complete");
@@ -20,7 +31,7 @@ async fn exec(test_code: &str) {
                 return {{ type: 'fnResOk' }};
             }}
 
-            async function test() {{
+            async function test(req) {{
 
                 {test_code}
 
@@ -30,23 +41,30 @@ async fn exec(test_code: &str) {
         .into(),
         timeout: JsSetup::DEF_TIMEOUT,
         heap_size: JsSetup::DEF_HEAP_SIZE,
+        max_code_bytes: JsSetup::DEF_MAX_CODE_BYTES,
     };
 
     let req = JsRequest::FnReq {
         method: "GET".into(),
         path: "".into(),
-        body: None,
+        body,
         headers: Default::default(),
+        body_json: None,
+        trace_id: None,
+        deadline_ms: None,
+        parts: None,
     };
 
     let js = JsExecDefault::create();
 
     let res = js.exec(setup, req).await.unwrap();
 
-    match res {
+    match &res {
         crate::js::JsResponse::FnResOk { .. } => (),
         _ => panic!("invalid response: {:?}", res),
     }
+
+    res
 }
 
 #[tokio::test(flavor = "multi_thread")]
@@ -79,6 +97,11 @@ async fn js_unit_test_url() {
     exec(include_str!("unit_tests/url.js")).await;
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn js_unit_test_globals() {
+    exec(include_str!("unit_tests/globals.js")).await;
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn js_unit_test_console() {
     exec(include_str!("unit_tests/console.js")).await;
@@ -88,3 +111,159 @@ async fn js_unit_test_console() {
 async fn js_unit_test_crypto() {
     exec(include_str!("unit_tests/crypto.js")).await;
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn js_unit_test_derive_key() {
+    exec(include_str!("unit_tests/derive_key.js")).await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn js_unit_test_vm_crypto() {
+    exec(include_str!("unit_tests/vm_crypto.js")).await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn js_unit_test_escape_path() {
+    exec(
+        r#"if (VM.escapePath('a/b') !== 'a~2Fb') {
+            throw new Error('expected a~2Fb');
+        }
+        if (VM.unescapePath('a~2Fb') !== 'a/b') {
+            throw new Error('expected a/b');
+        }
+        // '%' and already-escaped input stay reversible
+        for (const s of ['100%', '~2F', 'café@example.com']) {
+            if (VM.unescapePath(VM.escapePath(s)) !== s) {
+                throw new Error('round trip failed: ' + s);
+            }
+        }
+        // a truncated escape sequence throws
+        let threw = false;
+        try {
+            VM.unescapePath('~2');
+        } catch (_e) {
+            threw = true;
+        }
+        if (!threw) {
+            throw new Error('expected truncated escape to throw');
+        }"#,
+    )
+    .await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn js_unit_test_value_pointer() {
+    // msgpack-encoded nested structure built rust-side, resolved
+    // through json pointers in js
+    let body = Bytes::from_encode(&serde_json::json!({
+        "foo": [{ "bar": 42 }],
+        "a/b": "slash",
+        "m~n": "tilde",
+    }))
+    .unwrap();
+
+    exec_with_body(
+        r#"const body = new Uint8Array(req.body);
+        if (VM.valuePointerGet('/foo/0/bar', body) !== 42) {
+            throw new Error('expected 42');
+        }
+        // rfc 6901 escapes: ~1 is '/', ~0 is '~'
+        if (VM.valuePointerGet('/a~1b', body) !== 'slash') {
+            throw new Error('expected slash');
+        }
+        if (VM.valuePointerGet('/m~0n', body) !== 'tilde') {
+            throw new Error('expected tilde');
+        }
+        // a whole subtree resolves structurally
+        const sub = VM.valuePointerGet('/foo', body);
+        if (sub.length !== 1 || sub[0].bar !== 42) {
+            throw new Error('expected subtree');
+        }
+        // a pointer that does not resolve is null, not an error
+        if (VM.valuePointerGet('/nope', body) !== null) {
+            throw new Error('expected null');
+        }
+        // malformed msgpack throws (0xc1 is never valid)
+        let threw = false;
+        try {
+            VM.valuePointerGet('/foo', new Uint8Array([0xc1]));
+        } catch (_e) {
+            threw = true;
+        }
+        if (!threw) {
+            throw new Error('expected malformed msgpack to throw');
+        }"#,
+        Some(body),
+    )
+    .await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn js_unit_test_vm_crypto_ed25519() {
+    // fixture keypair generated rust-side; js receives
+    // pubkey(32) || sig(64) || msg through the request body
+    use aws_lc_rs::signature::KeyPair;
+    let key = aws_lc_rs::signature::Ed25519KeyPair::generate().unwrap();
+    let msg = b"hello world";
+    let sig = key.sign(msg);
+    let mut body = Vec::new();
+    body.extend_from_slice(key.public_key().as_ref());
+    body.extend_from_slice(sig.as_ref());
+    body.extend_from_slice(msg);
+
+    exec_with_body(
+        r#"const body = new Uint8Array(req.body);
+        const pubKey = body.slice(0, 32);
+        const sig = body.slice(32, 96);
+        const msg = body.slice(96);
+        if (!VM.signVerify('ed25519', pubKey, sig, msg)) {
+            throw new Error('invalid ed25519 signature');
+        }
+        const tampered = new TextEncoder().encode('bad message');
+        if (VM.signVerify('ed25519', pubKey, sig, tampered)) {
+            throw new Error('unexpected valid ed25519 signature');
+        }
+        let threw = false;
+        try {
+            VM.signVerify('ed25519', pubKey.slice(0, 5), sig, msg);
+        } catch (_e) {
+            threw = true;
+        }
+        if (!threw) {
+            throw new Error('expected malformed ed25519 key to throw');
+        }"#,
+        Some(body.into()),
+    )
+    .await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn js_unit_test_binary_body_round_trip() {
+    // every byte value crosses the deno boundary and back unchanged:
+    // despite the json_args name, rustyscript serializes through
+    // serde_v8, which moves Bytes as a real Uint8Array rather than
+    // base64 or a per-element number array
+    let body: Bytes =
+        (0..=255u8).cycle().take(64 * 1024).collect::<Vec<u8>>().into();
+
+    let res = exec_with_body(
+        r#"if (!(req.body instanceof Uint8Array)) {
+            throw new Error('expected req.body to be a Uint8Array');
+        }
+        const out = new Uint8Array(req.body.length);
+        for (let i = 0; i < req.body.length; ++i) {
+            out[i] = req.body[i] ^ 0xff;
+        }
+        return { type: 'fnResOk', body: out };"#,
+        Some(body.clone()),
+    )
+    .await;
+
+    match res {
+        crate::js::JsResponse::FnResOk { body: got, .. } => {
+            let expect: Bytes = body.iter().map(|b| b ^ 0xff).collect();
+            assert_eq!(expect, got);
+        }
+        _ => panic!("invalid response: {:?}", res),
+    }
+}
@@ -18,6 +18,25 @@ if (hash !== hashExpected) {
   throw new Error(`sha256 expected '${hashExpected}', got: '${hash}'`);
 }
 
+// sha512
+const hash512 = Array.from(new Uint8Array(await crypto.subtle.digest(
+  'SHA-512',
+  MSG,
+))).map(b => b.toString(16).padStart(2, '0')).join('');
+const hash512Expected = '309ecc489c12d6eb4cc40f50c902f2b4d0ed77ee511a7c7a9bcd3ca86d4cd86f989dd35bc5ff499670da34255b45b0cfd830e81f605dcf7dc5542e93ae9cd76f';
+if (hash512 !== hash512Expected) {
+  throw new Error(`sha512 expected '${hash512Expected}', got: '${hash512}'`);
+}
+
+// randomUUID
+const uuid = crypto.randomUUID();
+if (!/^[0-9a-f]{8}-[0-9a-f]{4}-4[0-9a-f]{3}-[89ab][0-9a-f]{3}-[0-9a-f]{12}$/.test(uuid)) {
+  throw new Error(`expected a v4 uuid, got: '${uuid}'`);
+}
+if (uuid === crypto.randomUUID()) {
+  throw new Error('expected distinct uuids');
+}
+
 // p256
 const p256keys = await crypto.subtle.generateKey(
   { name: 'ECDSA', namedCurve: 'P-256' },
@@ -0,0 +1,17 @@
+const secret = new TextEncoder().encode('root-seed');
+
+// deterministic: same secret + info yields the same key
+const a = VM.deriveKey(secret, new TextEncoder().encode('signing'));
+const b = VM.deriveKey(secret, new TextEncoder().encode('signing'));
+if (a.length !== 64) {
+  throw new Error(`expected 64 byte key, got: ${a.length}`);
+}
+if (a.toString() !== b.toString()) {
+  throw new Error('expected deterministic derived key');
+}
+
+// different info yields an independent key
+const c = VM.deriveKey(secret, new TextEncoder().encode('encryption'));
+if (a.toString() === c.toString()) {
+  throw new Error('expected different key for different info');
+}
@@ -0,0 +1,18 @@
+// structuredClone: the clone is deep, edits don't leak back
+const orig = { a: 1, nested: { list: [1, 2, 3] } };
+const clone = structuredClone(orig);
+clone.nested.list.push(4);
+if (orig.nested.list.length !== 3) {
+  throw new Error('structuredClone must deep copy');
+}
+if (JSON.stringify(clone.nested.list) !== '[1,2,3,4]') {
+  throw new Error(`unexpected clone: ${JSON.stringify(clone)}`);
+}
+if (structuredClone(undefined) !== undefined) {
+  throw new Error('structuredClone(undefined) must be undefined');
+}
+
+// Deno stub: env lookups return null instead of throwing
+if (Deno.env.get('HOME') !== null) {
+  throw new Error('expected Deno.env.get to return null');
+}
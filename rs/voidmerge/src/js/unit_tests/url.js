@@ -16,3 +16,16 @@ if (t1.toString() !== expected) {
 if (t2.toString() !== expected) {
   throw new Error(`URL t2 expected '${expected}', got: '${t2.toString()}'`);
 }
+
+// URLSearchParams
+const params = new URLSearchParams(t1.search);
+if (params.get("a") !== "1" || params.get("b") !== "2") {
+  throw new Error(`unexpected search params: '${params.toString()}'`);
+}
+if (params.get("missing") !== null) {
+  throw new Error("expected null for a missing search param");
+}
+params.append("c", "x y");
+if (params.toString() !== "a=1&b=2&c=x+y") {
+  throw new Error(`unexpected serialization: '${params.toString()}'`);
+}
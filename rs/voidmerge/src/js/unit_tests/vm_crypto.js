@@ -0,0 +1,63 @@
+const MSG = new TextEncoder().encode('hello world');
+const MSG2 = new TextEncoder().encode('bad message');
+
+// sha512
+const hash = Array.from(new Uint8Array(VM.hashSha512(
+  MSG,
+))).map(b => b.toString(16).padStart(2, '0')).join('');
+const hashExpected = '309ecc489c12d6eb4cc40f50c902f2b4d0ed77ee511a7c7a9bcd3ca86d4cd86f989dd35bc5ff499670da34255b45b0cfd830e81f605dcf7dc5542e93ae9cd76f';
+if (hash !== hashExpected) {
+  throw new Error(`sha512 expected '${hashExpected}', got: '${hash}'`);
+}
+
+// randomUuid
+const uuid = VM.randomUuid();
+if (!/^[0-9a-f]{8}-[0-9a-f]{4}-4[0-9a-f]{3}-[89ab][0-9a-f]{3}-[0-9a-f]{12}$/.test(uuid)) {
+  throw new Error(`expected a v4 uuid, got: '${uuid}'`);
+}
+if (uuid === VM.randomUuid()) {
+  throw new Error('expected distinct uuids');
+}
+
+// p256 keys generated and signed by crypto.subtle, verified by the op
+const p256keys = await crypto.subtle.generateKey(
+  { name: 'ECDSA', namedCurve: 'P-256' },
+  true,
+  ['sign', 'verify'],
+);
+const pubKey = new Uint8Array(
+  await crypto.subtle.exportKey('raw', p256keys.publicKey),
+);
+const sig = new Uint8Array(await crypto.subtle.sign(
+  { name: 'ECDSA', hash: { name: 'SHA-256' } },
+  p256keys.privateKey,
+  MSG,
+));
+if (!VM.signVerify('p256', pubKey, sig, MSG)) {
+  throw new Error('invalid p256 signature');
+}
+if (VM.signVerify('p256', pubKey, sig, MSG2)) {
+  throw new Error('unexpected valid p256 signature');
+}
+
+// malformed keys error instead of verifying
+let threw = false;
+try {
+  VM.signVerify('p256', new Uint8Array(5), sig, MSG);
+} catch (_e) {
+  threw = true;
+}
+if (!threw) {
+  throw new Error('expected malformed p256 key to throw');
+}
+
+// unknown algorithms error
+threw = false;
+try {
+  VM.signVerify('rot13', pubKey, sig, MSG);
+} catch (_e) {
+  threw = true;
+}
+if (!threw) {
+  throw new Error('expected unknown algorithm to throw');
+}
@@ -51,6 +51,48 @@ pub fn safe_now() -> f64 {
     now
 }
 
+/// Time source abstraction. Expiry and prune logic reads the clock
+/// through this trait so tests can drive TTL behavior with a manually
+/// advanced [TestClock] instead of real sleeps.
+pub trait VmClock: 'static + Send + Sync {
+    /// Current time as f64 seconds.
+    fn now(&self) -> f64;
+}
+
+/// Dyn [VmClock] type.
+pub type DynVmClock = Arc<dyn VmClock + 'static + Send + Sync>;
+
+/// The default [VmClock], delegating to [safe_now].
+pub struct SysClock;
+
+impl VmClock for SysClock {
+    fn now(&self) -> f64 {
+        safe_now()
+    }
+}
+
+/// A manually advanced [VmClock] for deterministic tests. The clock
+/// only moves when [TestClock::advance] is called.
+pub struct TestClock(std::sync::Mutex<f64>);
+
+impl TestClock {
+    /// Construct a new test clock starting at the current real time.
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self(std::sync::Mutex::new(safe_now())))
+    }
+
+    /// Advance the clock by `secs` seconds.
+    pub fn advance(&self, secs: f64) {
+        *self.0.lock().unwrap() += secs;
+    }
+}
+
+impl VmClock for TestClock {
+    fn now(&self) -> f64 {
+        *self.0.lock().unwrap()
+    }
+}
+
 /// Check for safe characters to be used in contexts / paths / etc.
 fn safe_str(s: &str) -> Result<()> {
     for b in s.as_bytes() {
@@ -76,6 +118,7 @@ struct RuntimeInner {
     pub obj: std::sync::OnceLock<obj::ObjWrap>,
     pub js: std::sync::OnceLock<js::DynJsExec>,
     pub msg: std::sync::OnceLock<msg::DynMsg>,
+    pub read_only: std::sync::atomic::AtomicBool,
 }
 
 /// A cloneable runtime instance that can be passed to modules.
@@ -132,6 +175,18 @@ impl Runtime {
             .ok_or_else(|| Error::other("closing"))?
             .clone())
     }
+
+    /// Check whether the runtime is in read-only mode. Mutating js
+    /// ops consult this so user code cannot write around a read-only
+    /// server.
+    pub fn read_only(&self) -> bool {
+        self.0
+            .upgrade()
+            .map(|inner| {
+                inner.read_only.load(std::sync::atomic::Ordering::Relaxed)
+            })
+            .unwrap_or(false)
+    }
 }
 
 /// VoidMerge [Runtime] manages module interdependencies.
@@ -168,6 +223,18 @@ impl RuntimeHandle {
     pub fn runtime(&self) -> Runtime {
         Runtime(Arc::downgrade(&self.0), self.1)
     }
+
+    /// Put the runtime into (or out of) read-only mode.
+    pub fn set_read_only(&self, read_only: bool) {
+        self.0
+            .read_only
+            .store(read_only, std::sync::atomic::Ordering::Relaxed);
+    }
+
+    /// Check whether the runtime is in read-only mode.
+    pub fn read_only(&self) -> bool {
+        self.0.read_only.load(std::sync::atomic::Ordering::Relaxed)
+    }
 }
 
 pub mod bytes_ext;
@@ -179,6 +246,9 @@ pub mod js;
 pub mod meter;
 pub mod msg;
 pub mod obj;
+pub mod secret;
 pub mod server;
+#[cfg(feature = "wasm")]
+pub mod wasm;
 
 use bytes_ext::BytesExt;
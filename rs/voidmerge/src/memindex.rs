@@ -2,34 +2,78 @@
 
 use crate::error::ErrorExt;
 use crate::obj::ObjMeta;
-use crate::safe_now;
+use crate::{DynVmClock, SysClock, VmClock};
 use crate::{Error, Result};
 use std::collections::{BTreeMap, HashMap, HashSet};
 use std::sync::Arc;
 
+/// Tiebreak key consulted by [MemIndex::put] when two entries for the
+/// same path carry created timestamps within the skew window: the key
+/// (typically a content hash), then the full meta string, decide the
+/// winner deterministically, so nodes with skewed clocks converge on
+/// the same survivor regardless of arrival order.
+pub trait IndexTiebreak {
+    /// The tiebreak key. Defaults to none.
+    fn tiebreak(&self) -> Option<&str> {
+        None
+    }
+}
+
+impl IndexTiebreak for () {}
+
 /// A memory-backed object index.
-pub struct MemIndex<Info: Clone> {
+pub struct MemIndex<Info: Clone + IndexTiebreak> {
+    clock: DynVmClock,
+    skew_secs: f64,
     map: OrderMap<(ObjMeta, Info)>,
     delete: Vec<(ObjMeta, Info)>,
 }
 
-impl<Info: Clone> Default for MemIndex<Info> {
+impl<Info: Clone + IndexTiebreak> Default for MemIndex<Info> {
     fn default() -> Self {
+        Self::with_clock(Arc::new(SysClock))
+    }
+}
+
+impl<Info: Clone + IndexTiebreak> MemIndex<Info> {
+    /// Default clock-skew window in seconds. Zero, so only entries
+    /// with exactly equal created timestamps tiebreak: a wider window
+    /// would let a deterministic loser reject a genuinely newer local
+    /// overwrite.
+    pub const DEF_SKEW_SECS: f64 = 0.0;
+
+    /// Construct an index reading expiry time from an injected clock.
+    pub fn with_clock(clock: DynVmClock) -> Self {
         Self {
+            clock,
+            skew_secs: Self::DEF_SKEW_SECS,
             map: Default::default(),
             delete: Default::default(),
         }
     }
-}
 
-impl<Info: Clone> MemIndex<Info> {
+    /// Set the clock-skew window: entries whose created timestamps
+    /// differ by at most this many seconds are treated as ties and
+    /// resolved through [IndexTiebreak] instead of newest-wins, which
+    /// keeps syncing nodes with skewed clocks convergent.
+    pub fn set_skew_secs(&mut self, skew_secs: f64) {
+        self.skew_secs = skew_secs;
+    }
+
     /// Get metrics.
     pub fn meter(&self) -> HashMap<Arc<str>, u64> {
+        let now = self.clock.now();
         let mut map: HashMap<Arc<str>, u64> = Default::default();
         for (meta, _info) in self.map.iter(f64::MIN, f64::MAX) {
             if meta.sys_prefix() != ObjMeta::SYS_CTX {
                 continue;
             }
+            // expired items that have not yet been pruned must not
+            // count toward storage quota
+            let x = meta.expires_secs();
+            if x != 0.0 && x <= now {
+                continue;
+            }
             *map.entry(meta.ctx().into()).or_default() += meta.byte_length();
         }
         map
@@ -43,7 +87,7 @@ impl<Info: Clone> MemIndex<Info> {
 
     /// Prune expired items.
     pub fn prune(&mut self) {
-        let now = safe_now();
+        let now = self.clock.now();
         self.map.retain(|_, (meta, info)| {
             let x = meta.expires_secs();
             if x == 0.0 || x > now {
@@ -75,33 +119,72 @@ impl<Info: Clone> MemIndex<Info> {
     pub fn list(
         &self,
         prefix: Arc<str>,
-        mut created_gt: f64,
+        created_gt: f64,
         limit: u32,
     ) -> Vec<Arc<str>> {
+        self.range(prefix, created_gt, f64::MAX, false, limit).0
+    }
+
+    /// List items created within `(created_gt, created_lt)` in
+    /// creation order, or newest first when `descending`, returning at
+    /// most `limit` paths plus the watermark to pass as `created_gt`
+    /// (`created_lt` when descending) on the next call. The watermark
+    /// is `None` when the range was exhausted.
+    pub fn range(
+        &self,
+        prefix: Arc<str>,
+        mut created_gt: f64,
+        mut created_lt: f64,
+        descending: bool,
+        limit: u32,
+    ) -> (Vec<Arc<str>>, Option<f64>) {
         if !created_gt.is_finite() {
             created_gt = f64::MIN;
         }
+        if !created_lt.is_finite() {
+            created_lt = f64::MAX;
+        }
         let mut out = Vec::new();
+        if descending {
+            let mut last_created_secs = f64::MAX;
+            for (meta, _info) in self.map.iter_rev(created_gt, created_lt) {
+                let created_secs = meta.created_secs();
+                if out.len() >= limit as usize
+                    && created_secs < last_created_secs
+                {
+                    // same edge case as below, mirrored: overflow the
+                    // limit to include all items tied on the boundary
+                    // timestamp, so resuming at the watermark cannot
+                    // miss any of them
+                    return (out, Some(last_created_secs));
+                }
+                last_created_secs = created_secs;
+                if created_secs < created_lt && meta.0.starts_with(&*prefix) {
+                    out.push(meta.0.clone());
+                }
+            }
+            return (out, None);
+        }
         let mut last_created_secs = 0.0;
-        for (meta, _info) in self.map.iter(created_gt, f64::MAX) {
+        for (meta, _info) in self.map.iter(created_gt, created_lt) {
             let created_secs = meta.created_secs();
             if out.len() >= limit as usize && created_secs > last_created_secs {
                 // in edge case of exactly matching created_secs, we may return
                 // more than the limit, but if we don't do this, the continue
                 // token will cause them to miss some items
-                return out;
+                return (out, Some(last_created_secs));
             }
             last_created_secs = created_secs;
             if created_secs > created_gt && meta.0.starts_with(&*prefix) {
                 out.push(meta.0.clone());
             }
         }
-        out
+        (out, None)
     }
 
     /// Put an item into the index.
     pub fn put(&mut self, meta: ObjMeta, info: Info) {
-        let now = safe_now();
+        let now = self.clock.now();
         let mx = meta.expires_secs();
         if mx > 0.0 && mx < now {
             self.delete.push((meta, info));
@@ -109,6 +192,8 @@ impl<Info: Clone> MemIndex<Info> {
         }
         let pfx = Pfx::new(&meta);
         let created_secs = meta.created_secs();
+        let new_path = meta.0.clone();
+        let new_tiebreak = info.tiebreak().map(str::to_string);
         if let Some((orig_meta, orig_info)) =
             self.map.insert(created_secs, pfx, (meta, info))
         {
@@ -118,7 +203,20 @@ impl<Info: Clone> MemIndex<Info> {
                 return;
             }
             let orig_created_secs = orig_meta.created_secs();
-            if orig_created_secs >= created_secs {
+            let keep_orig = if (orig_created_secs - created_secs).abs()
+                <= self.skew_secs
+            {
+                // near-identical timestamps from skewed node clocks:
+                // newest-wins would leave the survivor depending on
+                // arrival order, so tiebreak deterministically on the
+                // content hash, then the full meta string
+                let orig_tiebreak = orig_info.tiebreak().unwrap_or("");
+                (orig_tiebreak, &*orig_meta.0)
+                    >= (new_tiebreak.as_deref().unwrap_or(""), &*new_path)
+            } else {
+                orig_created_secs >= created_secs
+            };
+            if keep_orig {
                 // woops, put it back
                 if let Some((meta, info)) = self.map.insert(
                     orig_created_secs,
@@ -251,4 +349,171 @@ impl<T> OrderMap<T> {
             set.iter().filter_map(|pfx| self.map.get(pfx).map(|v| &v.1))
         })
     }
+
+    pub fn iter_rev(
+        &self,
+        mut start: f64,
+        mut end: f64,
+    ) -> impl Iterator<Item = &T> {
+        if !start.is_finite() {
+            start = f64::MIN;
+        }
+        if !end.is_finite() {
+            end = f64::MAX;
+        }
+        let start = Order(start);
+        let mut end = Order(end);
+        if end < start {
+            end = start;
+        }
+        self.order.range(start..end).rev().flat_map(|(_, set)| {
+            set.iter().filter_map(|pfx| self.map.get(pfx).map(|v| &v.1))
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn meter_skips_expired_items() {
+        let clock = crate::TestClock::new();
+        let mut index: MemIndex<()> = MemIndex::with_clock(clock.clone());
+
+        let now = clock.now();
+
+        // one item with no expiry, one with a short ttl
+        index.put(ObjMeta(format!("c/AAAA/keep/{now}/0/5").into()), ());
+        index.put(
+            ObjMeta(format!("c/AAAA/ttl/{now}/{}/7", now + 5.0).into()),
+            (),
+        );
+
+        let meter = index.meter();
+        assert_eq!(12, *meter.get("AAAA").unwrap());
+
+        clock.advance(10.0);
+
+        // expired but not yet pruned: excluded from the meter
+        let meter = index.meter();
+        assert_eq!(5, *meter.get("AAAA").unwrap());
+
+        // pruning at the advanced clock deletes the expired item
+        index.prune();
+        assert_eq!(1, index.get_delete().len());
+        let meter = index.meter();
+        assert_eq!(5, *meter.get("AAAA").unwrap());
+    }
+
+    #[test]
+    fn put_ties_converge_regardless_of_order() {
+        // Info stands in for a content hash
+        #[derive(Clone, Debug, PartialEq)]
+        struct H(&'static str);
+
+        impl IndexTiebreak for H {
+            fn tiebreak(&self) -> Option<&str> {
+                Some(self.0)
+            }
+        }
+
+        // exactly equal created timestamps: the survivor is decided
+        // by the tiebreak key, not arrival order
+        let meta = ObjMeta("c/AAAA/x/5/0/5".into());
+        let mut one: MemIndex<H> = Default::default();
+        one.put(meta.clone(), H("aaa"));
+        one.put(meta.clone(), H("bbb"));
+        let mut two: MemIndex<H> = Default::default();
+        two.put(meta.clone(), H("bbb"));
+        two.put(meta.clone(), H("aaa"));
+        assert_eq!(H("bbb"), one.get(meta.clone()).unwrap().1);
+        assert_eq!(H("bbb"), two.get(meta.clone()).unwrap().1);
+
+        // near-identical timestamps within a configured skew window
+        // also converge, even when the hash ordering contradicts the
+        // timestamp ordering
+        let early = ObjMeta("c/AAAA/y/5/0/5".into());
+        let late = ObjMeta("c/AAAA/y/6/0/5".into());
+        let mut one: MemIndex<H> = Default::default();
+        one.set_skew_secs(2.0);
+        one.put(early.clone(), H("zzz"));
+        one.put(late.clone(), H("aaa"));
+        let mut two: MemIndex<H> = Default::default();
+        two.set_skew_secs(2.0);
+        two.put(late.clone(), H("aaa"));
+        two.put(early.clone(), H("zzz"));
+        assert_eq!(H("zzz"), one.get(early.clone()).unwrap().1);
+        assert_eq!(H("zzz"), two.get(early.clone()).unwrap().1);
+
+        // outside the window the newer entry still wins as before
+        let mut index: MemIndex<H> = Default::default();
+        index.put(early.clone(), H("zzz"));
+        index.put(late.clone(), H("aaa"));
+        assert_eq!(H("aaa"), index.get(late).unwrap().1);
+    }
+
+    #[test]
+    fn range_pages_across_exact_timestamp_boundary() {
+        let mut index: MemIndex<()> = Default::default();
+
+        // three items sharing one created_secs, two a second later
+        for name in ["a", "b", "c"] {
+            index.put(ObjMeta(format!("c/AAAA/{name}/5/0/1").into()), ());
+        }
+        for name in ["d", "e"] {
+            index.put(ObjMeta(format!("c/AAAA/{name}/6/0/1").into()), ());
+        }
+
+        // the first page overflows the limit to include all items
+        // tied on the boundary timestamp
+        let (page, watermark) =
+            index.range("c/AAAA/".into(), 0.0, f64::MAX, false, 2);
+        assert_eq!(3, page.len());
+        assert_eq!(Some(5.0), watermark);
+
+        // resuming at the watermark returns the rest exactly once
+        let (page, watermark) = index.range(
+            "c/AAAA/".into(),
+            watermark.unwrap(),
+            f64::MAX,
+            false,
+            2,
+        );
+        assert_eq!(2, page.len());
+        assert_eq!(None, watermark);
+    }
+
+    #[test]
+    fn range_pages_descending_across_tie() {
+        let mut index: MemIndex<()> = Default::default();
+
+        // three items sharing one created_secs, two a second later
+        for name in ["a", "b", "c"] {
+            index.put(ObjMeta(format!("c/AAAA/{name}/5/0/1").into()), ());
+        }
+        for name in ["d", "e"] {
+            index.put(ObjMeta(format!("c/AAAA/{name}/6/0/1").into()), ());
+        }
+
+        // newest first: the 6.0 items fill the first page
+        let (page, watermark) =
+            index.range("c/AAAA/".into(), f64::MIN, f64::MAX, true, 2);
+        assert_eq!(2, page.len());
+        assert!(page.iter().all(|p| p.contains("/6/")));
+        assert_eq!(Some(6.0), watermark);
+
+        // resuming at the watermark overflows the limit to include
+        // all items tied on the boundary timestamp
+        let (page, watermark) = index.range(
+            "c/AAAA/".into(),
+            f64::MIN,
+            watermark.unwrap(),
+            true,
+            2,
+        );
+        assert_eq!(3, page.len());
+        assert!(page.iter().all(|p| p.contains("/5/")));
+        assert_eq!(None, watermark);
+    }
 }
@@ -111,6 +111,8 @@ fn sys() -> &'static Mutex<Sys> {
 struct OtelMeters {
     egress_byte: opentelemetry::metrics::Counter<f64>,
     fn_mib_milli: opentelemetry::metrics::Counter<f64>,
+    fn_cancelled: opentelemetry::metrics::Counter<f64>,
+    fn_rejected: opentelemetry::metrics::Counter<f64>,
     obj_store_byte_min: opentelemetry::metrics::Counter<f64>,
 
     _mem_avail_byte: opentelemetry::metrics::ObservableGauge<u64>,
@@ -139,6 +141,18 @@ impl Default for OtelMeters {
             .with_description("Function call memory * duration")
             .build();
 
+        let fn_cancelled = meter
+            .f64_counter("vm.fn.cancelled")
+            .with_unit("count")
+            .with_description("Function calls cancelled by the client")
+            .build();
+
+        let fn_rejected = meter
+            .f64_counter("vm.fn.rejected")
+            .with_unit("count")
+            .with_description("Function calls rejected by backpressure")
+            .build();
+
         let obj_store_byte_min = meter
             .f64_counter("vm.obj.storage")
             .with_unit("byte-min")
@@ -202,6 +216,8 @@ impl Default for OtelMeters {
         Self {
             egress_byte,
             fn_mib_milli,
+            fn_cancelled,
+            fn_rejected,
             obj_store_byte_min,
             _mem_avail_byte,
             _mem_used_byte,
@@ -223,6 +239,8 @@ fn otel() -> &'static OtelMeters {
 struct Agg {
     egress_byte: u128,
     fn_mib_milli: u128,
+    fn_cancelled: u128,
+    fn_rejected: u128,
     obj_store_byte_min: u128,
 }
 
@@ -287,6 +305,27 @@ pub fn meter_fn_mib_milli(ctx: &Arc<str>, fn_mib_milli: u128) {
     hook_trigger(ctx, "fn_mib_milli", fn_mib_milli);
 }
 
+/// Increment the cancelled fn call count for a context.
+pub fn meter_fn_cancelled(ctx: &Arc<str>) {
+    otel().fn_cancelled.add(
+        1.0,
+        &[opentelemetry::KeyValue::new("ctx", ctx.to_string())],
+    );
+    meter_ctx!(ctx).fn_cancelled += 1;
+    hook_trigger(ctx, "fn_cancelled", 1);
+}
+
+/// Increment the rejected fn call count for a context. A call is
+/// rejected when the js exec queue is full or the acquire wait expires.
+pub fn meter_fn_rejected(ctx: &Arc<str>) {
+    otel().fn_rejected.add(
+        1.0,
+        &[opentelemetry::KeyValue::new("ctx", ctx.to_string())],
+    );
+    meter_ctx!(ctx).fn_rejected += 1;
+    hook_trigger(ctx, "fn_rejected", 1);
+}
+
 /// Set the current storage size for a context.
 pub fn meter_obj_store_byte_min(ctx: &Arc<str>, obj_store_byte_min: u128) {
     otel().obj_store_byte_min.add(
@@ -309,6 +348,8 @@ async fn init_meter_task() {
                 %ctx,
                 egress_byte = meter.egress_byte as f64,
                 fn_mib_milli = meter.fn_mib_milli as f64,
+                fn_cancelled = meter.fn_cancelled as f64,
+                fn_rejected = meter.fn_rejected as f64,
                 obj_store_byte_min = meter.obj_store_byte_min as f64,
             );
         }
@@ -1,17 +1,42 @@
 //! Message channels.
 
 use crate::*;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::sync::{Arc, Mutex, Weak};
 
+fn gap_none(gap: &u64) -> bool {
+    *gap == 0
+}
+
 /// An individual message.
-#[derive(serde::Serialize, serde::Deserialize)]
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Message {
+    /// Per-channel sequence number, starting at 1 for the first message
+    /// sent over a channel. Receivers can detect missed messages by
+    /// watching for gaps in this sequence.
+    pub seq: u64,
+
+    /// Count of queued messages dropped from the channel immediately
+    /// before this one. Only ever non-zero on channels configured to
+    /// drop their oldest queued message on overflow instead of
+    /// closing; the dropped messages also leave a hole in `seq`.
+    #[serde(default, skip_serializing_if = "gap_none")]
+    pub gap: u64,
+
+    /// The message payload.
+    #[serde(flatten)]
+    pub payload: MessagePayload,
+}
+
+/// An individual message payload.
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
 #[serde(
     tag = "type",
     rename_all = "camelCase",
     rename_all_fields = "camelCase"
 )]
-pub enum Message {
+pub enum MessagePayload {
     /// A message from an application.
     App {
         /// The message payload.
@@ -51,12 +76,21 @@ pub trait Msg: 'static + Send + Sync {
     /// List the active message channels within a context.
     fn list(&self, ctx: Arc<str>) -> BoxFut<'_, Result<Vec<Arc<str>>>>;
 
-    /// Send a message over the channel.
+    /// Send a message over the channel. The channel assigns the
+    /// sequence number.
     fn send(
         &self,
         ctx: Arc<str>,
         msg_id: Arc<str>,
-        msg: Message,
+        msg: MessagePayload,
+    ) -> BoxFut<'_, Result<()>>;
+
+    /// Broadcast a message to every active channel within a context.
+    /// Each channel assigns its own sequence number.
+    fn broadcast(
+        &self,
+        ctx: Arc<str>,
+        msg: MessagePayload,
     ) -> BoxFut<'_, Result<()>>;
 }
 
@@ -76,8 +110,33 @@ impl Drop for MsgMem {
 }
 
 impl MsgMem {
-    /// Construct a new memory-backed message channel.
+    /// Default per-channel queue capacity.
+    pub const DEF_CAPACITY: usize = 32;
+
+    /// Construct a new memory-backed message channel. Channels buffer
+    /// up to [MsgMem::DEF_CAPACITY] messages and close on overflow.
     pub fn create() -> DynMsg {
+        Self::create_with(Self::DEF_CAPACITY, false)
+    }
+
+    /// Construct a new memory-backed message channel with an explicit
+    /// per-channel queue capacity. With `drop_oldest` set, a full
+    /// channel drops its oldest queued message on overflow and
+    /// records the loss in [Message::gap], instead of closing; a
+    /// burst past a slow consumer then loses messages rather than the
+    /// whole subscription.
+    pub fn create_with(capacity: usize, drop_oldest: bool) -> DynMsg {
+        Self::create_with_clock(capacity, drop_oldest, Arc::new(SysClock))
+    }
+
+    /// Construct a new memory-backed message channel reading time
+    /// from an injected clock, so tests can drive the unclaimed
+    /// channel prune with a [TestClock] instead of real sleeps.
+    pub fn create_with_clock(
+        capacity: usize,
+        drop_oldest: bool,
+        clock: DynVmClock,
+    ) -> DynMsg {
         let out = Arc::new_cyclic(|this: &Weak<MsgMem>| {
             let this = this.clone();
             let task = tokio::task::spawn(async move {
@@ -94,7 +153,7 @@ impl MsgMem {
             })
             .abort_handle();
             Self {
-                map: ChanMap::new(),
+                map: ChanMap::new(capacity, drop_oldest, clock),
                 task,
             }
         });
@@ -124,12 +183,18 @@ impl Msg for MsgMem {
         &self,
         ctx: Arc<str>,
         msg_id: Arc<str>,
-        msg: Message,
+        msg: MessagePayload,
     ) -> BoxFut<'_, Result<()>> {
         Box::pin(async move {
             let s = self.map.lock().unwrap().msg_send(&ctx, &msg_id);
-            if let Some(s) = s {
-                if s.try_send(msg).is_err() {
+            if let Some((seq, s)) = s {
+                let msg = Message {
+                    seq: seq.fetch_add(1, std::sync::atomic::Ordering::Relaxed)
+                        + 1,
+                    gap: 0,
+                    payload: msg,
+                };
+                if !s.push(msg) {
                     let _drop = self.map.lock().unwrap().remove(&ctx, &msg_id);
                     // _drop released here, after the lock guard is dropped.
                     Err(Error::other("msg channel closed"))
@@ -141,38 +206,146 @@ impl Msg for MsgMem {
             }
         })
     }
+
+    fn broadcast(
+        &self,
+        ctx: Arc<str>,
+        msg: MessagePayload,
+    ) -> BoxFut<'_, Result<()>> {
+        Box::pin(async move {
+            let list = self.map.lock().unwrap().msg_send_list(&ctx);
+            for (msg_id, seq, s) in list {
+                let msg = Message {
+                    seq: seq.fetch_add(1, std::sync::atomic::Ordering::Relaxed)
+                        + 1,
+                    gap: 0,
+                    payload: msg.clone(),
+                };
+                if !s.push(msg) {
+                    let _drop = self.map.lock().unwrap().remove(&ctx, &msg_id);
+                    // _drop released here, after the lock guard is dropped.
+                }
+            }
+            Ok(())
+        })
+    }
+}
+
+struct ChanBuf {
+    buf: VecDeque<Message>,
+    gap: u64,
+    closed: bool,
+    capacity: usize,
+    drop_oldest: bool,
+}
+
+/// Per-channel message queue. Unlike an mpsc channel, the sender side
+/// can see and shed the oldest queued message on overflow.
+#[derive(Clone)]
+struct ChanQueue(Arc<(Mutex<ChanBuf>, tokio::sync::Notify)>);
+
+impl ChanQueue {
+    fn new(capacity: usize, drop_oldest: bool) -> Self {
+        Self(Arc::new((
+            Mutex::new(ChanBuf {
+                buf: VecDeque::new(),
+                gap: 0,
+                closed: false,
+                capacity: std::cmp::max(1, capacity),
+                drop_oldest,
+            }),
+            tokio::sync::Notify::new(),
+        )))
+    }
+
+    /// Queue a message, returning false if the channel is closed or
+    /// full (and not configured to drop its oldest on overflow).
+    fn push(&self, msg: Message) -> bool {
+        {
+            let mut q = self.0.0.lock().unwrap();
+            if q.closed {
+                return false;
+            }
+            if q.buf.len() >= q.capacity {
+                if !q.drop_oldest {
+                    return false;
+                }
+                q.buf.pop_front();
+                q.gap += 1;
+            }
+            q.buf.push_back(msg);
+        }
+        self.0.1.notify_one();
+        true
+    }
+
+    /// Receive the next queued message, attributing any accumulated
+    /// drop count to it. None once the channel is closed and drained.
+    async fn recv(&self) -> Option<Message> {
+        loop {
+            {
+                let mut q = self.0.0.lock().unwrap();
+                if let Some(mut msg) = q.buf.pop_front() {
+                    msg.gap = std::mem::take(&mut q.gap);
+                    return Some(msg);
+                }
+                if q.closed {
+                    return None;
+                }
+            }
+            self.0.1.notified().await;
+        }
+    }
+
+    /// Close the channel, waking any pending receiver. Already queued
+    /// messages remain receivable.
+    fn close(&self) {
+        self.0.0.lock().unwrap().closed = true;
+        self.0.1.notify_waiters();
+    }
 }
 
 struct ChanItem {
-    pub ts: std::time::Instant,
-    pub send: tokio::sync::mpsc::Sender<Message>,
+    pub ts: f64,
+    pub seq: Arc<std::sync::atomic::AtomicU64>,
+    pub send: ChanQueue,
     pub recv: Option<DynMsgRecv>,
 }
 
 struct ChanMap {
     this: Weak<Mutex<Self>>,
     map: HashMap<Arc<str>, HashMap<Arc<str>, ChanItem>>,
+    capacity: usize,
+    drop_oldest: bool,
+    clock: DynVmClock,
 }
 
 impl ChanMap {
-    fn new() -> Arc<Mutex<Self>> {
+    fn new(
+        capacity: usize,
+        drop_oldest: bool,
+        clock: DynVmClock,
+    ) -> Arc<Mutex<Self>> {
         Arc::new_cyclic(|this| {
             Mutex::new(Self {
                 this: this.clone(),
                 map: HashMap::new(),
+                capacity,
+                drop_oldest,
+                clock,
             })
         })
     }
 
     fn prune(&mut self) -> Vec<DynMsgRecv> {
+        let now = self.clock.now();
         let mut to_drop = Vec::new();
         self.map.retain(|_, m| {
             m.retain(|_, i| {
-                if i.recv.is_none()
-                    || i.ts.elapsed() < std::time::Duration::from_secs(30)
-                {
+                if i.recv.is_none() || now - i.ts < 30.0 {
                     true
                 } else {
+                    i.send.close();
                     // Take recv out so it isn't dropped while the lock is held.
                     to_drop.extend(i.recv.take());
                     false
@@ -189,19 +362,20 @@ impl ChanMap {
         rand::rng().fill(&mut msg_id);
         use base64::prelude::*;
         let msg_id: Arc<str> = BASE64_URL_SAFE_NO_PAD.encode(msg_id).into();
-        let (s, r) = tokio::sync::mpsc::channel(32);
+        let queue = ChanQueue::new(self.capacity, self.drop_oldest);
         let recv = MsgMemRecv {
             ctx: ctx.clone(),
             msg_id: msg_id.clone(),
             drop: self.this.clone(),
-            recv: r,
+            recv: queue.clone(),
         };
         let recv: DynMsgRecv = Box::new(recv);
         self.map.entry(ctx).or_default().insert(
             msg_id.clone(),
             ChanItem {
-                ts: std::time::Instant::now(),
-                send: s,
+                ts: self.clock.now(),
+                seq: Arc::new(std::sync::atomic::AtomicU64::new(0)),
+                send: queue,
                 recv: Some(recv),
             },
         );
@@ -228,15 +402,32 @@ impl ChanMap {
         vec![]
     }
 
+    #[allow(clippy::type_complexity)]
+    fn msg_send_list(
+        &self,
+        ctx: &Arc<str>,
+    ) -> Vec<(Arc<str>, Arc<std::sync::atomic::AtomicU64>, ChanQueue)> {
+        if let Some(m) = self.map.get(ctx) {
+            return m
+                .iter()
+                .map(|(msg_id, i)| {
+                    (msg_id.clone(), i.seq.clone(), i.send.clone())
+                })
+                .collect();
+        }
+        Vec::new()
+    }
+
+    #[allow(clippy::type_complexity)]
     fn msg_send(
         &self,
         ctx: &Arc<str>,
         msg_id: &Arc<str>,
-    ) -> Option<tokio::sync::mpsc::Sender<Message>> {
+    ) -> Option<(Arc<std::sync::atomic::AtomicU64>, ChanQueue)> {
         if let Some(m) = self.map.get(ctx)
             && let Some(s) = m.get(msg_id)
         {
-            return Some(s.send.clone());
+            return Some((s.seq.clone(), s.send.clone()));
         }
         None
     }
@@ -250,6 +441,7 @@ impl ChanMap {
         let mut remove_ctx = false;
         if let Some(m) = self.map.get_mut(ctx) {
             if let Some(mut item) = m.remove(msg_id) {
+                item.send.close();
                 // Take recv out so it isn't dropped while the lock is held.
                 to_drop = item.recv.take();
             }
@@ -268,7 +460,7 @@ struct MsgMemRecv {
     ctx: Arc<str>,
     msg_id: Arc<str>,
     drop: Weak<Mutex<ChanMap>>,
-    recv: tokio::sync::mpsc::Receiver<Message>,
+    recv: ChanQueue,
 }
 
 impl Drop for MsgMemRecv {
@@ -285,3 +477,91 @@ impl MsgRecv for MsgMemRecv {
         Box::pin(async move { self.recv.recv().await })
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn payload(s: &str) -> MessagePayload {
+        MessagePayload::App {
+            msg: bytes::Bytes::copy_from_slice(s.as_bytes()),
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn drop_oldest_keeps_channel_alive() {
+        let msg = MsgMem::create_with(2, true);
+        let msg_id = msg.create("test".into()).await.unwrap();
+        let mut recv =
+            msg.get_recv("test".into(), msg_id.clone()).await.unwrap();
+
+        // a burst past the capacity drops the oldest, not the channel
+        for s in ["one", "two", "three", "four"] {
+            msg.send("test".into(), msg_id.clone(), payload(s))
+                .await
+                .unwrap();
+        }
+
+        // "one" and "two" were shed; the first delivered message
+        // carries the drop count and the seq hole
+        let got = recv.recv().await.unwrap();
+        assert_eq!(3, got.seq);
+        assert_eq!(2, got.gap);
+        let got = recv.recv().await.unwrap();
+        assert_eq!(4, got.seq);
+        assert_eq!(0, got.gap);
+    }
+
+    #[test]
+    fn prune_drops_unclaimed_channels() {
+        let clock = crate::TestClock::new();
+        let map = ChanMap::new(MsgMem::DEF_CAPACITY, false, clock.clone());
+
+        let ctx: Arc<str> = "test".into();
+        let unclaimed = map.lock().unwrap().msg_new(ctx.clone());
+        let claimed = map.lock().unwrap().msg_new(ctx.clone());
+        let recv = map.lock().unwrap().msg_get(&ctx, &claimed).unwrap();
+
+        // freshly created channels survive the prune
+        assert!(map.lock().unwrap().prune().is_empty());
+
+        clock.advance(60.0);
+
+        // past the window, the channel nobody ever claimed is dropped
+        // while the claimed one lives on
+        let dropped = map.lock().unwrap().prune();
+        assert_eq!(1, dropped.len());
+        assert!(map.lock().unwrap().msg_send(&ctx, &unclaimed).is_none());
+        assert!(map.lock().unwrap().msg_send(&ctx, &claimed).is_some());
+
+        drop(dropped);
+        drop(recv);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn overflow_without_drop_oldest_closes() {
+        let msg = MsgMem::create_with(2, false);
+        let msg_id = msg.create("test".into()).await.unwrap();
+        let mut recv =
+            msg.get_recv("test".into(), msg_id.clone()).await.unwrap();
+
+        msg.send("test".into(), msg_id.clone(), payload("one"))
+            .await
+            .unwrap();
+        msg.send("test".into(), msg_id.clone(), payload("two"))
+            .await
+            .unwrap();
+
+        // the overflowing send tears the channel down
+        assert!(
+            msg.send("test".into(), msg_id.clone(), payload("three"))
+                .await
+                .is_err()
+        );
+
+        // already queued messages still drain, then the recv ends
+        assert_eq!(1, recv.recv().await.unwrap().seq);
+        assert_eq!(2, recv.recv().await.unwrap().seq);
+        assert!(recv.recv().await.is_none());
+    }
+}
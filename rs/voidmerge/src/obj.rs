@@ -5,7 +5,11 @@ use bytes::Bytes;
 use std::collections::HashMap;
 use std::sync::Arc;
 
+pub mod obj_encrypt;
 pub mod obj_file;
+#[cfg(feature = "opendal")]
+pub mod obj_opendal;
+pub mod obj_tiered;
 
 /// Low-level object store trait.
 pub trait Obj: 'static + Send + Sync {
@@ -18,16 +22,27 @@ pub trait Obj: 'static + Send + Sync {
     /// Consider tombstoning or otherwise ensure revalidation will fail.
     fn rm(&self, path: Arc<str>) -> BoxFut<'_, Result<()>>;
 
-    /// List objects in the store by path prefix.
+    /// List objects in the store by path prefix, in creation order,
+    /// or newest first when `descending`. `created_lt` bounds the
+    /// range from above (exclusive); `None` means unbounded.
     fn list(
         &self,
         path_prefix: Arc<str>,
         created_gt: f64,
+        created_lt: Option<f64>,
+        descending: bool,
         limit: u32,
     ) -> BoxFut<'_, Result<Vec<Arc<str>>>>;
 
     /// Put an object into the store.
     fn put(&self, path: Arc<str>, obj: Bytes) -> BoxFut<'_, Result<()>>;
+
+    /// Prune expired objects now, instead of waiting for the store's
+    /// background prune interval. Stores without expiry support do
+    /// nothing.
+    fn prune(&self) -> BoxFut<'_, Result<()>> {
+        Box::pin(async move { Ok(()) })
+    }
 }
 
 /// Dyn [Obj] type.
@@ -79,6 +94,9 @@ impl ObjMeta {
     /// System path: "d" for context config.
     pub(crate) const SYS_CTX_CONFIG: &'static str = "d";
 
+    /// System path: "k" for context secrets.
+    pub(crate) const SYS_CTX_SECRET: &'static str = "k";
+
     /// System path: "c" for context.
     pub(crate) const SYS_CTX: &'static str = "c";
 
@@ -123,12 +141,33 @@ impl ObjMeta {
         )
     }
 
+    /// [Self::new_context], escaping the app path component through
+    /// [escape_path_component] so arbitrary user-supplied strings
+    /// (emails, unicode names) can be used without tripping the safe
+    /// character rules.
+    pub fn new_context_escaped(
+        ctx: &str,
+        app_path: &str,
+        created_secs: f64,
+        expires_secs: f64,
+        byte_length: f64,
+    ) -> Self {
+        Self::new_context(
+            ctx,
+            &escape_path_component(app_path),
+            created_secs,
+            expires_secs,
+            byte_length,
+        )
+    }
+
     /// Get the sys_prefix associated with this meta path.
     pub(crate) fn sys_prefix(&self) -> &'static str {
         match self.0.split('/').next() {
             Some(Self::SYS_SETUP) => Self::SYS_SETUP,
             Some(Self::SYS_CTX_SETUP) => Self::SYS_CTX_SETUP,
             Some(Self::SYS_CTX_CONFIG) => Self::SYS_CTX_CONFIG,
+            Some(Self::SYS_CTX_SECRET) => Self::SYS_CTX_SECRET,
             _ => Self::SYS_CTX,
         }
     }
@@ -174,6 +213,142 @@ impl ObjMeta {
             .clamp(0.0, u64::MAX as f64)
             .floor() as u64
     }
+
+    /// Encode a content type into the optional trailing meta path
+    /// segment. Content types contain characters (notably '/') that
+    /// would break path parsing, so the segment is base64url encoded.
+    pub(crate) fn content_type_segment(content_type: &str) -> String {
+        Bytes::copy_from_slice(content_type.as_bytes()).to_b64()
+    }
+
+    /// Get the content type associated with this meta path, if one was
+    /// recorded when the object was stored.
+    pub fn content_type(&self) -> Option<String> {
+        let seg = self.0.split('/').nth(6)?;
+        let raw = Bytes::from_b64(seg).ok()?;
+        String::from_utf8(raw.to_vec()).ok()
+    }
+}
+
+/// Escape an arbitrary string into the characters accepted in app
+/// path components ([a-z], [A-Z], [0-9], '-', '_', '.', and '~').
+/// Every other byte, plus a literal '~', is encoded as '~' followed
+/// by two uppercase hex digits, so the result is reversible via
+/// [unescape_path_component]. Worst case the output is three times
+/// the utf-8 byte length of the input, up to twelve characters per
+/// unicode scalar.
+pub fn escape_path_component(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for b in s.bytes() {
+        match b {
+            b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'-' | b'_'
+            | b'.' => out.push(b as char),
+            _ => out.push_str(&format!("~{b:02X}")),
+        }
+    }
+    out
+}
+
+/// Reverse [escape_path_component]. Errors on a truncated or non-hex
+/// escape sequence, or when the decoded bytes are not valid utf-8.
+pub fn unescape_path_component(s: &str) -> Result<String> {
+    let err =
+        || Error::invalid(format!("invalid path component escape in: {s}"));
+    let mut out = Vec::with_capacity(s.len());
+    let mut bytes = s.bytes();
+    while let Some(b) = bytes.next() {
+        if b == b'~' {
+            let hex =
+                [bytes.next().ok_or_else(err)?, bytes.next().ok_or_else(err)?];
+            let hex = std::str::from_utf8(&hex).map_err(|_| err())?;
+            out.push(u8::from_str_radix(hex, 16).map_err(|_| err())?);
+        } else {
+            out.push(b);
+        }
+    }
+    String::from_utf8(out).map_err(|_| err())
+}
+
+/// Structured fields parsed out of an [ObjMeta] path, as returned by
+/// the detailed obj-list api, so consumers do not have to split the
+/// raw path themselves.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ObjListEntry {
+    /// The app path.
+    #[serde(rename = "appPath")]
+    pub app_path: String,
+
+    /// Seconds since unix epoch when the object was created.
+    #[serde(rename = "createdSecs")]
+    pub created_secs: f64,
+
+    /// Seconds since unix epoch when the object expires,
+    /// or zero for no expiry.
+    #[serde(rename = "expiresSecs")]
+    pub expires_secs: f64,
+
+    /// Byte length of the object data.
+    #[serde(rename = "sizeBytes")]
+    pub size_bytes: u64,
+
+    /// The content type, if one was recorded when the object was
+    /// stored.
+    #[serde(
+        rename = "contentType",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub content_type: Option<String>,
+}
+
+impl From<&ObjMeta> for ObjListEntry {
+    fn from(meta: &ObjMeta) -> Self {
+        Self {
+            app_path: meta.app_path().to_string(),
+            created_secs: meta.created_secs(),
+            expires_secs: meta.expires_secs(),
+            size_bytes: meta.byte_length(),
+            content_type: meta.content_type(),
+        }
+    }
+}
+
+/// App path prefix for historical ctx_config versions kept for
+/// rollback; the assigned version number follows the prefix.
+const CTX_CONFIG_VERSION_PREFIX: &str = "config-v-";
+
+/// Parse the version number out of a historical config meta path.
+fn ctx_config_version(meta: &ObjMeta) -> Option<u64> {
+    meta.app_path()
+        .strip_prefix(CTX_CONFIG_VERSION_PREFIX)?
+        .parse()
+        .ok()
+}
+
+/// The fixed created slot used for setup/config objects. With a
+/// constant created time the full meta path never changes, so backends
+/// that key on the raw path overwrite in place rather than
+/// accumulating a stale object per change. (1 rather than 0, because
+/// list filters are strictly created-greater-than.)
+const SETUP_CREATED_SECS: f64 = 1.0;
+
+/// Schema version written into the [bytes_ext::Versioned] envelope
+/// around persisted setup objects. Objects from before the envelope
+/// existed decode as version 0.
+const SETUP_ENC_VERSION: u16 = 1;
+
+/// Decode a persisted setup object, accepting both the current
+/// [bytes_ext::Versioned] envelope and the bare pre-envelope
+/// encoding.
+fn decode_setup<T: serde::de::DeserializeOwned>(data: &[u8]) -> Result<T> {
+    bytes_ext::decode_versioned(data, SETUP_ENC_VERSION, |version, data| {
+        match version {
+            0 => Bytes::copy_from_slice(data).to_decode(),
+            v => Err(Error::other(format!(
+                "unknown setup encoding version: {v}"
+            ))),
+        }
+    })
 }
 
 /// Object store type.
@@ -187,6 +362,12 @@ impl ObjWrap {
     pub fn new(obj: DynObj) -> Self {
         Self { inner: obj }
     }
+
+    /// Unwrap the inner low-level store, e.g. to compose it into
+    /// another store like [obj_tiered::ObjTiered].
+    pub fn into_dyn(self) -> DynObj {
+        self.inner
+    }
 }
 
 impl ObjWrap {
@@ -210,23 +391,53 @@ impl ObjWrap {
         self.inner.rm(meta.0).await
     }
 
+    /// Prune expired objects now, instead of waiting for the store's
+    /// background prune interval.
+    pub async fn prune(&self) -> Result<()> {
+        tracing::trace!(request = "obj_prune");
+
+        self.inner.prune().await
+    }
+
     /// List objects in the store.
     pub async fn list(
         &self,
         path_prefix: &str,
         created_gt: f64,
         limit: u32,
+    ) -> Result<Vec<ObjMeta>> {
+        self.list_range(path_prefix, created_gt, None, false, limit)
+            .await
+    }
+
+    /// List objects in the store within an optional created range,
+    /// optionally newest first.
+    pub async fn list_range(
+        &self,
+        path_prefix: &str,
+        created_gt: f64,
+        created_lt: Option<f64>,
+        descending: bool,
+        limit: u32,
     ) -> Result<Vec<ObjMeta>> {
         tracing::trace!(
             request = "obj_list",
             ?path_prefix,
             ?created_gt,
+            ?created_lt,
+            ?descending,
             ?limit
         );
 
         Ok(self
             .inner
-            .list(path_prefix.into(), created_gt, limit)
+            .list(
+                path_prefix.into(),
+                created_gt,
+                created_lt,
+                descending,
+                limit,
+            )
             .await?
             .into_iter()
             .map(ObjMeta)
@@ -266,7 +477,7 @@ impl ObjWrap {
             ))
             .await
         {
-            sys_setup.to_decode()
+            decode_setup(&sys_setup)
         } else {
             Ok(SysSetup::default())
         }
@@ -277,7 +488,7 @@ impl ObjWrap {
         &self,
         sys_setup: crate::server::SysSetup,
     ) -> Result<()> {
-        let enc = Bytes::from_encode(&sys_setup)?;
+        let enc = bytes_ext::encode_versioned(SETUP_ENC_VERSION, &sys_setup)?;
         let meta = ObjMeta::new(
             ObjMeta::SYS_SETUP,
             ObjMeta::SYS_SETUP,
@@ -290,6 +501,71 @@ impl ObjWrap {
         Ok(())
     }
 
+    /// Get the hostname to context routing map.
+    pub async fn get_domains(&self) -> Result<HashMap<Arc<str>, Arc<str>>> {
+        if let Ok((_, domains)) = self
+            .get_single(&format!(
+                "{}/{}/domains",
+                ObjMeta::SYS_SETUP,
+                ObjMeta::SYS_SETUP
+            ))
+            .await
+        {
+            domains.to_decode()
+        } else {
+            Ok(HashMap::new())
+        }
+    }
+
+    /// Set the hostname to context routing map.
+    pub async fn set_domains(
+        &self,
+        domains: &HashMap<Arc<str>, Arc<str>>,
+    ) -> Result<()> {
+        let enc = Bytes::from_encode(domains)?;
+        let meta = ObjMeta::new(
+            ObjMeta::SYS_SETUP,
+            ObjMeta::SYS_SETUP,
+            "domains",
+            safe_now(),
+            0.0,
+            enc.len() as f64,
+        );
+        self.put(meta, enc).await?;
+        Ok(())
+    }
+
+    /// Load the server url-signing seed, generating and persisting a
+    /// random one on first use.
+    pub async fn url_sign_seed(&self) -> Result<Bytes> {
+        if let Ok((_, seed)) = self
+            .get_single(&format!(
+                "{}/{}/url-sign-key",
+                ObjMeta::SYS_SETUP,
+                ObjMeta::SYS_SETUP
+            ))
+            .await
+        {
+            return Ok(seed);
+        }
+
+        let mut seed = [0_u8; 32];
+        use rand::Rng;
+        rand::rng().fill(&mut seed);
+        let seed = Bytes::copy_from_slice(&seed);
+
+        let meta = ObjMeta::new(
+            ObjMeta::SYS_SETUP,
+            ObjMeta::SYS_SETUP,
+            "url-sign-key",
+            safe_now(),
+            0.0,
+            seed.len() as f64,
+        );
+        self.put(meta, seed.clone()).await?;
+        Ok(seed)
+    }
+
     /// List all configured ctx setups and configs.
     pub async fn list_ctx_all(
         &self,
@@ -300,20 +576,20 @@ impl ObjWrap {
 
         let mut out: HashMap<Arc<str>, (CtxSetup, CtxConfig)> = HashMap::new();
 
-        let prefix = format!("{}/", ObjMeta::SYS_CTX_SETUP).into();
-        let page = self.inner.list(prefix, 0.0, u32::MAX).await?;
-        for path in page {
-            let setup: CtxSetup =
-                self.get(ObjMeta(path)).await?.1.to_decode()?;
+        for data in self
+            .list_ctx_migrate(ObjMeta::SYS_CTX_SETUP, "setup")
+            .await?
+        {
+            let setup: CtxSetup = decode_setup(&data)?;
             let ctx = setup.ctx.clone();
             out.entry(ctx).or_default().0 = setup;
         }
 
-        let prefix = format!("{}/", ObjMeta::SYS_CTX_CONFIG).into();
-        let page = self.inner.list(prefix, 0.0, u32::MAX).await?;
-        for path in page {
-            let config: CtxConfig =
-                self.get(ObjMeta(path)).await?.1.to_decode()?;
+        for data in self
+            .list_ctx_migrate(ObjMeta::SYS_CTX_CONFIG, "config")
+            .await?
+        {
+            let config: CtxConfig = data.to_decode()?;
             let ctx = config.ctx.clone();
             out.entry(ctx).or_default().1 = config;
         }
@@ -321,17 +597,86 @@ impl ObjWrap {
         Ok(out)
     }
 
+    /// Read the setup/config objects under a sys prefix, one per ctx.
+    /// Legacy stores wrote a new timestamped object per change; when
+    /// legacy entries for a ctx are found, the newest is rewritten at
+    /// the canonical fixed path and the rest are removed.
+    async fn list_ctx_migrate(
+        &self,
+        sys_prefix: &'static str,
+        app_path: &'static str,
+    ) -> Result<Vec<Bytes>> {
+        use std::collections::hash_map::Entry;
+
+        let prefix: Arc<str> = format!("{sys_prefix}/").into();
+        let page =
+            self.inner.list(prefix, 0.0, None, false, u32::MAX).await?;
+
+        let mut newest: HashMap<Arc<str>, ObjMeta> = HashMap::new();
+        let mut stale: Vec<ObjMeta> = Vec::new();
+
+        for path in page {
+            let meta = ObjMeta(path);
+            // versioned config history entries live alongside the
+            // canonical object and are not migration candidates
+            if meta.app_path().starts_with(CTX_CONFIG_VERSION_PREFIX) {
+                continue;
+            }
+            match newest.entry(meta.ctx().into()) {
+                Entry::Vacant(e) => {
+                    e.insert(meta);
+                }
+                Entry::Occupied(mut e) => {
+                    if meta.created_secs() > e.get().created_secs() {
+                        stale.push(e.insert(meta));
+                    } else {
+                        stale.push(meta);
+                    }
+                }
+            }
+        }
+
+        for meta in stale {
+            let _ = self.rm(meta).await;
+        }
+
+        let mut out = Vec::new();
+
+        for (ctx, meta) in newest {
+            let (meta, data) = self.get(meta).await?;
+            if meta.created_secs() != SETUP_CREATED_SECS {
+                // migrate a legacy timestamped entry to the canonical
+                // path. remove first: backends that dedupe by app path
+                // would otherwise treat the older canonical write as
+                // stale and discard it
+                self.rm(meta).await?;
+                let canonical = ObjMeta::new(
+                    sys_prefix,
+                    &ctx,
+                    app_path,
+                    SETUP_CREATED_SECS,
+                    0.0,
+                    data.len() as f64,
+                );
+                self.put(canonical, data.clone()).await?;
+            }
+            out.push(data);
+        }
+
+        Ok(out)
+    }
+
     /// Set a ctx_setup.
     pub async fn set_ctx_setup(
         &self,
         ctx_setup: crate::server::CtxSetup,
     ) -> Result<()> {
-        let enc = Bytes::from_encode(&ctx_setup)?;
+        let enc = bytes_ext::encode_versioned(SETUP_ENC_VERSION, &ctx_setup)?;
         let meta = ObjMeta::new(
             ObjMeta::SYS_CTX_SETUP,
             &ctx_setup.ctx,
             "setup",
-            safe_now(),
+            SETUP_CREATED_SECS,
             0.0,
             enc.len() as f64,
         );
@@ -349,19 +694,140 @@ impl ObjWrap {
             ObjMeta::SYS_CTX_CONFIG,
             &ctx_config.ctx,
             "config",
-            safe_now(),
+            SETUP_CREATED_SECS,
             0.0,
             enc.len() as f64,
         );
         self.put(meta, enc).await?;
         Ok(())
     }
+
+    /// Remove the stored setup, config, and config version history
+    /// for a ctx. User objects under `c/{ctx}/` are not touched.
+    pub async fn rm_ctx(&self, ctx: &str) -> Result<()> {
+        for sys_prefix in
+            [ObjMeta::SYS_CTX_SETUP, ObjMeta::SYS_CTX_CONFIG]
+        {
+            for meta in self
+                .list(&format!("{sys_prefix}/{ctx}/"), 0.0, u32::MAX)
+                .await?
+            {
+                self.rm(meta).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Store a historical copy of a ctx_config for rollback,
+    /// assigning the next version number, then prune history down to
+    /// `keep` entries (oldest removed first). Returns the assigned
+    /// version.
+    pub async fn push_ctx_config_version(
+        &self,
+        ctx_config: &crate::server::CtxConfig,
+        keep: u32,
+    ) -> Result<u64> {
+        let versions =
+            self.list_ctx_config_versions(&ctx_config.ctx).await?;
+        let version = versions.last().map(|(v, _)| v + 1).unwrap_or(1);
+        let enc = Bytes::from_encode(ctx_config)?;
+        let meta = ObjMeta::new(
+            ObjMeta::SYS_CTX_CONFIG,
+            &ctx_config.ctx,
+            &format!("{CTX_CONFIG_VERSION_PREFIX}{version}"),
+            safe_now(),
+            0.0,
+            enc.len() as f64,
+        );
+        self.put(meta, enc).await?;
+        let keep = keep.max(1) as usize;
+        let total = versions.len() + 1;
+        for (_, meta) in
+            versions.into_iter().take(total.saturating_sub(keep))
+        {
+            self.rm(meta).await?;
+        }
+        Ok(version)
+    }
+
+    /// List stored historical config versions for a ctx, oldest
+    /// first.
+    pub async fn list_ctx_config_versions(
+        &self,
+        ctx: &str,
+    ) -> Result<Vec<(u64, ObjMeta)>> {
+        let prefix: Arc<str> = format!(
+            "{}/{ctx}/{CTX_CONFIG_VERSION_PREFIX}",
+            ObjMeta::SYS_CTX_CONFIG,
+        )
+        .into();
+        let page =
+            self.inner.list(prefix, 0.0, None, false, u32::MAX).await?;
+        let mut out = Vec::with_capacity(page.len());
+        for path in page {
+            let meta = ObjMeta(path);
+            if let Some(version) = ctx_config_version(&meta) {
+                out.push((version, meta));
+            }
+        }
+        out.sort_by_key(|(version, _)| *version);
+        Ok(out)
+    }
+
+    /// Get one stored historical config version for a ctx.
+    pub async fn get_ctx_config_version(
+        &self,
+        ctx: &str,
+        version: u64,
+    ) -> Result<crate::server::CtxConfig> {
+        for (v, meta) in self.list_ctx_config_versions(ctx).await? {
+            if v == version {
+                return self.get(meta).await?.1.to_decode();
+            }
+        }
+        Err(Error::not_found(format!(
+            "no config version {version} for context: {ctx}"
+        )))
+    }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
 
+    #[test]
+    fn path_component_escape_round_trip() {
+        // explicit cases: separator, percent, and already-escaped
+        // input, which must re-escape rather than double-decode
+        assert_eq!("a~2Fb", escape_path_component("a/b"));
+        assert_eq!("100~25", escape_path_component("100%"));
+        assert_eq!("~7E2F", escape_path_component("~2F"));
+        for s in ["a/b", "100%", "~2F", "", "safe-str_input.only"] {
+            let esc = escape_path_component(s);
+            crate::safe_str(&esc).unwrap();
+            assert_eq!(s, unescape_path_component(&esc).unwrap());
+        }
+
+        // random unicode round-trips and always passes the safe rules
+        use rand::Rng;
+        let mut rng = rand::rng();
+        for _ in 0..100 {
+            let s: String = (0..16)
+                .filter_map(|_| char::from_u32(rng.random_range(0..=0x10ffff)))
+                .collect();
+            let esc = escape_path_component(&s);
+            crate::safe_str(&esc).unwrap();
+            assert_eq!(s, unescape_path_component(&esc).unwrap());
+        }
+
+        // malformed escapes error instead of decoding garbage
+        assert!(unescape_path_component("~").is_err());
+        assert!(unescape_path_component("~2").is_err());
+        assert!(unescape_path_component("~ZZ").is_err());
+        // 0xFF alone is not valid utf-8
+        assert!(unescape_path_component("~FF").is_err());
+    }
+
     #[tokio::test]
     async fn obj_wrap() {
         let o = obj_file::ObjFile::create(None).await.unwrap();
@@ -392,4 +858,225 @@ mod test {
 
         assert_eq!(b"hello", got.as_ref());
     }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn ctx_setup_fixed_path_idempotent() {
+        let o = obj_file::ObjFile::create(None).await.unwrap();
+
+        let mut setup = crate::server::CtxSetup {
+            ctx: "AAAA".into(),
+            ..Default::default()
+        };
+        o.set_ctx_setup(setup.clone()).await.unwrap();
+        setup.timeout_secs = 5.0;
+        o.set_ctx_setup(setup.clone()).await.unwrap();
+        o.set_ctx_config(crate::server::CtxConfig {
+            ctx: "AAAA".into(),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+
+        // repeated writes land on a single canonical path
+        let list = o
+            .list(
+                &format!("{}/AAAA/", ObjMeta::SYS_CTX_SETUP),
+                0.0,
+                u32::MAX,
+            )
+            .await
+            .unwrap();
+        assert_eq!(1, list.len());
+        assert_eq!(SETUP_CREATED_SECS, list[0].created_secs());
+
+        let all = o.list_ctx_all().await.unwrap();
+        assert_eq!(5.0, all.get("AAAA").unwrap().0.timeout_secs);
+    }
+
+    #[test]
+    fn obj_meta_byte_length() {
+        // modern metas carry the trailing length segment
+        let meta =
+            ObjMeta::new(ObjMeta::SYS_CTX, "AAAA", "test", 5.0, 0.0, 42.0);
+        assert_eq!(42, meta.byte_length());
+
+        // legacy metas written before the length segment parse as zero
+        // rather than erroring
+        let legacy = ObjMeta("c/AAAA/test/5/0".into());
+        assert_eq!(0, legacy.byte_length());
+        assert_eq!("test", legacy.app_path());
+        assert_eq!(5.0, legacy.created_secs());
+    }
+
+    #[tokio::test]
+    async fn obj_list_metas_expose_byte_length() {
+        let o = obj_file::ObjFile::create(None).await.unwrap();
+
+        let data = Bytes::from_static(b"hello");
+        o.put(
+            ObjMeta::new(
+                ObjMeta::SYS_CTX,
+                "AAAA",
+                "test",
+                safe_now(),
+                0.0,
+                data.len() as f64,
+            ),
+            data,
+        )
+        .await
+        .unwrap();
+
+        // sizes come straight off the listed metas, no get required
+        let list = o
+            .list(&format!("{}/AAAA/", ObjMeta::SYS_CTX), 0.0, u32::MAX)
+            .await
+            .unwrap();
+        assert_eq!(1, list.len());
+        assert_eq!(5, list[0].byte_length());
+    }
+
+    #[tokio::test]
+    async fn obj_list_range_descending_paging() {
+        let o = obj_file::ObjFile::create(None).await.unwrap();
+
+        for (name, created) in
+            [("a", 5.0), ("b", 5.0), ("c", 5.0), ("d", 6.0), ("e", 6.0)]
+        {
+            o.put(
+                ObjMeta::new(
+                    ObjMeta::SYS_CTX,
+                    "AAAA",
+                    name,
+                    created,
+                    0.0,
+                    1.0,
+                ),
+                Bytes::from_static(b"x"),
+            )
+            .await
+            .unwrap();
+        }
+
+        // newest first: the 6.0 items fill the first page
+        let page =
+            o.list_range("c/AAAA/", 0.0, None, true, 2).await.unwrap();
+        assert_eq!(2, page.len());
+        assert!(page.iter().all(|m| m.created_secs() == 6.0));
+
+        // resuming below the first page overflows the limit to
+        // include all items tied on the boundary timestamp
+        let page = o
+            .list_range("c/AAAA/", 0.0, Some(6.0), true, 2)
+            .await
+            .unwrap();
+        assert_eq!(3, page.len());
+        assert!(page.iter().all(|m| m.created_secs() == 5.0));
+    }
+
+    /// Minimal path-keyed store with no ordering and no dedup by app
+    /// path, like a naive remote backend.
+    struct DumbObj(std::sync::Mutex<HashMap<Arc<str>, Bytes>>);
+
+    impl Obj for DumbObj {
+        fn get(
+            &self,
+            path: Arc<str>,
+        ) -> BoxFut<'_, Result<(Arc<str>, Bytes)>> {
+            Box::pin(async move {
+                let data = self
+                    .0
+                    .lock()
+                    .unwrap()
+                    .get(&path)
+                    .cloned()
+                    .ok_or_else(|| Error::not_found("no such path"))?;
+                Ok((path, data))
+            })
+        }
+
+        fn rm(&self, path: Arc<str>) -> BoxFut<'_, Result<()>> {
+            Box::pin(async move {
+                self.0.lock().unwrap().remove(&path);
+                Ok(())
+            })
+        }
+
+        fn list(
+            &self,
+            path_prefix: Arc<str>,
+            _created_gt: f64,
+            _created_lt: Option<f64>,
+            _descending: bool,
+            limit: u32,
+        ) -> BoxFut<'_, Result<Vec<Arc<str>>>> {
+            Box::pin(async move {
+                Ok(self
+                    .0
+                    .lock()
+                    .unwrap()
+                    .keys()
+                    .filter(|k| k.starts_with(&*path_prefix))
+                    .take(limit as usize)
+                    .cloned()
+                    .collect())
+            })
+        }
+
+        fn put(&self, path: Arc<str>, obj: Bytes) -> BoxFut<'_, Result<()>> {
+            Box::pin(async move {
+                self.0.lock().unwrap().insert(path, obj);
+                Ok(())
+            })
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn ctx_setup_legacy_migration_newest_wins() {
+        let o = ObjWrap::new(Arc::new(DumbObj(Default::default())));
+
+        // legacy stores wrote a new timestamped object per change
+        for (created, timeout_secs) in
+            [(1000.0, 3.0), (3000.0, 9.0), (2000.0, 6.0)]
+        {
+            let setup = crate::server::CtxSetup {
+                ctx: "AAAA".into(),
+                timeout_secs,
+                ..Default::default()
+            };
+            let enc = Bytes::from_encode(&setup).unwrap();
+            o.put(
+                ObjMeta::new(
+                    ObjMeta::SYS_CTX_SETUP,
+                    "AAAA",
+                    "setup",
+                    created,
+                    0.0,
+                    enc.len() as f64,
+                ),
+                enc,
+            )
+            .await
+            .unwrap();
+        }
+
+        let all = o.list_ctx_all().await.unwrap();
+        assert_eq!(9.0, all.get("AAAA").unwrap().0.timeout_secs);
+
+        // stale versions removed, newest rewritten at the fixed path
+        let list = o
+            .list(
+                &format!("{}/", ObjMeta::SYS_CTX_SETUP),
+                0.0,
+                u32::MAX,
+            )
+            .await
+            .unwrap();
+        assert_eq!(1, list.len());
+        assert_eq!(SETUP_CREATED_SECS, list[0].created_secs());
+
+        // a second load is a no-op
+        let all = o.list_ctx_all().await.unwrap();
+        assert_eq!(9.0, all.get("AAAA").unwrap().0.timeout_secs);
+    }
 }
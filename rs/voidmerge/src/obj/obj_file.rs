@@ -5,15 +5,62 @@ use crate::obj::*;
 use std::sync::Mutex;
 
 #[derive(Clone)]
-struct Info {
-    pub meta_path: std::path::PathBuf,
-    pub data_path: std::path::PathBuf,
+enum Info {
+    /// Two-file layout: the meta and data stored as a `meta-*` /
+    /// `data-*` pair.
+    Split {
+        meta_path: std::path::PathBuf,
+        data_path: std::path::PathBuf,
+    },
+
+    /// Single-file layout for small objects: an `obj-*` file holding
+    /// the meta line, a newline, then the raw data bytes.
+    Inline { path: std::path::PathBuf },
+}
+
+impl IndexTiebreak for Info {
+    // the content hash embedded in the file name, so timestamp ties
+    // between syncing nodes resolve to the same winner everywhere
+    fn tiebreak(&self) -> Option<&str> {
+        let name = match self {
+            Info::Split { meta_path, .. } => meta_path.file_name(),
+            Info::Inline { path } => path.file_name(),
+        }?;
+        name.to_str()?.split_once('-').map(|(_, hash)| hash)
+    }
+}
+
+/// Report from [ObjFile::verify].
+#[derive(Debug, Default, Clone)]
+pub struct VerifyReport {
+    /// Count of meta/data entry pairs checked.
+    pub checked: u64,
+
+    /// Meta paths of entries whose recomputed content hash did not
+    /// match the filename hash.
+    pub hash_mismatches: Vec<std::path::PathBuf>,
+
+    /// `meta-*` files with no matching `data-*` file, and vice versa.
+    pub orphaned: Vec<std::path::PathBuf>,
+
+    /// Count of orphaned files deleted and mismatched entries
+    /// quarantined in repair mode.
+    pub repaired: u64,
+
+    /// Total bytes of orphaned files deleted in repair mode.
+    /// Quarantined entries remain on disk, so they do not count.
+    pub bytes_reclaimed: u64,
 }
 
 /// File-backed object store.
 pub struct ObjFile {
     root: std::path::PathBuf,
     index: Mutex<MemIndex<Info>>,
+    // serializes puts to the same sys/ctx/app_path so two racing
+    // writers cannot interleave their file writes and index updates
+    path_locks:
+        Mutex<std::collections::HashMap<Arc<str>, Arc<tokio::sync::Mutex<()>>>>,
+    inline_data_bytes: usize,
     task: tokio::task::AbortHandle,
     tempdir: Option<tempfile::TempDir>,
 }
@@ -30,10 +77,42 @@ impl Drop for ObjFile {
 }
 
 impl ObjFile {
+    /// Default inline data threshold.
+    pub const DEF_INLINE_DATA_BYTES: usize = 4096;
+
     /// Construct a new file-backed object store.
     ///
     /// If root is `None`, a tempdir will be used.
     pub async fn create(root: Option<std::path::PathBuf>) -> Result<ObjWrap> {
+        Self::create_with_inline(root, Self::DEF_INLINE_DATA_BYTES).await
+    }
+
+    /// Construct a new file-backed object store with a custom inline
+    /// threshold: objects whose data is below `inline_data_bytes` are
+    /// written as one combined file instead of a meta/data pair,
+    /// halving the file count for stores dominated by tiny objects.
+    /// Both layouts are read back transparently, so the threshold can
+    /// change between runs without migrating the store.
+    pub async fn create_with_inline(
+        root: Option<std::path::PathBuf>,
+        inline_data_bytes: usize,
+    ) -> Result<ObjWrap> {
+        Self::create_with_clock(
+            root,
+            inline_data_bytes,
+            std::sync::Arc::new(crate::SysClock),
+        )
+        .await
+    }
+
+    /// Construct a new file-backed object store reading expiry time
+    /// from an injected clock, so tests can drive TTL behavior with a
+    /// [crate::TestClock] and [ObjWrap::prune] instead of real sleeps.
+    pub async fn create_with_clock(
+        root: Option<std::path::PathBuf>,
+        inline_data_bytes: usize,
+        clock: crate::DynVmClock,
+    ) -> Result<ObjWrap> {
         let mut tempdir = None;
 
         let root = if let Some(root) = root {
@@ -53,12 +132,7 @@ impl ObjFile {
                     tokio::time::sleep(std::time::Duration::from_secs(10))
                         .await;
                     if let Some(this) = this.upgrade() {
-                        let path_list = {
-                            let mut lock = this.index.lock().unwrap();
-                            lock.prune();
-                            lock.get_delete()
-                        };
-                        destroy(path_list).await;
+                        this.prune_index().await;
 
                         let now = std::time::Instant::now();
                         let diff_sec = (now - last_meter).as_secs_f64();
@@ -81,7 +155,9 @@ impl ObjFile {
             .abort_handle();
             Self {
                 root,
-                index: Mutex::new(MemIndex::default()),
+                index: Mutex::new(MemIndex::with_clock(clock)),
+                path_locks: Mutex::new(std::collections::HashMap::new()),
+                inline_data_bytes,
                 task,
                 tempdir,
             }
@@ -96,6 +172,170 @@ impl ObjFile {
         Ok(out)
     }
 
+    /// Audit an object store tree on disk without constructing a
+    /// store: recompute the sha256 over `meta + data` for every
+    /// entry, compare it to the filename hash, and report orphaned
+    /// `meta-*`/`data-*` files.
+    ///
+    /// With `repair` set, orphaned files are deleted and mismatched
+    /// entries are moved into a `_quarantine/` directory under the
+    /// store root for the operator to inspect; `_quarantine/` is
+    /// ignored by the loader and by later verify runs. Without
+    /// `repair`, nothing is touched.
+    pub async fn verify(
+        root: &std::path::Path,
+        repair: bool,
+    ) -> Result<VerifyReport> {
+        use base64::prelude::*;
+        use sha2::{Digest, Sha256};
+
+        let mut report = VerifyReport::default();
+
+        // mirror the load_* descent: sys_prefix/ctx/h1/h2
+        let mut levels = vec![root.to_path_buf()];
+        for depth in 0..4 {
+            let mut next = Vec::new();
+            for path in levels {
+                let mut dir = tokio::fs::read_dir(&path).await?;
+                while let Some(e) = dir.next_entry().await? {
+                    if !e.file_type().await?.is_dir() {
+                        continue;
+                    }
+                    if depth == 0
+                        && e.file_name().to_string_lossy().len() != 1
+                    {
+                        continue;
+                    }
+                    next.push(e.path());
+                }
+            }
+            levels = next;
+        }
+
+        for dir_path in levels {
+            let mut meta_hashes = std::collections::HashSet::new();
+            let mut data_hashes = std::collections::HashSet::new();
+            let mut obj_hashes = std::collections::HashSet::new();
+
+            let mut dir = tokio::fs::read_dir(&dir_path).await?;
+            while let Some(e) = dir.next_entry().await? {
+                if !e.file_type().await?.is_file() {
+                    continue;
+                }
+                let name = e.file_name().to_string_lossy().to_string();
+                if let Some(hash) = name.strip_prefix("meta-") {
+                    meta_hashes.insert(hash.to_string());
+                } else if let Some(hash) = name.strip_prefix("data-") {
+                    data_hashes.insert(hash.to_string());
+                } else if let Some(hash) = name.strip_prefix("obj-") {
+                    obj_hashes.insert(hash.to_string());
+                }
+            }
+
+            for hash in &meta_hashes {
+                let meta_path = dir_path.join(format!("meta-{hash}"));
+                if !data_hashes.contains(hash) {
+                    report.orphaned.push(meta_path);
+                    continue;
+                }
+                report.checked += 1;
+                let meta = tokio::fs::read(&meta_path).await?;
+                let data = tokio::fs::read(
+                    dir_path.join(format!("data-{hash}")),
+                )
+                .await?;
+                let mut hasher = Sha256::new();
+                hasher.update(&meta);
+                hasher.update(&data);
+                let actual =
+                    BASE64_URL_SAFE_NO_PAD.encode(hasher.finalize());
+                if actual != *hash {
+                    report.hash_mismatches.push(meta_path);
+                }
+            }
+
+            for hash in &data_hashes {
+                if !meta_hashes.contains(hash) {
+                    report
+                        .orphaned
+                        .push(dir_path.join(format!("data-{hash}")));
+                }
+            }
+
+            // inline entries hash the same meta + data bytes, minus
+            // the newline separating them in the combined file
+            for hash in &obj_hashes {
+                let path = dir_path.join(format!("obj-{hash}"));
+                report.checked += 1;
+                let raw = tokio::fs::read(&path).await?;
+                let split =
+                    raw.iter().position(|b| *b == b'\n').unwrap_or(raw.len());
+                let mut hasher = Sha256::new();
+                hasher.update(&raw[..split]);
+                if split < raw.len() {
+                    hasher.update(&raw[split + 1..]);
+                }
+                let actual =
+                    BASE64_URL_SAFE_NO_PAD.encode(hasher.finalize());
+                if actual != *hash {
+                    report.hash_mismatches.push(path);
+                }
+            }
+        }
+
+        if repair {
+            for path in &report.orphaned {
+                report.bytes_reclaimed +=
+                    tokio::fs::metadata(path).await?.len();
+                tokio::fs::remove_file(path).await?;
+                report.repaired += 1;
+            }
+
+            if !report.hash_mismatches.is_empty() {
+                let quarantine = root.join("_quarantine");
+                tokio::fs::create_dir_all(&quarantine).await?;
+                for path in &report.hash_mismatches {
+                    let name =
+                        path.file_name().unwrap().to_string_lossy();
+                    tokio::fs::rename(path, quarantine.join(&*name))
+                        .await?;
+                    // a mismatched split pair is quarantined whole so
+                    // the entry can be reassembled for inspection
+                    if let Some(hash) = name.strip_prefix("meta-")
+                        && let Some(parent) = path.parent()
+                    {
+                        let data_name = format!("data-{hash}");
+                        tokio::fs::rename(
+                            parent.join(&data_name),
+                            quarantine.join(data_name),
+                        )
+                        .await?;
+                    }
+                    report.repaired += 1;
+                }
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// Prune expired entries from the index and destroy their files.
+    async fn prune_index(&self) {
+        let path_list = {
+            let mut lock = self.index.lock().unwrap();
+            lock.prune();
+            lock.get_delete()
+        };
+        destroy(path_list).await;
+
+        // drop per-path write locks nobody is currently holding, so
+        // the table tracks live contention rather than path history
+        self.path_locks
+            .lock()
+            .unwrap()
+            .retain(|_, l| Arc::strong_count(l) > 1);
+    }
+
     async fn load(&self) -> Result<()> {
         let mut dir = tokio::fs::read_dir(&self.root).await?;
         while let Some(e) = dir.next_entry().await? {
@@ -167,12 +407,16 @@ impl ObjFile {
         sys_prefix: Arc<str>,
         ctx: Arc<str>,
     ) -> Result<()> {
+        let mut meta_hashes = std::collections::HashSet::new();
+        let mut data_files = Vec::new();
+
         let mut dir = tokio::fs::read_dir(&path).await?;
         while let Some(e) = dir.next_entry().await? {
             if e.file_type().await?.is_file() {
                 let name = e.file_name().to_string_lossy().to_string();
                 if name.starts_with("meta-") {
                     let hash = name.trim_start_matches("meta-");
+                    meta_hashes.insert(hash.to_string());
                     self.load_meta(
                         e.path(),
                         path.join(format!("data-{hash}")),
@@ -180,10 +424,35 @@ impl ObjFile {
                         ctx.clone(),
                     )
                     .await?;
+                } else if name.starts_with("obj-") {
+                    self.load_inline(
+                        e.path(),
+                        sys_prefix.clone(),
+                        ctx.clone(),
+                    )
+                    .await?;
+                } else if let Some(hash) = name.strip_prefix("data-") {
+                    data_files.push((hash.to_string(), e.path()));
                 }
             }
         }
 
+        // a crash after writing data-<hash> but before meta-<hash>
+        // leaves a data file that is never indexed or served: clean
+        // it up rather than letting it accumulate forever
+        let orphans: Vec<(ObjMeta, Info)> = data_files
+            .into_iter()
+            .filter(|(hash, _)| !meta_hashes.contains(hash))
+            .map(|(_, path)| {
+                tracing::warn!(
+                    ?path,
+                    "removing orphaned obj store data file"
+                );
+                (ObjMeta::default(), Info::Inline { path })
+            })
+            .collect();
+        destroy(orphans).await;
+
         Ok(())
     }
 
@@ -213,7 +482,7 @@ impl ObjFile {
             let mut lock = self.index.lock().unwrap();
             lock.put(
                 meta,
-                Info {
+                Info::Split {
                     meta_path,
                     data_path,
                 },
@@ -225,13 +494,57 @@ impl ObjFile {
 
         Ok(())
     }
+
+    async fn load_inline(
+        &self,
+        path: std::path::PathBuf,
+        sys_prefix: Arc<str>,
+        ctx: Arc<str>,
+    ) -> Result<()> {
+        let raw = tokio::fs::read(&path).await?;
+        let split = raw.iter().position(|b| *b == b'\n').unwrap_or(raw.len());
+        let meta = match std::str::from_utf8(&raw[..split]) {
+            Ok(meta) => ObjMeta(meta.trim().to_string().into()),
+            Err(_) => {
+                tracing::warn!(?path, "corrupt obj store on disk");
+                return Ok(());
+            }
+        };
+        if meta.sys_prefix() != &*sys_prefix || meta.ctx() != &*ctx {
+            tracing::warn!(?path, "corrupt obj store on disk");
+            return Ok(());
+        }
+
+        let path_list = {
+            let mut lock = self.index.lock().unwrap();
+            lock.put(meta, Info::Inline { path });
+            lock.get_delete()
+        };
+
+        destroy(path_list).await;
+
+        Ok(())
+    }
 }
 
 impl Obj for ObjFile {
     fn get(&self, path: Arc<str>) -> BoxFut<'_, Result<(Arc<str>, Bytes)>> {
         Box::pin(async move {
             let (meta, info) = self.index.lock().unwrap().get(ObjMeta(path))?;
-            let data = tokio::fs::read(info.data_path).await?.into();
+            let data = match info {
+                Info::Split { data_path, .. } => {
+                    tokio::fs::read(data_path).await?.into()
+                }
+                Info::Inline { path } => {
+                    let mut raw = tokio::fs::read(path).await?;
+                    let split = raw
+                        .iter()
+                        .position(|b| *b == b'\n')
+                        .map(|p| p + 1)
+                        .unwrap_or(raw.len());
+                    raw.split_off(split).into()
+                }
+            };
             Ok((meta.0, data))
         })
     }
@@ -253,6 +566,8 @@ impl Obj for ObjFile {
         &self,
         path_prefix: Arc<str>,
         created_gt: f64,
+        created_lt: Option<f64>,
+        descending: bool,
         limit: u32,
     ) -> BoxFut<'_, Result<Vec<Arc<str>>>> {
         Box::pin(async move {
@@ -260,7 +575,14 @@ impl Obj for ObjFile {
                 .index
                 .lock()
                 .unwrap()
-                .list(path_prefix, created_gt, limit))
+                .range(
+                    path_prefix,
+                    created_gt,
+                    created_lt.unwrap_or(f64::MAX),
+                    descending,
+                    limit,
+                )
+                .0)
         })
     }
 
@@ -280,6 +602,20 @@ impl Obj for ObjFile {
                 return Err(Error::other("appPath cannot be empty"));
             }
 
+            // hold the per-path lock across all the filesystem writes
+            // and the index update, so a racing put to the same path
+            // cannot index one writer's meta against the other's data
+            let path_lock = self
+                .path_locks
+                .lock()
+                .unwrap()
+                .entry(
+                    format!("{sys_prefix}/{ctx}/{}", meta.app_path()).into(),
+                )
+                .or_default()
+                .clone();
+            let _path_guard = path_lock.lock().await;
+
             let mut hasher = Sha256::new();
             hasher.update(meta.as_bytes());
             hasher.update(&data);
@@ -297,22 +633,35 @@ impl Obj for ObjFile {
 
             tokio::fs::create_dir_all(&dir).await?;
 
-            let meta_path = dir.join(format!("meta-{hash}"));
-            tokio::fs::write(&meta_path, meta.as_bytes()).await?;
-
-            let data_path = dir.join(format!("data-{hash}"));
-            tokio::fs::write(&data_path, data).await?;
+            let info = if data.len() < self.inline_data_bytes {
+                // small objects combine meta and data in one file to
+                // halve the inode count
+                let path = dir.join(format!("obj-{hash}"));
+                let mut buf = Vec::with_capacity(
+                    meta.as_bytes().len() + 1 + data.len(),
+                );
+                buf.extend_from_slice(meta.as_bytes());
+                buf.push(b'\n');
+                buf.extend_from_slice(&data);
+                tokio::fs::write(&path, buf).await?;
+                Info::Inline { path }
+            } else {
+                let meta_path = dir.join(format!("meta-{hash}"));
+                tokio::fs::write(&meta_path, meta.as_bytes()).await?;
+
+                let data_path = dir.join(format!("data-{hash}"));
+                tokio::fs::write(&data_path, data).await?;
+
+                Info::Split {
+                    meta_path,
+                    data_path,
+                }
+            };
 
             // finally if all the writes succeeded, update our map
             let path_list = {
                 let mut lock = self.index.lock().unwrap();
-                lock.put(
-                    meta,
-                    Info {
-                        meta_path,
-                        data_path,
-                    },
-                );
+                lock.put(meta, info);
                 lock.get_delete()
             };
 
@@ -321,22 +670,28 @@ impl Obj for ObjFile {
             Ok(())
         })
     }
+
+    fn prune(&self) -> BoxFut<'_, Result<()>> {
+        Box::pin(async move {
+            self.prune_index().await;
+            Ok(())
+        })
+    }
 }
 
 async fn destroy(list: Vec<(ObjMeta, Info)>) {
-    for (
-        _,
-        Info {
-            meta_path,
-            data_path,
-        },
-    ) in list
-    {
-        if let Err(err) = tokio::fs::remove_file(&meta_path).await {
-            tracing::warn!(?err, "failed to remove object store path");
-        }
-        if let Err(err) = tokio::fs::remove_file(&data_path).await {
-            tracing::warn!(?err, "failed to remove object store path");
+    for (_, info) in list {
+        let paths = match info {
+            Info::Split {
+                meta_path,
+                data_path,
+            } => vec![meta_path, data_path],
+            Info::Inline { path } => vec![path],
+        };
+        for path in paths {
+            if let Err(err) = tokio::fs::remove_file(&path).await {
+                tracing::warn!(?err, "failed to remove object store path");
+            }
         }
     }
 }
@@ -372,6 +727,7 @@ mod test {
 
         let of = ObjFile::create(Some(td.path().into())).await.unwrap();
 
+        // small objects replace through the inline layout
         of.put(
             "c/AAAA/bob/1.0/0.0".into(),
             bytes::Bytes::from_static(b"hello"),
@@ -386,21 +742,157 @@ mod test {
         .await
         .unwrap();
 
-        let mut file_count = 0;
+        // large objects replace through the split layout
+        of.put(
+            "c/AAAA/big/1.0/0.0".into(),
+            bytes::Bytes::from(vec![b'x'; 5000]),
+        )
+        .await
+        .unwrap();
+
+        of.put(
+            "c/AAAA/big/2.0/0.0".into(),
+            bytes::Bytes::from(vec![b'y'; 5000]),
+        )
+        .await
+        .unwrap();
+
+        let mut obj_count = 0;
+        let mut meta_count = 0;
 
         let mut dir = async_walkdir::WalkDir::new(td.path());
         use futures::StreamExt;
         while let Some(entry) = dir.next().await {
             let entry = entry.unwrap();
-            if entry.path().is_file()
-                && entry.file_name().to_string_lossy().starts_with("meta-")
-            {
+            if entry.path().is_file() {
+                let name = entry.file_name().to_string_lossy().to_string();
                 println!("{:?}", entry.path());
+                if name.starts_with("obj-") {
+                    obj_count += 1;
+                } else if name.starts_with("meta-") {
+                    meta_count += 1;
+                }
+            }
+        }
+
+        assert_eq!(1, obj_count);
+        assert_eq!(1, meta_count);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn ttl_expiry_with_virtual_clock() {
+        let clock = crate::TestClock::new();
+        let of = ObjFile::create_with_clock(
+            None,
+            ObjFile::DEF_INLINE_DATA_BYTES,
+            clock.clone(),
+        )
+        .await
+        .unwrap();
+
+        let now = clock.now();
+
+        // one item with no expiry, one expiring five seconds out
+        of.put(
+            format!("c/AAAA/keep/{now}/0").into(),
+            bytes::Bytes::from_static(b"hello"),
+        )
+        .await
+        .unwrap();
+        of.put(
+            format!("c/AAAA/ttl/{now}/{}", now + 5.0).into(),
+            bytes::Bytes::from_static(b"world"),
+        )
+        .await
+        .unwrap();
+
+        // pruning before expiry removes nothing
+        of.prune().await.unwrap();
+        assert_eq!(2, of.list("c/AAAA/".into(), 0.0, 100).await.unwrap().len());
+
+        // advancing past the ttl and pruning takes immediate effect,
+        // no real sleep required
+        clock.advance(10.0);
+        of.prune().await.unwrap();
+
+        let list = of.list("c/AAAA/".into(), 0.0, 100).await.unwrap();
+        assert_eq!(1, list.len());
+        assert!(list[0].contains("/keep/"));
+        assert!(
+            of.get(format!("c/AAAA/ttl/{now}/{}", now + 5.0).into())
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn timestamp_ties_converge_regardless_of_order() {
+        // two nodes syncing objects with identical meta but different
+        // content (same byte length, so the meta strings match) must
+        // keep the same winner no matter which arrived first
+        let x = bytes::Bytes::from_static(b"hello");
+        let y = bytes::Bytes::from_static(b"world");
+
+        let one = ObjFile::create(None).await.unwrap();
+        one.put("c/AAAA/bob/1.0/0.0".into(), x.clone()).await.unwrap();
+        one.put("c/AAAA/bob/1.0/0.0".into(), y.clone()).await.unwrap();
+
+        let two = ObjFile::create(None).await.unwrap();
+        two.put("c/AAAA/bob/1.0/0.0".into(), y).await.unwrap();
+        two.put("c/AAAA/bob/1.0/0.0".into(), x).await.unwrap();
+
+        let got_one = one.get("c/AAAA/bob/1.0/0.0".into()).await.unwrap().1;
+        let got_two = two.get("c/AAAA/bob/1.0/0.0".into()).await.unwrap().1;
+        assert_eq!(got_one, got_two);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn concurrent_puts_to_one_path_stay_consistent() {
+        let td = tempfile::tempdir().unwrap();
+
+        let of = ObjFile::create(Some(td.path().into())).await.unwrap();
+
+        // hammer one app_path from many tasks at once; each writer
+        // uses a distinct fill byte so a torn result is detectable
+        let mut tasks = Vec::new();
+        for i in 0..16u8 {
+            let of = of.clone();
+            tasks.push(tokio::task::spawn(async move {
+                of.put(
+                    "c/AAAA/race/1.0/0.0".into(),
+                    bytes::Bytes::from(vec![i; 5000]),
+                )
+                .await
+                .unwrap();
+            }));
+        }
+        for task in tasks {
+            task.await.unwrap();
+        }
+
+        // the surviving data is one writer's payload, whole
+        let got = of.get("c/AAAA/race/1.0/0.0".into()).await.unwrap().1;
+        assert_eq!(5000, got.len());
+        assert!(got.iter().all(|b| *b == got[0]));
+
+        // exactly one meta/data pair remains: every losing writer's
+        // files were destroyed, none leaked
+        let mut file_count = 0;
+        let mut dir = async_walkdir::WalkDir::new(td.path());
+        use futures::StreamExt;
+        while let Some(entry) = dir.next().await {
+            if entry.unwrap().path().is_file() {
                 file_count += 1;
             }
         }
+        assert_eq!(2, file_count);
 
-        assert_eq!(1, file_count);
+        // and the winner on disk matches the winner in the index
+        drop(of);
+        let of = ObjFile::create(Some(td.path().into())).await.unwrap();
+        let reloaded =
+            of.get("c/AAAA/race/1.0/0.0".into()).await.unwrap().1;
+        assert_eq!(got, reloaded);
     }
 
     #[tokio::test(flavor = "multi_thread")]
@@ -425,6 +917,138 @@ mod test {
         assert_eq!(&b"hello"[..], &got[..]);
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    async fn verify_reports_corruption() {
+        let td = tempfile::tempdir().unwrap();
+
+        let of = ObjFile::create(Some(td.path().into())).await.unwrap();
+        // one split-layout object, one inline
+        of.put(
+            "c/AAAA/bob/1.0/0.0".into(),
+            bytes::Bytes::from(vec![b'h'; 5000]),
+        )
+        .await
+        .unwrap();
+        of.put(
+            "c/AAAA/ned/2.0/0.0".into(),
+            bytes::Bytes::from_static(b"world"),
+        )
+        .await
+        .unwrap();
+        drop(of);
+
+        // a pristine store verifies clean
+        let report = ObjFile::verify(td.path(), false).await.unwrap();
+        assert_eq!(2, report.checked);
+        assert!(report.hash_mismatches.is_empty());
+        assert!(report.orphaned.is_empty());
+
+        // corrupt the split data file and the inline file in place
+        let mut data_path = None;
+        let mut obj_path = None;
+        let mut dir = async_walkdir::WalkDir::new(td.path());
+        use futures::StreamExt;
+        while let Some(entry) = dir.next().await {
+            let entry = entry.unwrap();
+            if entry.path().is_file() {
+                let name = entry.file_name().to_string_lossy().to_string();
+                if name.starts_with("data-") {
+                    data_path = Some(entry.path());
+                } else if name.starts_with("obj-") {
+                    obj_path = Some(entry.path());
+                }
+            }
+        }
+        let data_path = data_path.unwrap();
+        tokio::fs::write(&data_path, b"garbage").await.unwrap();
+        let obj_path = obj_path.unwrap();
+        let mut raw = tokio::fs::read(&obj_path).await.unwrap();
+        raw.extend_from_slice(b"zzz");
+        tokio::fs::write(&obj_path, raw).await.unwrap();
+
+        // and drop in a data file with no matching meta
+        let orphan = data_path.parent().unwrap().join("data-orphan");
+        tokio::fs::write(&orphan, b"zzz").await.unwrap();
+
+        let report = ObjFile::verify(td.path(), false).await.unwrap();
+        assert_eq!(2, report.checked);
+        assert_eq!(2, report.hash_mismatches.len());
+        assert_eq!(vec![orphan.clone()], report.orphaned);
+        // without repair, nothing was touched
+        assert_eq!(0, report.repaired);
+        assert_eq!(0, report.bytes_reclaimed);
+        assert!(tokio::fs::try_exists(&orphan).await.unwrap());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn verify_repair_quarantines_and_reclaims() {
+        let td = tempfile::tempdir().unwrap();
+
+        let of = ObjFile::create(Some(td.path().into())).await.unwrap();
+        // one split-layout object to corrupt, one inline to keep
+        of.put(
+            "c/AAAA/bob/1.0/0.0".into(),
+            bytes::Bytes::from(vec![b'h'; 5000]),
+        )
+        .await
+        .unwrap();
+        of.put(
+            "c/AAAA/ned/2.0/0.0".into(),
+            bytes::Bytes::from_static(b"world"),
+        )
+        .await
+        .unwrap();
+        drop(of);
+
+        let mut data_path = None;
+        let mut dir = async_walkdir::WalkDir::new(td.path());
+        use futures::StreamExt;
+        while let Some(entry) = dir.next().await {
+            let entry = entry.unwrap();
+            if entry.path().is_file()
+                && entry
+                    .file_name()
+                    .to_string_lossy()
+                    .starts_with("data-")
+            {
+                data_path = Some(entry.path());
+            }
+        }
+        let data_path = data_path.unwrap();
+        tokio::fs::write(&data_path, b"garbage").await.unwrap();
+        let orphan = data_path.parent().unwrap().join("data-orphan");
+        tokio::fs::write(&orphan, b"zzz").await.unwrap();
+
+        let report = ObjFile::verify(td.path(), true).await.unwrap();
+        // one orphan deleted plus one mismatched pair quarantined
+        assert_eq!(2, report.repaired);
+        assert_eq!(3, report.bytes_reclaimed);
+        assert!(!tokio::fs::try_exists(&orphan).await.unwrap());
+        // the quarantine holds both halves of the mismatched pair
+        let mut count = 0;
+        let mut dir =
+            tokio::fs::read_dir(td.path().join("_quarantine")).await.unwrap();
+        while let Some(e) = dir.next_entry().await.unwrap() {
+            let name = e.file_name().to_string_lossy().to_string();
+            assert!(
+                name.starts_with("meta-") || name.starts_with("data-")
+            );
+            count += 1;
+        }
+        assert_eq!(2, count);
+
+        // a second pass verifies clean: the quarantine dir is ignored
+        let report = ObjFile::verify(td.path(), false).await.unwrap();
+        assert_eq!(1, report.checked);
+        assert!(report.hash_mismatches.is_empty());
+        assert!(report.orphaned.is_empty());
+
+        // and the repaired store still loads the surviving object
+        let of = ObjFile::create(Some(td.path().into())).await.unwrap();
+        let got = of.get("c/AAAA/ned/2.0/0.0".into()).await.unwrap().1;
+        assert_eq!(&b"world"[..], &got[..]);
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     async fn load() {
         let tmp = tempfile::tempdir().unwrap();
@@ -445,4 +1069,159 @@ mod test {
         let got = of2.get("c/AAAA/bob/1.0/0.0".into()).await.unwrap().1;
         assert_eq!(&b"hello"[..], &got[..]);
     }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn load_removes_orphaned_data_files() {
+        let td = tempfile::tempdir().unwrap();
+
+        let of = ObjFile::create(Some(td.path().into())).await.unwrap();
+        of.put(
+            "c/AAAA/big/1.0/0.0".into(),
+            bytes::Bytes::from(vec![b'x'; 5000]),
+        )
+        .await
+        .unwrap();
+        drop(of);
+
+        // stand in for a crash after writing data-<hash> but before
+        // meta-<hash>: a data file with no matching meta
+        let mut data_path = None;
+        let mut dir = async_walkdir::WalkDir::new(td.path());
+        use futures::StreamExt;
+        while let Some(entry) = dir.next().await {
+            let entry = entry.unwrap();
+            if entry.path().is_file()
+                && entry
+                    .file_name()
+                    .to_string_lossy()
+                    .starts_with("data-")
+            {
+                data_path = Some(entry.path());
+            }
+        }
+        let orphan =
+            data_path.unwrap().parent().unwrap().join("data-orphan");
+        tokio::fs::write(&orphan, b"zzz").await.unwrap();
+
+        // load removes the orphan and the real pair still works
+        let of = ObjFile::create(Some(td.path().into())).await.unwrap();
+        assert!(!tokio::fs::try_exists(&orphan).await.unwrap());
+        let got = of.get("c/AAAA/big/1.0/0.0".into()).await.unwrap().1;
+        assert_eq!(&vec![b'x'; 5000][..], &got[..]);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn inline_and_split_mix_survives_reload() {
+        let td = tempfile::tempdir().unwrap();
+
+        let big = vec![b'x'; 5000];
+
+        let of = ObjFile::create(Some(td.path().into())).await.unwrap();
+        of.put(
+            "c/AAAA/small/1.0/0.0".into(),
+            bytes::Bytes::from_static(b"tiny"),
+        )
+        .await
+        .unwrap();
+        of.put("c/AAAA/big/2.0/0.0".into(), bytes::Bytes::from(big.clone()))
+            .await
+            .unwrap();
+
+        let got = of.get("c/AAAA/small/1.0/0.0".into()).await.unwrap().1;
+        assert_eq!(&b"tiny"[..], &got[..]);
+        let got = of.get("c/AAAA/big/2.0/0.0".into()).await.unwrap().1;
+        assert_eq!(&big[..], &got[..]);
+        drop(of);
+
+        // both layouts load back transparently
+        let of = ObjFile::create(Some(td.path().into())).await.unwrap();
+        let got = of.get("c/AAAA/small/1.0/0.0".into()).await.unwrap().1;
+        assert_eq!(&b"tiny"[..], &got[..]);
+        let got = of.get("c/AAAA/big/2.0/0.0".into()).await.unwrap().1;
+        assert_eq!(&big[..], &got[..]);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn legacy_split_layout_still_loads() {
+        let td = tempfile::tempdir().unwrap();
+
+        // a zero threshold writes every object in the old split
+        // layout, standing in for a store from before inline support
+        let of = ObjFile::create_with_inline(Some(td.path().into()), 0)
+            .await
+            .unwrap();
+        of.put(
+            "c/AAAA/bob/1.0/0.0".into(),
+            bytes::Bytes::from_static(b"hello"),
+        )
+        .await
+        .unwrap();
+        drop(of);
+
+        let of = ObjFile::create(Some(td.path().into())).await.unwrap();
+        let got = of.get("c/AAAA/bob/1.0/0.0".into()).await.unwrap().1;
+        assert_eq!(&b"hello"[..], &got[..]);
+
+        // replacing the legacy object cleans both old files
+        of.put(
+            "c/AAAA/bob/2.0/0.0".into(),
+            bytes::Bytes::from_static(b"world"),
+        )
+        .await
+        .unwrap();
+
+        let mut obj_count = 0;
+        let mut split_count = 0;
+        let mut dir = async_walkdir::WalkDir::new(td.path());
+        use futures::StreamExt;
+        while let Some(entry) = dir.next().await {
+            let entry = entry.unwrap();
+            if entry.path().is_file() {
+                let name = entry.file_name().to_string_lossy().to_string();
+                if name.starts_with("obj-") {
+                    obj_count += 1;
+                } else if name.starts_with("meta-")
+                    || name.starts_with("data-")
+                {
+                    split_count += 1;
+                }
+            }
+        }
+        assert_eq!(1, obj_count);
+        assert_eq!(0, split_count);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn inline_load_reads_one_file_per_object() {
+        const COUNT: usize = 10_000;
+
+        let td = tempfile::tempdir().unwrap();
+
+        let of = ObjFile::create(Some(td.path().into())).await.unwrap();
+        for i in 0..COUNT {
+            of.put(
+                format!("c/AAAA/item{i}/{}.0/0.0", i + 1).into(),
+                bytes::Bytes::from_static(b"tiny"),
+            )
+            .await
+            .unwrap();
+        }
+        drop(of);
+
+        // the store holds one file per object, not a meta/data pair
+        let mut file_count = 0;
+        let mut dir = async_walkdir::WalkDir::new(td.path());
+        use futures::StreamExt;
+        while let Some(entry) = dir.next().await {
+            if entry.unwrap().path().is_file() {
+                file_count += 1;
+            }
+        }
+        assert_eq!(COUNT, file_count);
+
+        // and load still indexes every object
+        let of = ObjFile::create(Some(td.path().into())).await.unwrap();
+        let list = of.list("c/AAAA/".into(), 0.0, u32::MAX).await.unwrap();
+        assert_eq!(COUNT, list.len());
+    }
 }
@@ -0,0 +1,356 @@
+//! Tiered object store: a hot store in front of a cold store.
+
+use crate::obj::*;
+
+/// Configuration for [ObjTiered].
+#[derive(Debug, Clone)]
+pub struct ObjTieredConfig {
+    /// Objects created more than this many seconds ago are moved from
+    /// the hot store to the cold store by the background demotion
+    /// task. Default: one day.
+    pub demote_age_secs: f64,
+
+    /// How often the background demotion task scans the hot store.
+    /// Default: 60s.
+    pub demote_interval: std::time::Duration,
+}
+
+impl Default for ObjTieredConfig {
+    fn default() -> Self {
+        Self {
+            demote_age_secs: 60.0 * 60.0 * 24.0,
+            demote_interval: std::time::Duration::from_secs(60),
+        }
+    }
+}
+
+/// Tiered object store composing two inner stores, e.g. hot data on
+/// local nvme and cold data on a slower mounted volume. Puts always
+/// land in the hot store, gets fall through to the cold store, and
+/// lists merge both tiers. A background task demotes objects past
+/// [ObjTieredConfig::demote_age_secs] into the cold store.
+pub struct ObjTiered {
+    hot: DynObj,
+    cold: DynObj,
+    task: tokio::task::AbortHandle,
+}
+
+impl Drop for ObjTiered {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+impl ObjTiered {
+    /// Construct a new tiered object store over a hot and a cold
+    /// inner store.
+    pub fn create(
+        hot: DynObj,
+        cold: DynObj,
+        config: ObjTieredConfig,
+    ) -> ObjWrap {
+        let out = Arc::new_cyclic(|this: &std::sync::Weak<ObjTiered>| {
+            let this = this.clone();
+            let task = tokio::task::spawn(async move {
+                loop {
+                    tokio::time::sleep(config.demote_interval).await;
+                    if let Some(this) = this.upgrade() {
+                        if let Err(err) =
+                            this.demote(config.demote_age_secs).await
+                        {
+                            tracing::error!(?err, "obj demotion error");
+                        }
+                    } else {
+                        return;
+                    }
+                }
+            })
+            .abort_handle();
+            Self { hot, cold, task }
+        });
+
+        let out: DynObj = out;
+
+        ObjWrap::new(out)
+    }
+
+    /// Move hot objects created more than `age_secs` ago into the
+    /// cold store: copy first, delete after, so a failure mid-demotion
+    /// leaves a duplicate rather than a loss.
+    async fn demote(&self, age_secs: f64) -> Result<()> {
+        let cutoff = crate::safe_now() - age_secs;
+        loop {
+            let page = self
+                .hot
+                .list("".into(), 0.0, Some(cutoff), false, 1000)
+                .await?;
+            if page.is_empty() {
+                return Ok(());
+            }
+            for path in page {
+                let (path, data) = self.hot.get(path).await?;
+                self.cold.put(path.clone(), data).await?;
+                self.hot.rm(path).await?;
+            }
+        }
+    }
+}
+
+impl Obj for ObjTiered {
+    fn get(&self, path: Arc<str>) -> BoxFut<'_, Result<(Arc<str>, Bytes)>> {
+        Box::pin(async move {
+            match self.hot.get(path.clone()).await {
+                Ok(found) => Ok(found),
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+                    // a cold store io failure surfaces as-is, it must
+                    // not masquerade as a missing object
+                    self.cold.get(path).await
+                }
+                Err(err) => Err(err),
+            }
+        })
+    }
+
+    fn rm(&self, path: Arc<str>) -> BoxFut<'_, Result<()>> {
+        Box::pin(async move {
+            // an object may live in either tier, or both mid-demotion
+            let hot = self.hot.rm(path.clone()).await;
+            let cold = self.cold.rm(path).await;
+            if hot.is_ok() || cold.is_ok() { Ok(()) } else { hot }
+        })
+    }
+
+    fn list(
+        &self,
+        path_prefix: Arc<str>,
+        created_gt: f64,
+        created_lt: Option<f64>,
+        descending: bool,
+        limit: u32,
+    ) -> BoxFut<'_, Result<Vec<Arc<str>>>> {
+        Box::pin(async move {
+            let hot = self
+                .hot
+                .list(
+                    path_prefix.clone(),
+                    created_gt,
+                    created_lt,
+                    descending,
+                    limit,
+                )
+                .await?;
+            let cold = self
+                .cold
+                .list(path_prefix, created_gt, created_lt, descending, limit)
+                .await?;
+
+            // dedup by full path, preferring the hot copy
+            let mut seen = std::collections::HashSet::new();
+            let mut out = Vec::with_capacity(hot.len() + cold.len());
+            for path in hot.into_iter().chain(cold) {
+                if seen.insert(path.clone()) {
+                    out.push(path);
+                }
+            }
+
+            let created =
+                |path: &Arc<str>| ObjMeta(path.clone()).created_secs();
+            out.sort_by(|a, b| {
+                if descending {
+                    created(b).total_cmp(&created(a))
+                } else {
+                    created(a).total_cmp(&created(b))
+                }
+            });
+            out.truncate(limit as usize);
+
+            Ok(out)
+        })
+    }
+
+    fn put(&self, path: Arc<str>, obj: Bytes) -> BoxFut<'_, Result<()>> {
+        self.hot.put(path, obj)
+    }
+
+    fn prune(&self) -> BoxFut<'_, Result<()>> {
+        Box::pin(async move {
+            self.hot.prune().await?;
+            self.cold.prune().await
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    async fn file_store() -> DynObj {
+        crate::obj::obj_file::ObjFile::create(None)
+            .await
+            .unwrap()
+            .into_dyn()
+    }
+
+    fn meta(name: &str, created: f64) -> ObjMeta {
+        ObjMeta::new(ObjMeta::SYS_CTX, "AAAA", name, created, 0.0, 1.0)
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn tiered_read_through() {
+        let hot = file_store().await;
+        let cold = file_store().await;
+
+        // an object only the cold store holds
+        cold.put(meta("old", 5.0).0, Bytes::from_static(b"cold"))
+            .await
+            .unwrap();
+
+        let o = ObjTiered::create(
+            hot.clone(),
+            cold.clone(),
+            Default::default(),
+        );
+
+        o.put(meta("new", 6.0), Bytes::from_static(b"hot"))
+            .await
+            .unwrap();
+
+        // hot hit and cold fall-through both resolve
+        let got = o.get(meta("new", 6.0)).await.unwrap().1;
+        assert_eq!(b"hot", got.as_ref());
+        let got = o.get(meta("old", 5.0)).await.unwrap().1;
+        assert_eq!(b"cold", got.as_ref());
+
+        // puts never touch the cold store
+        assert!(hot.get(meta("new", 6.0).0).await.is_ok());
+        assert!(cold.get(meta("new", 6.0).0).await.is_err());
+
+        // a miss in both tiers is still not found
+        let err = o.get(meta("nope", 7.0)).await.unwrap_err();
+        assert_eq!(std::io::ErrorKind::NotFound, err.kind());
+    }
+
+    /// Cold store stand-in whose reads always fail with a non-NotFound
+    /// io error, like an unmounted volume.
+    struct BrokenObj;
+
+    impl Obj for BrokenObj {
+        fn get(
+            &self,
+            _path: Arc<str>,
+        ) -> BoxFut<'_, Result<(Arc<str>, Bytes)>> {
+            Box::pin(async move { Err(Error::other("volume gone")) })
+        }
+
+        fn rm(&self, _path: Arc<str>) -> BoxFut<'_, Result<()>> {
+            Box::pin(async move { Err(Error::other("volume gone")) })
+        }
+
+        fn list(
+            &self,
+            _path_prefix: Arc<str>,
+            _created_gt: f64,
+            _created_lt: Option<f64>,
+            _descending: bool,
+            _limit: u32,
+        ) -> BoxFut<'_, Result<Vec<Arc<str>>>> {
+            Box::pin(async move { Err(Error::other("volume gone")) })
+        }
+
+        fn put(&self, _path: Arc<str>, _obj: Bytes) -> BoxFut<'_, Result<()>> {
+            Box::pin(async move { Err(Error::other("volume gone")) })
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn tiered_cold_io_error_is_not_notfound() {
+        let o = ObjTiered::create(
+            file_store().await,
+            Arc::new(BrokenObj),
+            Default::default(),
+        );
+
+        // a hot miss falling through to a broken cold store surfaces
+        // the io error, distinct from a genuine missing object
+        let err = o.get(meta("nope", 5.0)).await.unwrap_err();
+        assert_ne!(std::io::ErrorKind::NotFound, err.kind());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn tiered_merged_listing() {
+        let hot = file_store().await;
+        let cold = file_store().await;
+
+        cold.put(meta("a", 5.0).0, Bytes::from_static(b"x"))
+            .await
+            .unwrap();
+        // the same path in both tiers, as mid-demotion would leave it
+        cold.put(meta("b", 6.0).0, Bytes::from_static(b"x"))
+            .await
+            .unwrap();
+        hot.put(meta("b", 6.0).0, Bytes::from_static(b"x"))
+            .await
+            .unwrap();
+        hot.put(meta("c", 7.0).0, Bytes::from_static(b"x"))
+            .await
+            .unwrap();
+
+        let o = ObjTiered::create(hot, cold, Default::default());
+
+        let list = o.list("c/AAAA/", 0.0, u32::MAX).await.unwrap();
+        assert_eq!(
+            vec!["a", "b", "c"],
+            list.iter().map(|m| m.app_path()).collect::<Vec<_>>(),
+        );
+
+        let list = o
+            .list_range("c/AAAA/", 0.0, None, true, u32::MAX)
+            .await
+            .unwrap();
+        assert_eq!(
+            vec!["c", "b", "a"],
+            list.iter().map(|m| m.app_path()).collect::<Vec<_>>(),
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn tiered_demotion() {
+        let hot = file_store().await;
+        let cold = file_store().await;
+
+        let o = ObjTiered::create(
+            hot.clone(),
+            cold.clone(),
+            ObjTieredConfig {
+                demote_age_secs: 60.0,
+                demote_interval: std::time::Duration::from_millis(10),
+            },
+        );
+
+        // created far in the past: eligible for demotion immediately
+        o.put(meta("old", 5.0), Bytes::from_static(b"x"))
+            .await
+            .unwrap();
+        // created now: must stay hot
+        let now = crate::safe_now();
+        o.put(meta("new", now), Bytes::from_static(b"x"))
+            .await
+            .unwrap();
+
+        let mut demoted = false;
+        for _ in 0..200 {
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+            if cold.get(meta("old", 5.0).0).await.is_ok() {
+                demoted = true;
+                break;
+            }
+        }
+        assert!(demoted);
+        assert!(hot.get(meta("old", 5.0).0).await.is_err());
+        assert!(hot.get(meta("new", now).0).await.is_ok());
+
+        // the demoted object still reads and lists through the store
+        assert!(o.get(meta("old", 5.0)).await.is_ok());
+        assert_eq!(2, o.list("c/AAAA/", 0.0, u32::MAX).await.unwrap().len());
+    }
+}
@@ -0,0 +1,240 @@
+//! Context secrets, encrypted at rest with a server master key.
+//!
+//! Secrets are stored in the object store under a reserved sys prefix,
+//! encrypted with a master key provided via the `VM_MASTER_KEY` environment
+//! variable (or [secret_global_set_master_key]). They are only ever
+//! decrypted when requested from within the javascript runtime, so
+//! plaintext values never appear in admin api output or logs, and
+//! rotating a secret does not require re-putting the context code.
+
+use crate::*;
+use bytes::Bytes;
+
+static MASTER_KEY: std::sync::OnceLock<[u8; 32]> = std::sync::OnceLock::new();
+
+/// Set the master key used to encrypt context secrets.
+/// (Default: read from the `VM_MASTER_KEY` environment variable).
+pub fn secret_global_set_master_key(key: &str) -> bool {
+    MASTER_KEY.set(derive_master_key(key)).is_ok()
+}
+
+fn derive_master_key(raw: &str) -> [u8; 32] {
+    use sha2::{Digest, Sha256};
+    let mut hasher = Sha256::new();
+    hasher.update(b"vm-secret-master-key");
+    hasher.update(raw.as_bytes());
+    hasher.finalize().into()
+}
+
+/// Derive a deterministic 64-byte secondary key from a root secret
+/// using HKDF-SHA-512. The same `secret` / `info` pair always produces
+/// the same key, and different `info` values produce independent keys,
+/// so a context can derive per-purpose keys from a single stored
+/// secret.
+pub fn derive_key(secret: &[u8], info: &[u8]) -> [u8; 64] {
+    use hkdf::Hkdf;
+    use sha2::Sha512;
+    let hk = Hkdf::<Sha512>::new(None, secret);
+    let mut okm = [0_u8; 64];
+    hk.expand(info, &mut okm)
+        .expect("64 bytes is a valid hkdf-sha512 output length");
+    okm
+}
+
+fn master_key() -> Result<[u8; 32]> {
+    if let Some(key) = MASTER_KEY.get() {
+        return Ok(*key);
+    }
+    match std::env::var("VM_MASTER_KEY") {
+        Ok(raw) if !raw.is_empty() => {
+            Ok(*MASTER_KEY.get_or_init(|| derive_master_key(&raw)))
+        }
+        _ => Err(Error::other(
+            "secrets require a master key: set VM_MASTER_KEY",
+        )),
+    }
+}
+
+/// The aad binds a ciphertext to a single ctx / name pair, so an
+/// encrypted value cannot be copied between secrets.
+fn aad(ctx: &str, name: &str) -> String {
+    format!("{ctx}/{name}")
+}
+
+/// Encrypt a secret value for storage.
+fn encrypt(ctx: &str, name: &str, value: &[u8]) -> Result<Bytes> {
+    use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+    use chacha20poly1305::XChaCha20Poly1305;
+
+    let key = master_key()?;
+    let cipher = XChaCha20Poly1305::new((&key).into());
+
+    let mut nonce = [0_u8; 24];
+    use rand::Rng;
+    rand::rng().fill(&mut nonce);
+
+    let aad = aad(ctx, name);
+    let enc = cipher
+        .encrypt(
+            (&nonce).into(),
+            Payload {
+                msg: value,
+                aad: aad.as_bytes(),
+            },
+        )
+        .map_err(|_| Error::other("secret encrypt failed"))?;
+
+    let mut out = Vec::with_capacity(nonce.len() + enc.len());
+    out.extend_from_slice(&nonce);
+    out.extend_from_slice(&enc);
+    Ok(out.into())
+}
+
+/// Decrypt a stored secret value.
+fn decrypt(ctx: &str, name: &str, data: &[u8]) -> Result<Bytes> {
+    use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+    use chacha20poly1305::XChaCha20Poly1305;
+
+    if data.len() < 24 {
+        return Err(Error::other("secret decrypt failed"));
+    }
+    let (nonce, enc) = data.split_at(24);
+    let nonce: &[u8; 24] = nonce.try_into().unwrap();
+
+    let key = master_key()?;
+    let cipher = XChaCha20Poly1305::new((&key).into());
+
+    let aad = aad(ctx, name);
+    let out = cipher
+        .decrypt(
+            nonce.into(),
+            Payload {
+                msg: enc,
+                aad: aad.as_bytes(),
+            },
+        )
+        .map_err(|_| Error::other("secret decrypt failed"))?;
+
+    Ok(out.into())
+}
+
+fn meta_prefix(ctx: &str, name: &str) -> String {
+    format!("{}/{}/{}/", crate::obj::ObjMeta::SYS_CTX_SECRET, ctx, name)
+}
+
+/// Store (or rotate) an encrypted secret in the object store.
+pub(crate) async fn put(
+    obj: &crate::obj::ObjWrap,
+    ctx: &str,
+    name: &str,
+    value: &[u8],
+) -> Result<()> {
+    safe_str(name)?;
+    let enc = encrypt(ctx, name, value)?;
+    let meta = crate::obj::ObjMeta::new(
+        crate::obj::ObjMeta::SYS_CTX_SECRET,
+        ctx,
+        name,
+        safe_now(),
+        0.0,
+        enc.len() as f64,
+    );
+    obj.put(meta, enc).await
+}
+
+/// Fetch and decrypt a secret from the object store.
+pub(crate) async fn get(
+    obj: &crate::obj::ObjWrap,
+    ctx: &str,
+    name: &str,
+) -> Result<Bytes> {
+    let (_, data) = obj.get_single(&meta_prefix(ctx, name)).await?;
+    decrypt(ctx, name, &data)
+}
+
+/// Delete a secret from the object store.
+pub(crate) async fn rm(
+    obj: &crate::obj::ObjWrap,
+    ctx: &str,
+    name: &str,
+) -> Result<()> {
+    let (meta, _) = obj.get_single(&meta_prefix(ctx, name)).await?;
+    obj.rm(meta).await
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn init_master_key() {
+        let _ = secret_global_set_master_key("test-master-key");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn secret_roundtrip() {
+        init_master_key();
+
+        let o = crate::obj::obj_file::ObjFile::create(None).await.unwrap();
+
+        put(&o, "AAAA", "apikey", b"one").await.unwrap();
+        assert_eq!(b"one", get(&o, "AAAA", "apikey").await.unwrap().as_ref());
+
+        // rotate in place
+        put(&o, "AAAA", "apikey", b"two").await.unwrap();
+        assert_eq!(b"two", get(&o, "AAAA", "apikey").await.unwrap().as_ref());
+
+        rm(&o, "AAAA", "apikey").await.unwrap();
+        assert!(get(&o, "AAAA", "apikey").await.is_err());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn secret_not_in_ctx_obj_list_and_not_plaintext() {
+        init_master_key();
+
+        let o = crate::obj::obj_file::ObjFile::create(None).await.unwrap();
+
+        put(&o, "AAAA", "apikey", b"super-sensitive").await.unwrap();
+
+        // not visible via the context object listing prefix
+        let list = o
+            .list(
+                &format!("{}/AAAA/", crate::obj::ObjMeta::SYS_CTX),
+                0.0,
+                u32::MAX,
+            )
+            .await
+            .unwrap();
+        assert!(list.is_empty());
+
+        // and the stored bytes are not the plaintext
+        let (_, data) = o.get_single(&meta_prefix("AAAA", "apikey")).await.unwrap();
+        assert!(!data
+            .windows(b"super-sensitive".len())
+            .any(|w| w == b"super-sensitive"));
+    }
+
+    #[test]
+    fn derive_key_deterministic() {
+        let a = derive_key(b"root-seed", b"signing");
+        let b = derive_key(b"root-seed", b"signing");
+        assert_eq!(a, b);
+
+        // different info: independent key
+        let c = derive_key(b"root-seed", b"encryption");
+        assert_ne!(a, c);
+
+        // different secret: independent key
+        let d = derive_key(b"other-seed", b"signing");
+        assert_ne!(a, d);
+    }
+
+    #[test]
+    fn secret_aad_binding() {
+        init_master_key();
+
+        let enc = encrypt("AAAA", "apikey", b"hello").unwrap();
+        assert_eq!(b"hello", decrypt("AAAA", "apikey", &enc).unwrap().as_ref());
+        assert!(decrypt("AAAA", "other", &enc).is_err());
+        assert!(decrypt("BBBB", "apikey", &enc).is_err());
+    }
+}
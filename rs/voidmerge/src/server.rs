@@ -1,13 +1,17 @@
 //! A server manages multiple contexts.
 
 use crate::*;
-use std::collections::HashMap;
-use std::sync::{Arc, Mutex};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, RwLock};
 
 fn p_no(s: &Arc<str>) -> bool {
     s.is_empty()
 }
 
+fn p_no_map(m: &Arc<std::collections::BTreeMap<Arc<str>, Arc<str>>>) -> bool {
+    m.is_empty()
+}
+
 fn timeout_secs() -> f64 {
     10.0
 }
@@ -20,6 +24,26 @@ fn is_false(b: &bool) -> bool {
     !b
 }
 
+fn is_true(b: &bool) -> bool {
+    *b
+}
+
+fn validate() -> bool {
+    true
+}
+
+static CONFIG_HISTORY: std::sync::OnceLock<u32> = std::sync::OnceLock::new();
+
+/// Set the count of historical ctx config versions retained for
+/// rollback. (Default: 10).
+pub fn server_global_set_config_history(count: u32) -> bool {
+    CONFIG_HISTORY.set(count).is_ok()
+}
+
+fn server_global_get_config_history() -> u32 {
+    *CONFIG_HISTORY.get_or_init(|| 10)
+}
+
 /// System setup information.
 #[derive(Default, Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct SysSetup {
@@ -51,6 +75,25 @@ pub struct CtxSetup {
     /// Max memory allowed for function invocations.
     #[serde(rename = "h", default = "max_heap_bytes")]
     pub max_heap_bytes: usize,
+
+    /// Max request body bytes accepted for function invocations in
+    /// this context. Larger bodies are rejected with 413 before
+    /// dispatch. None applies only the global server body limit.
+    #[serde(rename = "r", default, skip_serializing_if = "Option::is_none")]
+    pub max_fn_request_bytes: Option<u64>,
+
+    /// Max total byte length of config code plus module sources
+    /// accepted for this context. Oversized configs are rejected with
+    /// 413 before they are validated or stored. None applies the
+    /// 1 MiB default.
+    #[serde(rename = "b", default, skip_serializing_if = "Option::is_none")]
+    pub max_code_bytes: Option<u64>,
+
+    /// Count of js threads to pre-warm for this context when it is
+    /// set up, trading startup memory for predictable first-request
+    /// latency on known-hot contexts. None performs no warmup.
+    #[serde(rename = "w", default, skip_serializing_if = "Option::is_none")]
+    pub warm_threads: Option<u32>,
 }
 
 impl Default for CtxSetup {
@@ -61,6 +104,9 @@ impl Default for CtxSetup {
             ctx_admin: Default::default(),
             timeout_secs: timeout_secs(),
             max_heap_bytes: max_heap_bytes(),
+            max_fn_request_bytes: None,
+            max_code_bytes: None,
+            warm_threads: None,
         }
     }
 }
@@ -81,7 +127,7 @@ impl CtxSetup {
 }
 
 /// Context config information.
-#[derive(Default, Clone, serde::Serialize, serde::Deserialize)]
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
 pub struct CtxConfig {
     /// The context identifier.
     #[serde(rename = "c", default, skip_serializing_if = "p_no")]
@@ -95,6 +141,24 @@ pub struct CtxConfig {
     #[serde(rename = "l", default, skip_serializing_if = "p_no")]
     pub code: Arc<str>,
 
+    /// Javascript es module sources for the context, keyed by module
+    /// specifier. When non-empty this takes precedence over
+    /// [CtxConfig::code], and [CtxConfig::code_entry] names the entry
+    /// module.
+    #[serde(rename = "m", default, skip_serializing_if = "p_no_map")]
+    pub code_modules: Arc<std::collections::BTreeMap<Arc<str>, Arc<str>>>,
+
+    /// Entry module specifier within [CtxConfig::code_modules]. The
+    /// entry module must export the `vm` function.
+    #[serde(rename = "n", default, skip_serializing_if = "p_no")]
+    pub code_entry: Arc<str>,
+
+    /// Wasm module for the context, an alternative to javascript code.
+    /// Only dispatched when the server's executor chain includes a
+    /// wasm executor; see the `wasm` feature.
+    #[serde(rename = "w", default, skip_serializing_if = "Option::is_none")]
+    pub wasm: Option<bytes::Bytes>,
+
     /// Javascript code env metadata for the context.
     #[serde(
         rename = "e",
@@ -102,6 +166,51 @@ pub struct CtxConfig {
         skip_serializing_if = "serde_json::Value::is_null"
     )]
     pub code_env: Arc<serde_json::Value>,
+
+    /// Optional allowlist of function request paths (glob patterns,
+    /// where '*' matches any run of characters). If this list is
+    /// non-empty, function requests whose path does not match any
+    /// pattern are rejected with not found before dispatching to JS.
+    #[serde(rename = "f", default, skip_serializing_if = "Vec::is_empty")]
+    pub fn_path_allow: Vec<Arc<str>>,
+
+    /// Optional allowlist of inbound request header names forwarded
+    /// to function invocations. If this list is non-empty, only the
+    /// named headers (case-insensitive) are forwarded.
+    #[serde(rename = "a", default, skip_serializing_if = "Vec::is_empty")]
+    pub hdr_allow: Vec<Arc<str>>,
+
+    /// Additional inbound request header names (case-insensitive)
+    /// stripped before forwarding to function invocations.
+    /// `cookie` is always stripped.
+    #[serde(rename = "b", default, skip_serializing_if = "Vec::is_empty")]
+    pub hdr_deny: Vec<Arc<str>>,
+
+    /// Compile and smoke-test the code before committing this config,
+    /// rejecting the put with the compile or runtime error while the
+    /// previous code stays in place. Only applies to javascript
+    /// configs; wasm configs and configs without code are never
+    /// validated.
+    #[serde(rename = "v", default = "validate", skip_serializing_if = "is_true")]
+    pub validate: bool,
+}
+
+impl Default for CtxConfig {
+    fn default() -> Self {
+        Self {
+            ctx: Default::default(),
+            ctx_admin: Default::default(),
+            code: Default::default(),
+            code_modules: Default::default(),
+            code_entry: Default::default(),
+            wasm: None,
+            code_env: Default::default(),
+            fn_path_allow: Default::default(),
+            hdr_allow: Default::default(),
+            hdr_deny: Default::default(),
+            validate: validate(),
+        }
+    }
 }
 
 impl std::fmt::Debug for CtxConfig {
@@ -110,7 +219,17 @@ impl std::fmt::Debug for CtxConfig {
             .field("ctx", &self.ctx)
             .field("ctx_admin", &self.ctx_admin)
             .field("code_bytes", &self.code.len())
+            .field("code_module_count", &self.code_modules.len())
+            .field("code_entry", &self.code_entry)
+            .field(
+                "wasm_bytes",
+                &self.wasm.as_ref().map(|w| w.len()).unwrap_or(0),
+            )
             .field("code_env", &self.code_env)
+            .field("fn_path_allow", &self.fn_path_allow)
+            .field("hdr_allow", &self.hdr_allow)
+            .field("hdr_deny", &self.hdr_deny)
+            .field("validate", &self.validate)
             .finish()
     }
 }
@@ -121,16 +240,296 @@ impl CtxConfig {
         for token in self.ctx_admin.iter() {
             safe_str(token)?;
         }
+        if !self.code_modules.is_empty()
+            && !self.code_modules.contains_key(&self.code_entry)
+        {
+            return Err(Error::other(format!(
+                "code entry module not found: {}",
+                self.code_entry,
+            )));
+        }
         Ok(())
     }
 }
 
+/// Partial update document for [Server::ctx_put], merging onto the
+/// stored ([CtxSetup], [CtxConfig]) pair in a single call. `None`
+/// fields are left unchanged and an empty list clears the stored
+/// list, so e.g. a ctxadmin can rotate its own token list without
+/// re-sending the code.
+#[derive(Default, Clone, serde::Serialize, serde::Deserialize)]
+pub struct CtxPut {
+    /// The context identifier.
+    #[serde(rename = "c", default, skip_serializing_if = "p_no")]
+    pub ctx: Arc<str>,
+
+    /// If this boolean is true, other properties will be ignored,
+    /// and the context will be deleted (sysadmin only). The stored
+    /// setup, config, and config version history are removed; user
+    /// objects under the context are not touched.
+    #[serde(rename = "d", default, skip_serializing_if = "is_false")]
+    pub delete: bool,
+
+    /// Replacement context admin token list, applied to the
+    /// config-side list a ctxadmin may already manage.
+    #[serde(rename = "x", default, skip_serializing_if = "Option::is_none")]
+    pub ctx_admin: Option<Vec<Arc<str>>>,
+
+    /// Timeout for function invocations (sysadmin only).
+    #[serde(rename = "t", default, skip_serializing_if = "Option::is_none")]
+    pub timeout_secs: Option<f64>,
+
+    /// Max memory allowed for function invocations (sysadmin only).
+    #[serde(rename = "h", default, skip_serializing_if = "Option::is_none")]
+    pub max_heap_bytes: Option<usize>,
+
+    /// Javascript code for the context.
+    #[serde(rename = "l", default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<Arc<str>>,
+
+    /// Javascript code env metadata for the context.
+    #[serde(rename = "e", default, skip_serializing_if = "Option::is_none")]
+    pub code_env: Option<Arc<serde_json::Value>>,
+}
+
+impl std::fmt::Debug for CtxPut {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CtxPut")
+            .field("ctx", &self.ctx)
+            .field("delete", &self.delete)
+            .field("ctx_admin", &self.ctx_admin)
+            .field("timeout_secs", &self.timeout_secs)
+            .field("max_heap_bytes", &self.max_heap_bytes)
+            .field("code_bytes", &self.code.as_ref().map(|c| c.len()))
+            .field("code_env", &self.code_env)
+            .finish()
+    }
+}
+
+impl CtxPut {
+    fn touches_setup(&self) -> bool {
+        self.timeout_secs.is_some() || self.max_heap_bytes.is_some()
+    }
+
+    fn touches_config(&self) -> bool {
+        self.ctx_admin.is_some()
+            || self.code.is_some()
+            || self.code_env.is_some()
+    }
+
+    fn merge_setup(&self, setup: &mut CtxSetup) {
+        if let Some(timeout_secs) = self.timeout_secs {
+            setup.timeout_secs = timeout_secs;
+        }
+        if let Some(max_heap_bytes) = self.max_heap_bytes {
+            setup.max_heap_bytes = max_heap_bytes;
+        }
+    }
+
+    fn merge_config(&self, config: &mut CtxConfig) {
+        if let Some(ctx_admin) = &self.ctx_admin {
+            config.ctx_admin = ctx_admin.clone();
+        }
+        if let Some(code) = &self.code {
+            config.code = code.clone();
+        }
+        if let Some(code_env) = &self.code_env {
+            config.code_env = code_env.clone();
+        }
+    }
+}
+
+/// Token rotation document for [Server::ctx_tokens]: `add` tokens
+/// join the config-side admin list, then `remove` tokens are dropped
+/// from both the setup- and config-side lists.
+#[derive(Default, Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct CtxTokens {
+    /// Tokens to add. Tokens already present on either list are left
+    /// where they are.
+    #[serde(rename = "a", default, skip_serializing_if = "Vec::is_empty")]
+    pub add: Vec<Arc<str>>,
+
+    /// Tokens to remove, applied after `add`.
+    #[serde(rename = "r", default, skip_serializing_if = "Vec::is_empty")]
+    pub remove: Vec<Arc<str>>,
+}
+
+/// Summary information about a single context as returned
+/// by [Server::ctx_list]. Admin tokens themselves are never
+/// included, only their counts.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct CtxListEntry {
+    /// The context identifier.
+    #[serde(rename = "c")]
+    pub ctx: Arc<str>,
+
+    /// Count of configured context admin tokens.
+    #[serde(rename = "a")]
+    pub ctx_admin_count: usize,
+
+    /// Timeout for function invocations.
+    #[serde(rename = "t")]
+    pub timeout_secs: f64,
+
+    /// Max memory allowed for function invocations.
+    #[serde(rename = "h")]
+    pub max_heap_bytes: usize,
+}
+
+/// Summary of one stored historical ctx config version, as returned
+/// by [Server::ctx_config_versions].
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct CtxConfigVersion {
+    /// The version number.
+    #[serde(rename = "v")]
+    pub version: u64,
+
+    /// Seconds since unix epoch when the version was stored.
+    #[serde(rename = "t")]
+    pub created_secs: f64,
+
+    /// Base64url sha256 of the version's javascript code.
+    #[serde(rename = "h")]
+    pub code_hash: String,
+
+    /// Byte length of the stored encoded config.
+    #[serde(rename = "s")]
+    pub size_bytes: u64,
+}
+
+/// A single object that failed revalidation, as returned
+/// by [Server::ctx_revalidate].
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct RevalidateFailure {
+    /// The failing object meta path.
+    #[serde(rename = "m")]
+    pub meta: crate::obj::ObjMeta,
+
+    /// The error produced by the context's ObjCheck handler.
+    #[serde(rename = "e")]
+    pub error: String,
+}
+
+/// Report returned by [Server::ctx_revalidate].
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct RevalidateReport {
+    /// Count of objects checked in this pass.
+    #[serde(rename = "k")]
+    pub checked: u64,
+
+    /// Objects that failed the check.
+    #[serde(rename = "f", default, skip_serializing_if = "Vec::is_empty")]
+    pub failures: Vec<RevalidateFailure>,
+
+    /// Continuation token. Set when a full page was processed and more
+    /// objects may remain; pass it back as `created_gt` to resume.
+    #[serde(rename = "n", default, skip_serializing_if = "Option::is_none")]
+    pub continuation: Option<f64>,
+}
+
+fn query_limit() -> u32 {
+    1000
+}
+
+fn query_data_bytes() -> u64 {
+    1024 * 1024
+}
+
+/// Query parameters accepted by [Server::obj_query].
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ObjQuery {
+    /// App path prefixes to match. Results merge every prefix in one
+    /// pass, dropping duplicates where prefixes overlap.
+    #[serde(rename = "p", default, skip_serializing_if = "Vec::is_empty")]
+    pub prefixes: Vec<String>,
+
+    /// Filter to objects with created_secs larger than this.
+    #[serde(rename = "g", default)]
+    pub created_gt: f64,
+
+    /// Filter to objects with created_secs smaller than this.
+    #[serde(rename = "l", default, skip_serializing_if = "Option::is_none")]
+    pub created_lt: Option<f64>,
+
+    /// Max entries returned, clamped to 1000.
+    #[serde(rename = "n", default = "query_limit")]
+    pub limit: u32,
+
+    /// Also fetch object data, subject to `max_data_bytes`.
+    #[serde(rename = "r", default, skip_serializing_if = "is_false")]
+    pub return_data: bool,
+
+    /// Total data byte budget when `return_data` is set. Entries past
+    /// the first object that does not fit come back meta-only and the
+    /// response is marked truncated.
+    #[serde(rename = "b", default = "query_data_bytes")]
+    pub max_data_bytes: u64,
+}
+
+impl Default for ObjQuery {
+    fn default() -> Self {
+        Self {
+            prefixes: Vec::new(),
+            created_gt: 0.0,
+            created_lt: None,
+            limit: query_limit(),
+            return_data: false,
+            max_data_bytes: query_data_bytes(),
+        }
+    }
+}
+
+/// A single [Server::obj_query] result.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ObjQueryEntry {
+    /// The object meta path.
+    #[serde(rename = "m")]
+    pub meta: crate::obj::ObjMeta,
+
+    /// The object data, when requested and within the byte budget.
+    #[serde(rename = "d", default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<bytes::Bytes>,
+}
+
+/// Response returned by [Server::obj_query].
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ObjQueryResponse {
+    /// Matched entries, merged across prefixes oldest first.
+    #[serde(rename = "e", default, skip_serializing_if = "Vec::is_empty")]
+    pub entries: Vec<ObjQueryEntry>,
+
+    /// True when `return_data` was set but the data byte budget ran
+    /// out; entries from the cutoff on are meta-only.
+    #[serde(rename = "t", default, skip_serializing_if = "is_false")]
+    pub truncated: bool,
+}
+
+/// Normalize a hostname for domain routing: trimmed, lowercased, any
+/// `:port` suffix stripped. Bracketed ipv6 literals are not handled;
+/// domain routing is for dns names.
+fn normalize_host(host: &str) -> String {
+    let host = host.trim();
+    let host = host.split_once(':').map(|(h, _)| h).unwrap_or(host);
+    host.to_lowercase()
+}
+
 /// A server manages multiple contexts.
 pub struct Server {
     runtime: RuntimeHandle,
-    sys_setup: Mutex<SysSetup>,
-    ctx_setup: Mutex<HashMap<Arc<str>, (CtxSetup, CtxConfig)>>,
-    ctx_map: Mutex<HashMap<Arc<str>, Arc<crate::ctx::Ctx>>>,
+    // read-mostly: lookups happen on every request, writes only on
+    // admin configuration changes
+    sys_setup: RwLock<SysSetup>,
+    ctx_setup: RwLock<HashMap<Arc<str>, (CtxSetup, CtxConfig)>>,
+    ctx_map: RwLock<HashMap<Arc<str>, Arc<crate::ctx::Ctx>>>,
+    // seed for signed object urls, persisted in the object store so
+    // signatures survive restarts
+    url_sign_seed: bytes::Bytes,
+    // per source ip request rate limit applied by the http server,
+    // stored as f64 bits; zero disables the limit
+    rate_limit_rps: std::sync::atomic::AtomicU64,
+    // hostname -> ctx map for host-based routing, persisted in the
+    // object store alongside the sys setup
+    domains: RwLock<HashMap<Arc<str>, Arc<str>>>,
 }
 
 impl Server {
@@ -140,11 +539,18 @@ impl Server {
 
         let ctx_setup = runtime.runtime().obj()?.list_ctx_all().await?;
 
+        let url_sign_seed = runtime.runtime().obj()?.url_sign_seed().await?;
+
+        let domains = runtime.runtime().obj()?.get_domains().await?;
+
         let this = Self {
             runtime,
-            sys_setup: Mutex::new(sys_setup),
-            ctx_setup: Mutex::new(ctx_setup.clone()),
-            ctx_map: Mutex::new(HashMap::new()),
+            sys_setup: RwLock::new(sys_setup),
+            ctx_setup: RwLock::new(ctx_setup.clone()),
+            ctx_map: RwLock::new(HashMap::new()),
+            url_sign_seed,
+            rate_limit_rps: std::sync::atomic::AtomicU64::new(0),
+            domains: RwLock::new(domains),
         };
 
         for (ctx, (setup, config)) in ctx_setup {
@@ -160,6 +566,7 @@ impl Server {
         setup: CtxSetup,
         config: CtxConfig,
     ) -> Result<()> {
+        let warm_threads = setup.warm_threads.unwrap_or(0);
         let sub = crate::ctx::Ctx::new(
             ctx.clone(),
             setup,
@@ -167,24 +574,120 @@ impl Server {
             self.runtime.runtime(),
         )
         .await?;
-        self.ctx_map.lock().unwrap().insert(ctx, sub);
+        self.ctx_map.write().unwrap().insert(ctx.clone(), sub);
+        if warm_threads > 0 {
+            self.warm_context(ctx, warm_threads).await?;
+        }
+        Ok(())
+    }
+
+    /// Pre-create `count` parked js threads ready to serve this
+    /// context, trading startup memory for predictable first-request
+    /// latency. Called automatically for contexts configured with
+    /// [CtxSetup::warm_threads].
+    pub async fn warm_context(&self, ctx: Arc<str>, count: u32) -> Result<()> {
+        let c = match self.ctx_map.read().unwrap().get(&ctx) {
+            None => {
+                return Err(Error::not_found(format!(
+                    "invalid context: {ctx}"
+                )));
+            }
+            Some(c) => c.clone(),
+        };
+        c.warm(count as usize).await
+    }
+
+    /// Dry-run a pending config's code on a js thread before it is
+    /// committed: syntax errors, unresolvable imports, and a missing
+    /// or uncallable `vm` export all fail here. Wasm configs and
+    /// configs without code are not validated. Timeouts count as
+    /// failure.
+    async fn validate_config(&self, config: &CtxConfig) -> Result<()> {
+        if config.wasm.is_some()
+            || (config.code.is_empty() && config.code_modules.is_empty())
+        {
+            return Ok(());
+        }
+        let setup = self
+            .ctx_setup
+            .read()
+            .unwrap()
+            .get(&config.ctx)
+            .map(|r| r.0.clone())
+            .unwrap_or_default();
+        let js_setup = crate::js::JsSetup {
+            runtime: self.runtime.runtime(),
+            ctx: config.ctx.clone(),
+            timeout: std::time::Duration::from_secs_f64(setup.timeout_secs),
+            heap_size: setup.max_heap_bytes,
+            max_code_bytes: setup
+                .max_code_bytes
+                .map(|b| b as usize)
+                .unwrap_or(crate::js::JsSetup::DEF_MAX_CODE_BYTES),
+            code: config.code.clone(),
+            modules: config.code_modules.clone(),
+            entry: config.code_entry.clone(),
+            wasm: None,
+            env: config.code_env.clone(),
+        };
+        self.runtime
+            .runtime()
+            .js()?
+            .exec(js_setup, crate::js::JsRequest::Ping)
+            .await
+            .map_err(Error::invalid)?;
         Ok(())
     }
 
     fn get_sys_setup(&self) -> SysSetup {
-        self.sys_setup.lock().unwrap().clone()
+        self.sys_setup.read().unwrap().clone()
     }
 
     fn get_ctx_setup(&self, ctx: &str) -> Result<(CtxSetup, CtxConfig)> {
         self.ctx_setup
-            .lock()
+            .read()
             .unwrap()
             .get(ctx)
             .cloned()
             .ok_or_else(|| Error::not_found(format!("no context: {ctx}")))
     }
 
-    fn check_sysadmin(&self, token: &Arc<str>) -> Result<()> {
+    /// Put the server into (or out of) read-only mode. While set,
+    /// mutating routes fail with `PermissionDenied`; reads and `GET`
+    /// function requests continue to be served. The flag lives on the
+    /// runtime so mutating js ops honor it too: a `GET` function
+    /// cannot write through `objPut` while the server is read-only.
+    pub fn set_read_only(&self, read_only: bool) {
+        self.runtime.set_read_only(read_only);
+    }
+
+    /// Set the per source ip request rate limit, in requests per
+    /// second, applied by the http server. Zero (the default) disables
+    /// the limit.
+    pub fn set_rate_limit_rps(&self, rps: f64) {
+        self.rate_limit_rps.store(
+            rps.max(0.0).to_bits(),
+            std::sync::atomic::Ordering::Relaxed,
+        );
+    }
+
+    /// Get the per source ip request rate limit, if one is set.
+    pub fn rate_limit_rps(&self) -> Option<f64> {
+        let rps = f64::from_bits(
+            self.rate_limit_rps
+                .load(std::sync::atomic::Ordering::Relaxed),
+        );
+        if rps > 0.0 { Some(rps) } else { None }
+    }
+
+    fn check_read_only(&self) -> Result<()> {
+        if self.runtime.read_only() {
+            return Err(Error::unauthorized("server is in read-only mode"));
+        }
+        Ok(())
+    }
+
+    pub(crate) fn check_sysadmin(&self, token: &Arc<str>) -> Result<()> {
         if !self.get_sys_setup().sys_admin.contains(token) {
             return Err(Error::unauthorized(
                 "action requires sysadmin permissions",
@@ -226,7 +729,7 @@ impl Server {
             .obj()?
             .set_sys_setup(sys_setup.clone())
             .await?;
-        *self.sys_setup.lock().unwrap() = sys_setup;
+        *self.sys_setup.write().unwrap() = sys_setup;
         Ok(())
     }
 
@@ -242,6 +745,7 @@ impl Server {
         token: Arc<str>,
         setup: CtxSetup,
     ) -> Result<()> {
+        self.check_read_only()?;
         self.check_sysadmin(&token)?;
 
         setup.check()?;
@@ -254,7 +758,7 @@ impl Server {
 
         let (ctx, (ctx_setup, ctx_config)) = {
             let ctx = setup.ctx.clone();
-            let mut lock = self.ctx_setup.lock().unwrap();
+            let mut lock = self.ctx_setup.write().unwrap();
             let r = lock.entry(ctx.clone()).or_default();
             r.0 = setup;
             (ctx, r.clone())
@@ -273,19 +777,61 @@ impl Server {
         token: Arc<str>,
         config: CtxConfig,
     ) -> Result<()> {
+        self.check_read_only()?;
         self.check_ctxadmin(&token, &config.ctx)?;
 
         config.check()?;
 
-        self.runtime
-            .runtime()
-            .obj()?
-            .set_ctx_config(config.clone())
-            .await?;
+        // reject oversized code before it is validated, recorded in
+        // the rollback history, or stored as the canonical version
+        let max_code_bytes = self
+            .ctx_setup
+            .read()
+            .unwrap()
+            .get(&config.ctx)
+            .and_then(|r| r.0.max_code_bytes)
+            .unwrap_or(crate::js::JsSetup::DEF_MAX_CODE_BYTES as u64);
+        let code_bytes = config.code.len() as u64
+            + config
+                .code_modules
+                .values()
+                .map(|source| source.len() as u64)
+                .sum::<u64>();
+        if code_bytes > max_code_bytes {
+            tracing::trace!(
+                request = "ctx_config",
+                ctx = ?config.ctx,
+                "code too large"
+            );
+            return Err(Error::new(
+                std::io::ErrorKind::FileTooLarge,
+                format!(
+                    "config code is {code_bytes} bytes, exceeding the \
+                     context limit of {max_code_bytes} bytes"
+                ),
+            ));
+        }
+
+        // prove the new code compiles and exports a callable vm
+        // function before it replaces the current version; broken
+        // code is rejected here with the old config left in place
+        if config.validate {
+            self.validate_config(&config).await?;
+        }
+
+        let obj = self.runtime.runtime().obj()?;
+        // record the full config in the rollback history before it
+        // becomes the canonical version
+        obj.push_ctx_config_version(
+            &config,
+            server_global_get_config_history(),
+        )
+        .await?;
+        obj.set_ctx_config(config.clone()).await?;
 
         let (ctx, (ctx_setup, ctx_config)) = {
             let ctx = config.ctx.clone();
-            let mut lock = self.ctx_setup.lock().unwrap();
+            let mut lock = self.ctx_setup.write().unwrap();
             let r = lock.entry(ctx.clone()).or_default();
             r.1 = config;
             (ctx, r.clone())
@@ -298,86 +844,435 @@ impl Server {
         Ok(())
     }
 
-    /// Handle a msg listen request.
-    pub async fn msg_listen(
+    /// Re-run context setup from the persisted setup and config in
+    /// the object store, picking up changes made out-of-band, e.g. by
+    /// an object store restore (sysadmin). The replaced context
+    /// instance is dropped, which aborts its background tasks.
+    pub async fn reload_context(
         &self,
+        token: Arc<str>,
         ctx: Arc<str>,
-        msg_id: Arc<str>,
-    ) -> Option<crate::msg::DynMsgRecv> {
-        tracing::trace!(request = "msg_listen", ?ctx, ?msg_id);
+    ) -> Result<()> {
+        self.check_sysadmin(&token)?;
 
-        self.runtime
-            .runtime()
-            .msg()
-            .ok()?
-            .get_recv(ctx, msg_id)
-            .await
+        // re-read from the store, not the in-memory cache: the whole
+        // point is to pick up changes the cache never saw
+        let mut all = self.runtime.runtime().obj()?.list_ctx_all().await?;
+        let (setup, config) = all
+            .remove(&ctx)
+            .ok_or_else(|| Error::not_found(format!("no context: {ctx}")))?;
+
+        tracing::trace!(request = "reload_context", ?ctx);
+
+        self.ctx_setup
+            .write()
+            .unwrap()
+            .insert(ctx.clone(), (setup.clone(), config.clone()));
+
+        self.setup_context(ctx, setup, config).await
     }
 
-    /// Generate a full backup file on the local system.
-    pub async fn obj_backup_full(&self, token: Arc<str>) -> Result<()> {
-        self.check_sysadmin(&token)?;
+    /// List stored historical config versions for a context, oldest
+    /// first. A version is recorded on every config put; see
+    /// [Server::ctx_config_rollback].
+    pub async fn ctx_config_versions(
+        &self,
+        token: Arc<str>,
+        ctx: Arc<str>,
+    ) -> Result<Vec<CtxConfigVersion>> {
+        use sha2::Digest;
 
-        let mut zip = tokio::task::spawn_blocking(|| {
-            let zip = std::fs::OpenOptions::new()
-                .write(true)
-                .create_new(true)
-                .open("backup.zip")?;
-            std::io::Result::Ok(zip::ZipWriter::new(zip))
-        })
-        .await??;
+        self.check_ctxadmin(&token, &ctx)?;
 
-        let mut created_gt = 0.0;
-        let mut file_no = 1;
+        let obj = self.runtime.runtime().obj()?;
+        let mut out = Vec::new();
+        for (version, meta) in obj.list_ctx_config_versions(&ctx).await? {
+            let (meta, data) = obj.get(meta).await?;
+            let config: CtxConfig = data.to_decode()?;
+            out.push(CtxConfigVersion {
+                version,
+                created_secs: meta.created_secs(),
+                code_hash: bytes::Bytes::copy_from_slice(
+                    &sha2::Sha256::digest(config.code.as_bytes()),
+                )
+                .to_b64(),
+                size_bytes: meta.byte_length(),
+            });
+        }
+        Ok(out)
+    }
 
-        loop {
-            let meta_list = self
-                .runtime
-                .runtime()
-                .obj()?
-                .list("", created_gt, 200)
-                .await?;
+    /// Re-apply a previously stored config version through the normal
+    /// config put path (including thread invalidation and warm-up),
+    /// recording the rollback itself as a new version.
+    pub async fn ctx_config_rollback(
+        &self,
+        token: Arc<str>,
+        ctx: Arc<str>,
+        version: u64,
+    ) -> Result<()> {
+        self.check_ctxadmin(&token, &ctx)?;
 
-            if meta_list.is_empty() {
-                return Ok(());
-            }
+        tracing::trace!(request = "ctx_config_rollback", ?ctx, ?version);
 
-            for meta in meta_list {
-                created_gt = meta.created_secs();
+        let config = self
+            .runtime
+            .runtime()
+            .obj()?
+            .get_ctx_config_version(&ctx, version)
+            .await?;
+        self.ctx_config_put(token, config).await
+    }
 
-                let (meta, data) =
-                    self.runtime.runtime().obj()?.get(meta).await?;
+    /// Apply a [CtxPut] partial update to an existing context's
+    /// stored setup and config in a single call. Setup-side fields
+    /// (delete, timeout, heap) remain sysadmin-only, matching the
+    /// split ctx-setup/config routes this unifies; the rest require
+    /// ctxadmin permissions.
+    pub async fn ctx_put(&self, token: Arc<str>, input: CtxPut) -> Result<()> {
+        self.check_read_only()?;
+        let ctx = input.ctx.clone();
+        self.check_ctxadmin(&token, &ctx)?;
 
-                let meta2 = meta.clone();
-                zip = tokio::task::spawn_blocking(move || {
-                    use std::io::Write;
-                    let enc = rmp_serde::to_vec(&(meta2, data))
-                        .map_err(std::io::Error::other)?;
-                    zip.start_file(
-                        file_no.to_string(),
-                        zip::write::SimpleFileOptions::default(),
-                    )?;
-                    zip.write_all(&enc)?;
-                    std::io::Result::Ok(zip)
-                })
-                .await??;
+        if !self.get_sys_setup().sys_admin.contains(&token)
+            && (input.delete || input.touches_setup())
+        {
+            return Err(Error::unauthorized(
+                "setup fields require sysadmin permissions",
+            ));
+        }
 
-                file_no += 1;
+        tracing::trace!(request = "ctx_put", ?input);
 
-                tracing::info!(%meta, "backup file");
+        let obj = self.runtime.runtime().obj()?;
+
+        if input.delete {
+            obj.rm_ctx(&ctx).await?;
+            self.ctx_setup.write().unwrap().remove(&ctx);
+            self.ctx_map.write().unwrap().remove(&ctx);
+            return Ok(());
+        }
+
+        // prove replacement code before committing, as a full config
+        // put would; merged from a snapshot so no lock is held across
+        // the js round trip
+        if let Some(code) = &input.code
+            && !code.is_empty()
+        {
+            let (_, mut config) = self.get_ctx_setup(&ctx)?;
+            input.merge_config(&mut config);
+            if config.validate {
+                self.validate_config(&config).await?;
             }
         }
-    }
 
-    /// Restore a full backup file from the local system.
-    pub async fn obj_restore_full(&self, token: Arc<str>) -> Result<()> {
-        self.check_sysadmin(&token)?;
+        // the authoritative merge happens under the ctx_setup lock so
+        // concurrent partial updates cannot clobber each other
+        let (setup, config) = {
+            let mut lock = self.ctx_setup.write().unwrap();
+            let r = lock.get_mut(&ctx).ok_or_else(|| {
+                Error::not_found(format!("invalid context: {ctx}"))
+            })?;
+            let mut pair = r.clone();
+            input.merge_setup(&mut pair.0);
+            input.merge_config(&mut pair.1);
+            pair.0.check()?;
+            pair.1.check()?;
+            *r = pair.clone();
+            pair
+        };
 
-        let (mut zip, count) = tokio::task::spawn_blocking(|| {
-            let zip =
-                std::fs::OpenOptions::new().read(true).open("backup.zip")?;
-            let zip = zip::ZipArchive::new(zip)?;
-            let count = zip.len();
+        if input.touches_setup() {
+            obj.set_ctx_setup(setup.clone()).await?;
+        }
+        if input.touches_config() {
+            obj.push_ctx_config_version(
+                &config,
+                server_global_get_config_history(),
+            )
+            .await?;
+            obj.set_ctx_config(config.clone()).await?;
+        }
+
+        self.setup_context(ctx, setup, config).await?;
+
+        Ok(())
+    }
+
+    /// Provision a new context from an existing template context,
+    /// copying the [CtxSetup] and [CtxConfig] (admin tokens, code,
+    /// timeouts, etc) in a single call.
+    pub async fn ctx_clone(
+        &self,
+        token: Arc<str>,
+        src_ctx: Arc<str>,
+        dst_ctx: Arc<str>,
+    ) -> Result<()> {
+        self.check_read_only()?;
+        self.check_sysadmin(&token)?;
+
+        safe_str(&dst_ctx)?;
+        if self.ctx_setup.read().unwrap().contains_key(&dst_ctx) {
+            return Err(Error::other(format!(
+                "context already exists: {dst_ctx}"
+            )));
+        }
+
+        let (mut setup, mut config) = self.get_ctx_setup(&src_ctx)?;
+        setup.ctx = dst_ctx.clone();
+        config.ctx = dst_ctx.clone();
+
+        self.runtime
+            .runtime()
+            .obj()?
+            .set_ctx_setup(setup.clone())
+            .await?;
+        self.runtime
+            .runtime()
+            .obj()?
+            .set_ctx_config(config.clone())
+            .await?;
+
+        self.ctx_setup
+            .write()
+            .unwrap()
+            .insert(dst_ctx.clone(), (setup.clone(), config.clone()));
+
+        tracing::trace!(request = "ctx_clone", ?src_ctx, ?dst_ctx);
+
+        let has_code = !config.code.is_empty()
+            || !config.code_modules.is_empty()
+            || config.wasm.is_some();
+
+        self.setup_context(dst_ctx.clone(), setup, config).await?;
+
+        if has_code {
+            // verify the cloned code is functional in the new context
+            // with a no-op function request
+            let c = self
+                .ctx_map
+                .read()
+                .unwrap()
+                .get(&dst_ctx)
+                .cloned()
+                .ok_or_else(|| {
+                    Error::not_found(format!("invalid context: {dst_ctx}"))
+                })?;
+            c.fn_req(crate::js::JsRequest::FnReq {
+                method: "GET".into(),
+                path: "/".into(),
+                body: None,
+                headers: HashMap::new(),
+                body_json: None,
+                trace_id: None,
+                deadline_ms: None,
+                parts: None,
+            })
+            .await
+            .map_err(|err| err.with_info("cloned context code failed"))?;
+        }
+
+        Ok(())
+    }
+
+    /// List the contexts configured on this server.
+    pub async fn ctx_list(&self, token: Arc<str>) -> Result<Vec<CtxListEntry>> {
+        self.check_sysadmin(&token)?;
+
+        tracing::trace!(request = "ctx_list");
+
+        let mut out: Vec<CtxListEntry> = self
+            .ctx_setup
+            .read()
+            .unwrap()
+            .values()
+            .map(|(setup, _)| CtxListEntry {
+                ctx: setup.ctx.clone(),
+                ctx_admin_count: setup.ctx_admin.len(),
+                timeout_secs: setup.timeout_secs,
+                max_heap_bytes: setup.max_heap_bytes,
+            })
+            .collect();
+
+        out.sort_by(|a, b| a.ctx.cmp(&b.ctx));
+
+        Ok(out)
+    }
+
+    /// Map a hostname to a context for host-based routing, so
+    /// requests arriving for that host are served by the context's
+    /// function without the ctx path segment. An empty ctx removes
+    /// the mapping. Requires a sysadmin token.
+    pub async fn domain_set(
+        &self,
+        token: Arc<str>,
+        host: Arc<str>,
+        ctx: Arc<str>,
+    ) -> Result<()> {
+        self.check_read_only()?;
+        self.check_sysadmin(&token)?;
+
+        let host: Arc<str> = normalize_host(&host).into();
+        if host.is_empty() {
+            return Err(Error::invalid("host cannot be empty"));
+        }
+        safe_str(&host)?;
+        if !ctx.is_empty() {
+            safe_str(&ctx)?;
+        }
+
+        tracing::trace!(request = "domain_set", ?host, ?ctx);
+
+        let domains = {
+            let mut lock = self.domains.write().unwrap();
+            if ctx.is_empty() {
+                lock.remove(&host);
+            } else {
+                lock.insert(host, ctx);
+            }
+            lock.clone()
+        };
+        self.runtime.runtime().obj()?.set_domains(&domains).await?;
+
+        Ok(())
+    }
+
+    /// List the hostname to context routing map. Requires a sysadmin
+    /// token.
+    pub fn domain_list(
+        &self,
+        token: Arc<str>,
+    ) -> Result<HashMap<Arc<str>, Arc<str>>> {
+        self.check_sysadmin(&token)?;
+
+        tracing::trace!(request = "domain_list");
+
+        Ok(self.domains.read().unwrap().clone())
+    }
+
+    /// Resolve a request host header to its mapped context, if any.
+    pub fn domain_resolve(&self, host: &str) -> Option<Arc<str>> {
+        let host = normalize_host(host);
+        self.domains.read().unwrap().get(host.as_str()).cloned()
+    }
+
+    /// Store (or rotate) an encrypted secret for a context.
+    /// Note the value itself is intentionally never logged.
+    pub async fn secret_put(
+        &self,
+        token: Arc<str>,
+        ctx: Arc<str>,
+        name: Arc<str>,
+        value: bytes::Bytes,
+    ) -> Result<()> {
+        self.check_ctxadmin(&token, &ctx)?;
+
+        tracing::trace!(request = "secret_put", ?ctx, ?name);
+
+        crate::secret::put(
+            &self.runtime.runtime().obj()?,
+            &ctx,
+            &name,
+            &value,
+        )
+        .await
+    }
+
+    /// Delete a secret from a context.
+    pub async fn secret_rm(
+        &self,
+        token: Arc<str>,
+        ctx: Arc<str>,
+        name: Arc<str>,
+    ) -> Result<()> {
+        self.check_ctxadmin(&token, &ctx)?;
+
+        tracing::trace!(request = "secret_rm", ?ctx, ?name);
+
+        crate::secret::rm(&self.runtime.runtime().obj()?, &ctx, &name).await
+    }
+
+    /// Handle a msg listen request.
+    pub async fn msg_listen(
+        &self,
+        ctx: Arc<str>,
+        msg_id: Arc<str>,
+    ) -> Option<crate::msg::DynMsgRecv> {
+        tracing::trace!(request = "msg_listen", ?ctx, ?msg_id);
+
+        self.runtime
+            .runtime()
+            .msg()
+            .ok()?
+            .get_recv(ctx, msg_id)
+            .await
+    }
+
+    /// Generate a full backup file on the local system.
+    pub async fn obj_backup_full(&self, token: Arc<str>) -> Result<()> {
+        self.check_sysadmin(&token)?;
+
+        let mut zip = tokio::task::spawn_blocking(|| {
+            let zip = std::fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open("backup.zip")?;
+            std::io::Result::Ok(zip::ZipWriter::new(zip))
+        })
+        .await??;
+
+        let mut created_gt = 0.0;
+        let mut file_no = 1;
+
+        loop {
+            let meta_list = self
+                .runtime
+                .runtime()
+                .obj()?
+                .list("", created_gt, 200)
+                .await?;
+
+            if meta_list.is_empty() {
+                return Ok(());
+            }
+
+            for meta in meta_list {
+                created_gt = meta.created_secs();
+
+                let (meta, data) =
+                    self.runtime.runtime().obj()?.get(meta).await?;
+
+                let meta2 = meta.clone();
+                zip = tokio::task::spawn_blocking(move || {
+                    use std::io::Write;
+                    let enc = rmp_serde::to_vec(&(meta2, data))
+                        .map_err(std::io::Error::other)?;
+                    zip.start_file(
+                        file_no.to_string(),
+                        zip::write::SimpleFileOptions::default(),
+                    )?;
+                    zip.write_all(&enc)?;
+                    std::io::Result::Ok(zip)
+                })
+                .await??;
+
+                file_no += 1;
+
+                tracing::info!(%meta, "backup file");
+            }
+        }
+    }
+
+    /// Restore a full backup file from the local system.
+    pub async fn obj_restore_full(&self, token: Arc<str>) -> Result<()> {
+        self.check_read_only()?;
+        self.check_sysadmin(&token)?;
+
+        let (mut zip, count) = tokio::task::spawn_blocking(|| {
+            let zip =
+                std::fs::OpenOptions::new().read(true).open("backup.zip")?;
+            let zip = zip::ZipArchive::new(zip)?;
+            let count = zip.len();
             std::io::Result::Ok((zip, count))
         })
         .await??;
@@ -417,6 +1312,8 @@ impl Server {
         ctx: Arc<str>,
         prefix: Arc<str>,
         created_gt: f64,
+        created_lt: Option<f64>,
+        descending: bool,
         limit: u32,
     ) -> Result<Vec<crate::obj::ObjMeta>> {
         self.check_ctxadmin(&token, &ctx)?;
@@ -429,6 +1326,8 @@ impl Server {
             ?ctx,
             ?prefix,
             ?created_gt,
+            ?created_lt,
+            ?descending,
             ?limit
         );
 
@@ -436,7 +1335,7 @@ impl Server {
             .runtime
             .runtime()
             .obj()?
-            .list(&prefix, created_gt, limit)
+            .list_range(&prefix, created_gt, created_lt, descending, limit)
             .await;
 
         if let Ok(meta_list) = &res {
@@ -474,75 +1373,621 @@ impl Server {
         res
     }
 
-    /// Put an item into the object store.
-    pub async fn obj_put(
+    /// Query the object store across multiple app path prefixes in a
+    /// single round trip, optionally fetching object data under a
+    /// total byte budget.
+    pub async fn obj_query(
         &self,
         token: Arc<str>,
-        meta: crate::obj::ObjMeta,
-        data: bytes::Bytes,
-    ) -> Result<crate::obj::ObjMeta> {
-        let ctx: Arc<str> = meta.ctx().into();
+        ctx: Arc<str>,
+        query: ObjQuery,
+    ) -> Result<ObjQueryResponse> {
         self.check_ctxadmin(&token, &ctx)?;
 
-        let cs = meta.created_secs();
-        let cs = if cs < 1.0 {
-            safe_now().to_string()
-        } else {
-            meta.0.split('/').nth(3).unwrap_or("").to_string()
-        };
+        let limit = query.limit.min(query_limit());
 
-        let meta = crate::obj::ObjMeta(
-            format!(
-                "c/{ctx}/{}/{cs}/{}/{}",
-                meta.app_path(),
-                meta.expires_secs(),
-                data.len(),
-            )
-            .into(),
+        tracing::trace!(
+            request = "obj_query",
+            ?ctx,
+            prefixes = ?query.prefixes,
+            ?limit,
+            return_data = ?query.return_data
         );
 
-        tracing::trace!(request = "obj_put", ?ctx, ?meta);
+        let obj = self.runtime.runtime().obj()?;
+
+        // merge every prefix, dropping duplicates where prefixes
+        // overlap (e.g. "a" and "a.b" both match "a.b.c")
+        let mut seen = HashSet::new();
+        let mut meta_list = Vec::new();
+        for prefix in query.prefixes.iter() {
+            let prefix =
+                format!("{}/{}/{prefix}", crate::obj::ObjMeta::SYS_CTX, ctx);
+            for meta in obj
+                .list_range(
+                    &prefix,
+                    query.created_gt,
+                    query.created_lt,
+                    false,
+                    limit,
+                )
+                .await?
+            {
+                if seen.insert(meta.0.clone()) {
+                    meta_list.push(meta);
+                }
+            }
+        }
 
-        let c = match self.ctx_map.lock().unwrap().get(&ctx) {
-            None => {
-                return Err(Error::not_found(format!(
-                    "invalid context: {ctx}"
-                )));
+        meta_list
+            .sort_by(|a, b| a.created_secs().total_cmp(&b.created_secs()));
+        meta_list.truncate(limit as usize);
+
+        let mut egress: u128 = 0;
+        let mut budget = query.max_data_bytes;
+        let mut truncated = false;
+        let mut entries = Vec::with_capacity(meta_list.len());
+        for meta in meta_list {
+            egress += meta.len() as u128;
+
+            let data = if query.return_data && !truncated {
+                let (_, data) = obj.get(meta.clone()).await?;
+                if data.len() as u64 > budget {
+                    // from here on, entries come back meta-only
+                    truncated = true;
+                    None
+                } else {
+                    budget -= data.len() as u64;
+                    egress += data.len() as u128;
+                    Some(data)
+                }
+            } else {
+                None
+            };
+
+            entries.push(ObjQueryEntry { meta, data });
+        }
+
+        crate::meter::meter_egress_byte(&ctx, egress);
+
+        Ok(ObjQueryResponse { entries, truncated })
+    }
+
+    /// Atomically add a single context admin token to a context's
+    /// setup, returning the resulting token list. Unlike
+    /// [Server::ctx_setup_put] this does not replace the whole list,
+    /// so concurrent admins cannot clobber each other's changes.
+    pub async fn ctx_admin_add(
+        &self,
+        token: Arc<str>,
+        ctx: Arc<str>,
+        add: Arc<str>,
+    ) -> Result<Vec<Arc<str>>> {
+        self.check_read_only()?;
+        self.check_sysadmin(&token)?;
+        safe_str(&add)?;
+
+        tracing::trace!(request = "ctx_admin_add", ?ctx);
+
+        self.ctx_admin_update(ctx, |list| {
+            if !list.contains(&add) {
+                list.push(add);
             }
-            Some(c) => c.clone(),
+        })
+        .await
+    }
+
+    /// Atomically remove a single context admin token from a
+    /// context's setup, returning the resulting token list.
+    pub async fn ctx_admin_remove(
+        &self,
+        token: Arc<str>,
+        ctx: Arc<str>,
+        remove: Arc<str>,
+    ) -> Result<Vec<Arc<str>>> {
+        self.check_read_only()?;
+        self.check_sysadmin(&token)?;
+
+        tracing::trace!(request = "ctx_admin_remove", ?ctx);
+
+        self.ctx_admin_update(ctx, |list| {
+            list.retain(|t| *t != remove);
+        })
+        .await
+    }
+
+    /// Read-modify-write of a context's admin token list under the
+    /// ctx_setup lock, persisting and re-applying the result.
+    async fn ctx_admin_update(
+        &self,
+        ctx: Arc<str>,
+        update: impl FnOnce(&mut Vec<Arc<str>>),
+    ) -> Result<Vec<Arc<str>>> {
+        let (setup, config) = {
+            let mut lock = self.ctx_setup.write().unwrap();
+            let r = lock.get_mut(&ctx).ok_or_else(|| {
+                Error::not_found(format!("invalid context: {ctx}"))
+            })?;
+            update(&mut r.0.ctx_admin);
+            r.clone()
         };
-        c.obj_check_req(meta.clone(), data.clone()).await?;
+
+        let list = setup.ctx_admin.clone();
 
         self.runtime
             .runtime()
             .obj()?
-            .put(meta.clone(), data)
+            .set_ctx_setup(setup.clone())
             .await?;
 
-        Ok(meta)
+        self.setup_context(ctx, setup, config).await?;
+
+        Ok(list)
     }
 
-    /// Process a function request.
-    pub async fn fn_req(
+    /// Atomically rotate a context's admin tokens without re-sending
+    /// the full setup or config: `add` tokens join the config-side
+    /// list, then `remove` tokens are dropped from both the setup-
+    /// and config-side lists. Permitted for existing ctxadmins. The
+    /// change is persisted and honored by subsequent permission
+    /// checks immediately; code and js threads are untouched.
+    /// Removing the last remaining token is rejected unless the
+    /// caller is sysadmin, so a context cannot lock itself out.
+    /// Returns the resulting union of both lists.
+    pub async fn ctx_tokens(
         &self,
+        token: Arc<str>,
         ctx: Arc<str>,
-        req: crate::js::JsRequest,
-    ) -> Result<crate::js::JsResponse> {
-        let req_id = rid();
-
-        tracing::trace!(request = "fn_req", %req_id, ?ctx, ?req);
+        input: CtxTokens,
+    ) -> Result<Vec<Arc<str>>> {
+        self.check_read_only()?;
+        self.check_ctxadmin(&token, &ctx)?;
+        for t in input.add.iter() {
+            safe_str(t)?;
+        }
 
-        let c = match self.ctx_map.lock().unwrap().get(&ctx) {
-            None => {
-                tracing::trace!(request = "fn_req", ?ctx, "invalid context");
-                return Err(Error::not_found(format!(
-                    "invalid context: {ctx}"
-                )));
+        tracing::trace!(request = "ctx_tokens", ?ctx);
+
+        let is_sysadmin = self.get_sys_setup().sys_admin.contains(&token);
+
+        // the read-modify-write happens under the ctx_setup lock so
+        // two rotations racing cannot clobber each other's changes
+        let (setup, config) = {
+            let mut lock = self.ctx_setup.write().unwrap();
+            let r = lock.get_mut(&ctx).ok_or_else(|| {
+                Error::not_found(format!("invalid context: {ctx}"))
+            })?;
+            let mut pair = r.clone();
+            for t in input.add.iter() {
+                if !pair.0.ctx_admin.contains(t)
+                    && !pair.1.ctx_admin.contains(t)
+                {
+                    pair.1.ctx_admin.push(t.clone());
+                }
             }
-            Some(c) => c.clone(),
+            pair.0.ctx_admin.retain(|t| !input.remove.contains(t));
+            pair.1.ctx_admin.retain(|t| !input.remove.contains(t));
+            if pair.0.ctx_admin.is_empty()
+                && pair.1.ctx_admin.is_empty()
+                && !is_sysadmin
+            {
+                return Err(Error::unauthorized(
+                    "removing the last ctxadmin token requires \
+                     sysadmin permissions",
+                ));
+            }
+            *r = pair.clone();
+            pair
         };
 
-        let res = c.fn_req(req).await;
+        // persisted without a config version push: a token rotation
+        // is not a code change, and recording it in the rollback
+        // history would serve no one
+        let obj = self.runtime.runtime().obj()?;
+        obj.set_ctx_setup(setup.clone()).await?;
+        obj.set_ctx_config(config.clone()).await?;
+
+        let mut list = setup.ctx_admin;
+        for t in config.ctx_admin {
+            if !list.contains(&t) {
+                list.push(t);
+            }
+        }
+        Ok(list)
+    }
+
+    /// Run the context's ObjCheck handler against already-stored
+    /// objects under `c/{ctx}/{prefix}`, reporting (and optionally
+    /// deleting) objects that no longer pass. One page of up to
+    /// `limit` objects after `created_gt` is processed per call; the
+    /// report carries a continuation watermark when more may remain,
+    /// so long-running revalidation does not starve normal traffic.
+    pub async fn ctx_revalidate(
+        &self,
+        token: Arc<str>,
+        ctx: Arc<str>,
+        prefix: String,
+        created_gt: f64,
+        limit: u32,
+        delete_failures: bool,
+    ) -> Result<RevalidateReport> {
+        use futures::StreamExt;
+
+        /// How many objects are checked concurrently.
+        const CONCURRENT: usize = 4;
+
+        self.check_ctxadmin(&token, &ctx)?;
+
+        tracing::trace!(request = "ctx_revalidate", ?ctx, ?prefix);
+
+        let c = match self.ctx_map.read().unwrap().get(&ctx) {
+            None => {
+                return Err(Error::not_found(format!(
+                    "invalid context: {ctx}"
+                )));
+            }
+            Some(c) => c.clone(),
+        };
+
+        let obj = self.runtime.runtime().obj()?;
+        let metas = obj
+            .list(&format!("c/{ctx}/{prefix}"), created_gt, limit)
+            .await?;
+        // the store over-returns past the limit when the page boundary
+        // lands on a created_secs tie, so an over-full page still means
+        // more may remain
+        let full = !metas.is_empty() && metas.len() as u32 >= limit;
+
+        let results: Vec<(crate::obj::ObjMeta, Result<()>)> =
+            futures::stream::iter(metas.into_iter().map(|meta| {
+                let c = c.clone();
+                let obj = obj.clone();
+                async move {
+                    let res = match obj.get(meta.clone()).await {
+                        Ok((meta, data)) => c.obj_check_req(meta, data).await,
+                        Err(err) => Err(err),
+                    };
+                    (meta, res)
+                }
+            }))
+            .buffered(CONCURRENT)
+            .collect()
+            .await;
+
+        let mut checked = 0;
+        let mut watermark = created_gt;
+        let mut failures = Vec::new();
+        for (meta, res) in results {
+            checked += 1;
+            let created_secs = meta.created_secs();
+            if created_secs > watermark {
+                watermark = created_secs;
+            }
+            if let Err(err) = res {
+                if delete_failures {
+                    obj.rm(meta.clone()).await?;
+                }
+                failures.push(RevalidateFailure {
+                    meta,
+                    error: err.to_string(),
+                });
+            }
+        }
+
+        Ok(RevalidateReport {
+            checked,
+            failures,
+            continuation: if full { Some(watermark) } else { None },
+        })
+    }
+
+    /// Snapshot of the admin-visible status of a context. Admin
+    /// tokens themselves are never included, only their count.
+    pub fn ctx_status(
+        &self,
+        token: Arc<str>,
+        ctx: Arc<str>,
+    ) -> Result<serde_json::Value> {
+        let (setup, config) = self.check_ctxadmin(&token, &ctx)?;
+
+        tracing::trace!(request = "ctx_status", ?ctx);
+
+        let has_code = !config.code.is_empty()
+            || !config.code_modules.is_empty()
+            || config.wasm.is_some();
+
+        let js = self
+            .runtime
+            .runtime()
+            .js()
+            .map(|js| js.stats())
+            .unwrap_or_default();
+
+        Ok(serde_json::json!({
+            "ctx": &*ctx,
+            "running": self.ctx_map.read().unwrap().contains_key(&ctx),
+            "ctxAdminCount": setup.ctx_admin.len() + config.ctx_admin.len(),
+            "timeoutSecs": setup.timeout_secs,
+            "maxHeapBytes": setup.max_heap_bytes,
+            "hasCode": has_code,
+            "codeEnv": &*config.code_env,
+            "js": {
+                "threadsInUse": js.threads_in_use,
+                "queueLen": js.queue_len,
+                "rejected": js.rejected,
+            },
+        }))
+    }
+
+    /// Sign a time-limited grant of read access to a single object.
+    /// The returned query string is of the form `?exp=<secs>&sig=<b64>`
+    /// and can be appended to the object get url, which will then serve
+    /// the object without a bearer token until `expires_secs` (absolute
+    /// unix epoch seconds) has passed.
+    pub fn sign_obj_url(
+        &self,
+        token: Arc<str>,
+        ctx: Arc<str>,
+        app_path: String,
+        expires_secs: f64,
+    ) -> Result<String> {
+        self.check_ctxadmin(&token, &ctx)?;
+
+        tracing::trace!(request = "sign_obj_url", ?ctx, ?app_path);
+
+        let exp = expires_secs.to_string();
+        let sig = self.obj_url_sig(&ctx, &app_path, &exp);
+        Ok(format!("?exp={exp}&sig={sig}"))
+    }
+
+    /// The signature covers the exact `exp` string from the query so
+    /// float formatting can never cause a verification mismatch.
+    fn obj_url_sig(&self, ctx: &str, app_path: &str, exp: &str) -> String {
+        let sig = crate::secret::derive_key(
+            &self.url_sign_seed,
+            format!("{ctx}/{app_path}/{exp}").as_bytes(),
+        );
+        bytes::Bytes::copy_from_slice(&sig[..32]).to_b64()
+    }
+
+    /// Verify a signed url query produced by [Server::sign_obj_url].
+    fn check_obj_url(
+        &self,
+        ctx: &str,
+        app_path: &str,
+        exp: &str,
+        sig: &str,
+    ) -> Result<()> {
+        // tolerate a few seconds of clock skew between the signing
+        // server and the verifying server
+        const SKEW_SECS: f64 = 5.0;
+
+        let exp_secs: f64 = exp
+            .parse()
+            .map_err(|_| Error::unauthorized("invalid signed url"))?;
+        if exp_secs + SKEW_SECS <= safe_now() {
+            return Err(Error::unauthorized("signed url expired"));
+        }
+
+        let expect = self.obj_url_sig(ctx, app_path, exp);
+        // constant-time comparison
+        let mut diff = if expect.len() == sig.len() { 0_u8 } else { 1 };
+        for (a, b) in expect.bytes().zip(sig.bytes()) {
+            diff |= a ^ b;
+        }
+        if diff != 0 {
+            return Err(Error::unauthorized("invalid signed url"));
+        }
+
+        Ok(())
+    }
+
+    /// Get an item from the object store using a signed url query
+    /// produced by [Server::sign_obj_url] in lieu of a bearer token.
+    pub async fn obj_get_signed(
+        &self,
+        ctx: Arc<str>,
+        app_path: String,
+        exp: &str,
+        sig: &str,
+    ) -> Result<(crate::obj::ObjMeta, bytes::Bytes)> {
+        self.check_obj_url(&ctx, &app_path, exp, sig)?;
+
+        let meta =
+            crate::obj::ObjMeta::new_context(&ctx, &app_path, 0.0, 0.0, 0.0);
+
+        tracing::trace!(request = "obj_get_signed", ?ctx, ?meta);
+
+        let res = self.runtime.runtime().obj()?.get(meta).await;
+
+        if let Ok((meta, data)) = &res {
+            crate::meter::meter_egress_byte(
+                &ctx,
+                (meta.len() + data.len()) as u128,
+            );
+        }
+
+        res
+    }
+
+    /// Get just the metadata for a single object without transferring
+    /// its content. Used by the http layer to answer conditional gets.
+    pub async fn obj_get_meta(
+        &self,
+        token: Arc<str>,
+        ctx: Arc<str>,
+        app_path: String,
+    ) -> Result<crate::obj::ObjMeta> {
+        self.check_ctxadmin(&token, &ctx)?;
+
+        tracing::trace!(request = "obj_get_meta", ?ctx, ?app_path);
+
+        self.obj_meta_single(&ctx, &app_path).await
+    }
+
+    /// Get just the metadata for a single object using a signed url
+    /// query produced by [Server::sign_obj_url] in lieu of a bearer
+    /// token.
+    pub async fn obj_get_meta_signed(
+        &self,
+        ctx: Arc<str>,
+        app_path: String,
+        exp: &str,
+        sig: &str,
+    ) -> Result<crate::obj::ObjMeta> {
+        self.check_obj_url(&ctx, &app_path, exp, sig)?;
+
+        tracing::trace!(request = "obj_get_meta_signed", ?ctx, ?app_path);
+
+        self.obj_meta_single(&ctx, &app_path).await
+    }
+
+    async fn obj_meta_single(
+        &self,
+        ctx: &str,
+        app_path: &str,
+    ) -> Result<crate::obj::ObjMeta> {
+        // app paths cannot contain '/', so listing up to the trailing
+        // slash matches at most the one live version of this object
+        let mut res = self
+            .runtime
+            .runtime()
+            .obj()?
+            .list(&format!("c/{ctx}/{app_path}/"), 0.0, 1)
+            .await?;
+
+        if res.is_empty() {
+            return Err(Error::not_found(format!(
+                "could not find {app_path}"
+            )));
+        }
+
+        let meta = res.remove(0);
+        crate::meter::meter_egress_byte(ctx, meta.len() as u128);
+        Ok(meta)
+    }
+
+    /// Put an item into the object store. An optional content type
+    /// is recorded in the meta path and served back by the raw route.
+    pub async fn obj_put(
+        &self,
+        token: Arc<str>,
+        meta: crate::obj::ObjMeta,
+        data: bytes::Bytes,
+        content_type: Option<String>,
+    ) -> Result<crate::obj::ObjMeta> {
+        self.check_read_only()?;
+        let ctx: Arc<str> = meta.ctx().into();
+        self.check_ctxadmin(&token, &ctx)?;
+
+        let cs = meta.created_secs();
+        let cs = if cs < 1.0 {
+            safe_now().to_string()
+        } else {
+            meta.0.split('/').nth(3).unwrap_or("").to_string()
+        };
+
+        let ct = match &content_type {
+            Some(ct) => format!(
+                "/{}",
+                crate::obj::ObjMeta::content_type_segment(ct),
+            ),
+            None => String::new(),
+        };
+
+        let meta = crate::obj::ObjMeta(
+            format!(
+                "c/{ctx}/{}/{cs}/{}/{}{ct}",
+                meta.app_path(),
+                meta.expires_secs(),
+                data.len(),
+            )
+            .into(),
+        );
+
+        tracing::trace!(request = "obj_put", ?ctx, ?meta);
+
+        let c = match self.ctx_map.read().unwrap().get(&ctx) {
+            None => {
+                return Err(Error::not_found(format!(
+                    "invalid context: {ctx}"
+                )));
+            }
+            Some(c) => c.clone(),
+        };
+        c.obj_check_req(meta.clone(), data.clone()).await?;
+
+        self.runtime
+            .runtime()
+            .obj()?
+            .put(meta.clone(), data)
+            .await?;
+
+        Ok(meta)
+    }
+
+    /// Process a function request.
+    pub async fn fn_req(
+        &self,
+        ctx: Arc<str>,
+        mut req: crate::js::JsRequest,
+    ) -> Result<crate::js::JsResponse> {
+        let req_id = rid();
+
+        tracing::trace!(request = "fn_req", %req_id, ?ctx, ?req);
+
+        if let crate::js::JsRequest::FnReq {
+            method,
+            path,
+            headers,
+            body,
+            ..
+        } = &mut req
+        {
+            // read-only mode: GET functions keep serving, mutating
+            // methods (PUT, POST, DELETE) are refused before user
+            // code runs
+            if !method.eq_ignore_ascii_case("GET") {
+                self.check_read_only()?;
+            }
+
+            let (setup, config) = self.get_ctx_setup(&ctx)?;
+            if let (Some(max), Some(body)) =
+                (setup.max_fn_request_bytes, body.as_ref())
+                && body.len() as u64 > max
+            {
+                tracing::trace!(request = "fn_req", ?ctx, "body too large");
+                return Err(Error::new(
+                    std::io::ErrorKind::FileTooLarge,
+                    format!(
+                        "request body exceeds context limit of {max} bytes"
+                    ),
+                ));
+            }
+            if !config.fn_path_allow.is_empty()
+                && !config
+                    .fn_path_allow
+                    .iter()
+                    .any(|pat| glob_match(pat, path))
+            {
+                tracing::trace!(request = "fn_req", ?ctx, "path not allowed");
+                return Err(Error::not_found(format!("no such path: {path}")));
+            }
+            *headers =
+                filter_fn_headers(&ctx, &config, std::mem::take(headers));
+        }
+
+        let c = match self.ctx_map.read().unwrap().get(&ctx) {
+            None => {
+                tracing::trace!(request = "fn_req", ?ctx, "invalid context");
+                return Err(Error::not_found(format!(
+                    "invalid context: {ctx}"
+                )));
+            }
+            Some(c) => c.clone(),
+        };
+
+        let res = c.fn_req(req).await;
 
         tracing::trace!(request = "fn_req", %req_id, ?ctx, ?res);
 
@@ -566,3 +2011,1474 @@ fn rid() -> u64 {
         std::sync::atomic::AtomicU64::new(1);
     I.fetch_add(1, std::sync::atomic::Ordering::Relaxed)
 }
+
+/// Max byte length of a single inbound header value forwarded to a
+/// function invocation.
+const HDR_MAX_VALUE_BYTES: usize = 8 * 1024;
+
+/// Max total byte length of all inbound headers forwarded to a
+/// function invocation.
+const HDR_MAX_TOTAL_BYTES: usize = 64 * 1024;
+
+/// Filter inbound request headers before they are forwarded to a
+/// function invocation. `cookie` is always stripped, per-context
+/// deny and allow lists are applied, and oversized headers are
+/// dropped.
+fn filter_fn_headers(
+    ctx: &str,
+    config: &CtxConfig,
+    headers: HashMap<String, String>,
+) -> HashMap<String, String> {
+    let mut total = 0;
+    let mut out = HashMap::new();
+
+    for (k, v) in headers {
+        let k = k.to_lowercase();
+
+        if k == "cookie" {
+            continue;
+        }
+        if config.hdr_deny.iter().any(|d| d.eq_ignore_ascii_case(&k)) {
+            continue;
+        }
+        if !config.hdr_allow.is_empty()
+            && !config.hdr_allow.iter().any(|a| a.eq_ignore_ascii_case(&k))
+        {
+            continue;
+        }
+        if v.len() > HDR_MAX_VALUE_BYTES
+            || total + k.len() + v.len() > HDR_MAX_TOTAL_BYTES
+        {
+            continue;
+        }
+
+        if k == "authorization" {
+            tracing::debug!(
+                request = "fn_req",
+                ?ctx,
+                "forwarding authorization header to context function",
+            );
+        }
+
+        total += k.len() + v.len();
+        out.insert(k, v);
+    }
+
+    out
+}
+
+/// Match a simple glob pattern where '*' matches any run of characters.
+/// Linear two-pointer scan: on a mismatch after a '*', resume at the
+/// star and consume one more path byte. Patterns come from ctxadmins
+/// but the path is attacker-controlled on an unauthenticated route, so
+/// a backtracking matcher exponential in the '*' count is not an
+/// option.
+fn glob_match(pattern: &str, path: &str) -> bool {
+    let pat = pattern.as_bytes();
+    let path = path.as_bytes();
+
+    let mut p = 0;
+    let mut s = 0;
+    let mut star: Option<(usize, usize)> = None;
+
+    while s < path.len() {
+        if p < pat.len() && (pat[p] == b'*' || pat[p] == path[s]) {
+            if pat[p] == b'*' {
+                star = Some((p, s));
+                p += 1;
+            } else {
+                p += 1;
+                s += 1;
+            }
+        } else if let Some((star_p, star_s)) = star {
+            p = star_p + 1;
+            s = star_s + 1;
+            star = Some((star_p, star_s + 1));
+        } else {
+            return false;
+        }
+    }
+
+    // trailing stars match the empty remainder
+    while p < pat.len() && pat[p] == b'*' {
+        p += 1;
+    }
+    p == pat.len()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn ctx_list_entries() {
+        let rth = RuntimeHandle::default();
+        rth.set_obj(
+            crate::obj::obj_file::ObjFile::create(None).await.unwrap(),
+        );
+        rth.set_js(crate::js::JsExecDefault::create());
+        rth.set_msg(crate::msg::MsgMem::create());
+        let server = Server::new(rth).await.unwrap();
+        let admin: Arc<str> = "test-admin".into();
+        server.set_sys_admin(vec![admin.clone()]).await.unwrap();
+
+        server
+            .ctx_setup_put(
+                admin.clone(),
+                CtxSetup {
+                    ctx: "beta".into(),
+                    ctx_admin: vec!["a".into(), "b".into()],
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        server
+            .ctx_setup_put(
+                admin.clone(),
+                CtxSetup {
+                    ctx: "alpha".into(),
+                    ctx_admin: vec!["a".into()],
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+
+        // non-sysadmin tokens are rejected
+        assert!(server.ctx_list("nope".into()).await.is_err());
+
+        let list = server.ctx_list(admin).await.unwrap();
+        assert_eq!(2, list.len());
+        assert_eq!("alpha", list[0].ctx.as_ref());
+        assert_eq!(1, list[0].ctx_admin_count);
+        assert_eq!("beta", list[1].ctx.as_ref());
+        assert_eq!(2, list[1].ctx_admin_count);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn ctx_clone_from_template() {
+        let rth = RuntimeHandle::default();
+        rth.set_obj(
+            crate::obj::obj_file::ObjFile::create(None).await.unwrap(),
+        );
+        rth.set_js(crate::js::JsExecDefault::create());
+        rth.set_msg(crate::msg::MsgMem::create());
+        let server = Server::new(rth).await.unwrap();
+        let admin: Arc<str> = "test-admin".into();
+        server.set_sys_admin(vec![admin.clone()]).await.unwrap();
+
+        server
+            .ctx_setup_put(
+                admin.clone(),
+                CtxSetup {
+                    ctx: "tmpl".into(),
+                    timeout_secs: 5.0,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        server
+            .ctx_config_put(
+                admin.clone(),
+                CtxConfig {
+                    ctx: "tmpl".into(),
+                    code: "
+async function vm(req) {
+    if (req.type === 'fnReq') {
+        return { type: 'fnResOk' };
+    }
+    throw new Error('unhandled');
+}
+"
+                    .into(),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+
+        // non-sysadmin tokens cannot clone
+        assert!(
+            server
+                .ctx_clone("nope".into(), "tmpl".into(), "copy".into())
+                .await
+                .is_err()
+        );
+
+        server
+            .ctx_clone(admin.clone(), "tmpl".into(), "copy".into())
+            .await
+            .unwrap();
+
+        // cloning over an existing context is rejected
+        assert!(
+            server
+                .ctx_clone(admin.clone(), "tmpl".into(), "copy".into())
+                .await
+                .is_err()
+        );
+
+        // the clone kept the template settings and functional code
+        let list = server.ctx_list(admin).await.unwrap();
+        assert_eq!(2, list.len());
+        assert_eq!("copy", list[0].ctx.as_ref());
+        assert_eq!(5.0, list[0].timeout_secs);
+
+        let res = server
+            .fn_req(
+                "copy".into(),
+                crate::js::JsRequest::FnReq {
+                    method: "GET".into(),
+                    path: "/".into(),
+                    body: None,
+                    headers: HashMap::new(),
+                    body_json: None,
+                    trace_id: None,
+                    deadline_ms: None,
+                    parts: None,
+                },
+            )
+            .await
+            .unwrap();
+        assert!(matches!(res, crate::js::JsResponse::FnResOk { .. }));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn reload_context_picks_up_out_of_band_config() {
+        let rth = RuntimeHandle::default();
+        rth.set_obj(
+            crate::obj::obj_file::ObjFile::create(None).await.unwrap(),
+        );
+        rth.set_js(crate::js::JsExecDefault::create());
+        rth.set_msg(crate::msg::MsgMem::create());
+        let runtime = rth.runtime();
+        let server = Server::new(rth).await.unwrap();
+        let admin: Arc<str> = "test-admin".into();
+        server.set_sys_admin(vec![admin.clone()]).await.unwrap();
+
+        server
+            .ctx_setup_put(
+                admin.clone(),
+                CtxSetup {
+                    ctx: "test".into(),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        server
+            .ctx_config_put(
+                admin.clone(),
+                CtxConfig {
+                    ctx: "test".into(),
+                    code: "
+async function vm(req) {
+    return { type: 'fnResOk', bodyJson: { v: 1 } };
+}
+"
+                    .into(),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+
+        // write a replacement config directly to the store, the way a
+        // restore would, bypassing the running server entirely
+        runtime
+            .obj()
+            .unwrap()
+            .set_ctx_config(CtxConfig {
+                ctx: "test".into(),
+                code: "
+async function vm(req) {
+    return { type: 'fnResOk', bodyJson: { v: 2 } };
+}
+"
+                .into(),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        let req = || crate::js::JsRequest::FnReq {
+            method: "GET".into(),
+            path: "/".into(),
+            body: None,
+            headers: HashMap::new(),
+            body_json: None,
+            trace_id: None,
+            deadline_ms: None,
+            parts: None,
+        };
+
+        // the running context has not noticed the store change
+        let res = server.fn_req("test".into(), req()).await.unwrap();
+        match res {
+            crate::js::JsResponse::FnResOk { body_json, .. } => {
+                assert_eq!(Some(serde_json::json!({ "v": 1 })), body_json);
+            }
+            res => panic!("unexpected response: {res:?}"),
+        }
+
+        // non-sysadmin tokens cannot reload, unknown contexts 404
+        assert!(
+            server
+                .reload_context("nope".into(), "test".into())
+                .await
+                .is_err()
+        );
+        assert!(
+            server
+                .reload_context(admin.clone(), "missing".into())
+                .await
+                .is_err()
+        );
+
+        server.reload_context(admin, "test".into()).await.unwrap();
+
+        // the rebuilt context serves the restored code
+        let res = server.fn_req("test".into(), req()).await.unwrap();
+        match res {
+            crate::js::JsResponse::FnResOk { body_json, .. } => {
+                assert_eq!(Some(serde_json::json!({ "v": 2 })), body_json);
+            }
+            res => panic!("unexpected response: {res:?}"),
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn obj_query_merges_prefixes_and_budgets_data() {
+        let rth = RuntimeHandle::default();
+        rth.set_obj(
+            crate::obj::obj_file::ObjFile::create(None).await.unwrap(),
+        );
+        rth.set_js(crate::js::JsExecDefault::create());
+        rth.set_msg(crate::msg::MsgMem::create());
+        let runtime = rth.runtime();
+        let server = Server::new(rth).await.unwrap();
+        let admin: Arc<str> = "test-admin".into();
+        server.set_sys_admin(vec![admin.clone()]).await.unwrap();
+        server
+            .ctx_setup_put(
+                admin.clone(),
+                CtxSetup {
+                    ctx: "test".into(),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+
+        // store objects directly, bypassing the obj check
+        for (app_path, created, data) in [
+            ("a.one", 1.0, &b"aaaa"[..]),
+            ("b.one", 2.0, &b"bb"[..]),
+            ("a.two", 3.0, &b"AAAA"[..]),
+        ] {
+            let data = bytes::Bytes::copy_from_slice(data);
+            let meta = crate::obj::ObjMeta::new_context(
+                "test",
+                app_path,
+                created,
+                0.0,
+                data.len() as f64,
+            );
+            runtime.obj().unwrap().put(meta, data).await.unwrap();
+        }
+
+        // non-admin tokens cannot query
+        assert!(
+            server
+                .obj_query("nope".into(), "test".into(), ObjQuery::default())
+                .await
+                .is_err()
+        );
+
+        // prefixes merge oldest first, and the 7 byte data budget
+        // covers a.one (4) and b.one (2) but not a.two (4)
+        let res = server
+            .obj_query(
+                admin.clone(),
+                "test".into(),
+                ObjQuery {
+                    prefixes: vec!["a.".into(), "b.".into()],
+                    return_data: true,
+                    max_data_bytes: 7,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        assert!(res.truncated);
+        assert_eq!(3, res.entries.len());
+        assert_eq!("a.one", res.entries[0].meta.app_path());
+        assert_eq!("b.one", res.entries[1].meta.app_path());
+        assert_eq!("a.two", res.entries[2].meta.app_path());
+        assert_eq!(b"aaaa", res.entries[0].data.as_ref().unwrap().as_ref());
+        assert_eq!(b"bb", res.entries[1].data.as_ref().unwrap().as_ref());
+        assert!(res.entries[2].data.is_none());
+
+        // overlapping prefixes do not duplicate entries
+        let res = server
+            .obj_query(
+                admin,
+                "test".into(),
+                ObjQuery {
+                    prefixes: vec!["a".into(), "a.".into(), "a.one".into()],
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        assert!(!res.truncated);
+        assert_eq!(2, res.entries.len());
+        assert!(res.entries.iter().all(|e| e.data.is_none()));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn read_only_mode_rejects_mutations() {
+        let rth = RuntimeHandle::default();
+        rth.set_obj(
+            crate::obj::obj_file::ObjFile::create(None).await.unwrap(),
+        );
+        rth.set_js(crate::js::JsExecDefault::create());
+        rth.set_msg(crate::msg::MsgMem::create());
+        let server = Server::new(rth).await.unwrap();
+        let admin: Arc<str> = "test-admin".into();
+        server.set_sys_admin(vec![admin.clone()]).await.unwrap();
+
+        // seed a context while still writable
+        server
+            .ctx_setup_put(
+                admin.clone(),
+                CtxSetup {
+                    ctx: "docs".into(),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        server
+            .ctx_config_put(
+                admin.clone(),
+                CtxConfig {
+                    ctx: "docs".into(),
+                    code: "
+async function vm(req) {
+    if (req.type === 'fnReq') {
+        if (req.path === '/write') {
+            await VM.objPut({
+                meta: 'c/docs/sneak/0/0',
+                data: new Uint8Array([1]),
+            });
+        }
+        return { type: 'fnResOk' };
+    }
+    if (req.type === 'objCheckReq') {
+        return { type: 'objCheckResOk' };
+    }
+    throw new Error('unhandled');
+}
+"
+                    .into(),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        server
+            .obj_put(
+                admin.clone(),
+                crate::obj::ObjMeta("c/docs/seed/0/0".into()),
+                bytes::Bytes::from_static(b"data"),
+                None,
+            )
+            .await
+            .unwrap();
+
+        server.set_read_only(true);
+
+        // even sysadmin mutations are refused
+        let err = server
+            .ctx_setup_put(
+                admin.clone(),
+                CtxSetup {
+                    ctx: "other".into(),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(std::io::ErrorKind::PermissionDenied, err.kind());
+        let err = server
+            .ctx_config_put(
+                admin.clone(),
+                CtxConfig {
+                    ctx: "docs".into(),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(std::io::ErrorKind::PermissionDenied, err.kind());
+        let err = server
+            .obj_put(
+                admin.clone(),
+                crate::obj::ObjMeta("c/docs/more/0/0".into()),
+                bytes::Bytes::from_static(b"data"),
+                None,
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(std::io::ErrorKind::PermissionDenied, err.kind());
+
+        let fn_req = |method: &str, path: &str| crate::js::JsRequest::FnReq {
+            method: method.into(),
+            path: path.into(),
+            body: None,
+            headers: HashMap::new(),
+            body_json: None,
+            trace_id: None,
+            deadline_ms: None,
+            parts: None,
+        };
+
+        // all mutating function methods are refused before user code
+        // runs
+        for method in ["PUT", "POST", "DELETE"] {
+            let err = server
+                .fn_req("docs".into(), fn_req(method, "/"))
+                .await
+                .unwrap_err();
+            assert_eq!(std::io::ErrorKind::PermissionDenied, err.kind());
+        }
+
+        // reads and GET functions keep serving
+        let res = server
+            .fn_req("docs".into(), fn_req("GET", "/"))
+            .await
+            .unwrap();
+        assert!(matches!(res, crate::js::JsResponse::FnResOk { .. }));
+        server
+            .obj_get(admin.clone(), "docs".into(), "seed".into())
+            .await
+            .unwrap();
+
+        // a GET function cannot write around the route gate: objPut
+        // is refused at the op
+        let err = server
+            .fn_req("docs".into(), fn_req("GET", "/write"))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("read-only"));
+
+        // and the posture is reversible
+        server.set_read_only(false);
+        server
+            .obj_put(
+                admin,
+                crate::obj::ObjMeta("c/docs/more/0/0".into()),
+                bytes::Bytes::from_static(b"data"),
+                None,
+            )
+            .await
+            .unwrap();
+        let res = server
+            .fn_req("docs".into(), fn_req("GET", "/write"))
+            .await
+            .unwrap();
+        assert!(matches!(res, crate::js::JsResponse::FnResOk { .. }));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn config_validation_rejects_broken_code() {
+        let rth = RuntimeHandle::default();
+        rth.set_obj(
+            crate::obj::obj_file::ObjFile::create(None).await.unwrap(),
+        );
+        rth.set_js(crate::js::JsExecDefault::create());
+        rth.set_msg(crate::msg::MsgMem::create());
+        let server = Server::new(rth).await.unwrap();
+        let admin: Arc<str> = "test-admin".into();
+        server.set_sys_admin(vec![admin.clone()]).await.unwrap();
+        server
+            .ctx_setup_put(
+                admin.clone(),
+                CtxSetup {
+                    ctx: "test".into(),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+
+        const GOOD: &str = "
+async function vm(req) {
+    if (req.type === 'fnReq') {
+        return {
+            type: 'fnResOk',
+            body: new TextEncoder().encode('good'),
+        };
+    }
+    throw new Error('unhandled');
+}
+";
+
+        server
+            .ctx_config_put(
+                admin.clone(),
+                CtxConfig {
+                    ctx: "test".into(),
+                    code: GOOD.into(),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+
+        let fn_req = || crate::js::JsRequest::FnReq {
+            method: "GET".into(),
+            path: "/".into(),
+            body: None,
+            headers: HashMap::new(),
+            body_json: None,
+            trace_id: None,
+            deadline_ms: None,
+            parts: None,
+        };
+
+        // a syntax error is rejected at config time
+        let err = server
+            .ctx_config_put(
+                admin.clone(),
+                CtxConfig {
+                    ctx: "test".into(),
+                    code: "async function vm(req {".into(),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(std::io::ErrorKind::InvalidInput, err.kind());
+
+        // code without a callable vm export is rejected
+        let err = server
+            .ctx_config_put(
+                admin.clone(),
+                CtxConfig {
+                    ctx: "test".into(),
+                    code: "const vmish = 1;".into(),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(std::io::ErrorKind::InvalidInput, err.kind());
+
+        // the previous code is still in place after the rejections
+        let res = server
+            .fn_req("test".into(), fn_req())
+            .await
+            .unwrap();
+        match res {
+            crate::js::JsResponse::FnResOk { body, .. } => {
+                assert_eq!(b"good", body.as_ref());
+            }
+            res => panic!("unexpected response: {res:?}"),
+        }
+
+        // opting out of validation commits the code unchecked
+        server
+            .ctx_config_put(
+                admin.clone(),
+                CtxConfig {
+                    ctx: "test".into(),
+                    code: "const vmish = 1;".into(),
+                    validate: false,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        assert!(server.fn_req("test".into(), fn_req()).await.is_err());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn config_put_rejects_oversized_code() {
+        let rth = RuntimeHandle::default();
+        rth.set_obj(
+            crate::obj::obj_file::ObjFile::create(None).await.unwrap(),
+        );
+        rth.set_js(crate::js::JsExecDefault::create());
+        rth.set_msg(crate::msg::MsgMem::create());
+        let server = Server::new(rth).await.unwrap();
+        let admin: Arc<str> = "test-admin".into();
+        server.set_sys_admin(vec![admin.clone()]).await.unwrap();
+        server
+            .ctx_setup_put(
+                admin.clone(),
+                CtxSetup {
+                    ctx: "test".into(),
+                    max_code_bytes: Some(256),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+
+        const GOOD: &str = "
+async function vm(req) {
+    if (req.type === 'fnReq') {
+        return {
+            type: 'fnResOk',
+            body: new TextEncoder().encode('small'),
+        };
+    }
+    throw new Error('unhandled');
+}
+";
+
+        // code within the limit is accepted
+        server
+            .ctx_config_put(
+                admin.clone(),
+                CtxConfig {
+                    ctx: "test".into(),
+                    code: GOOD.into(),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+
+        // code over the limit is rejected with 413, old code in place
+        let err = server
+            .ctx_config_put(
+                admin.clone(),
+                CtxConfig {
+                    ctx: "test".into(),
+                    code: format!("// {}\n{GOOD}", "x".repeat(256))
+                        .into(),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(std::io::ErrorKind::FileTooLarge, err.kind());
+
+        // module sources count toward the limit too
+        let mut modules = std::collections::BTreeMap::new();
+        modules.insert(
+            Arc::from("main.js"),
+            Arc::from(format!("// {}\nexport {{}};", "x".repeat(256))),
+        );
+        let err = server
+            .ctx_config_put(
+                admin.clone(),
+                CtxConfig {
+                    ctx: "test".into(),
+                    code_modules: Arc::new(modules),
+                    code_entry: "main.js".into(),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(std::io::ErrorKind::FileTooLarge, err.kind());
+
+        let res = server
+            .fn_req(
+                "test".into(),
+                crate::js::JsRequest::FnReq {
+                    method: "GET".into(),
+                    path: "/".into(),
+                    body: None,
+                    headers: HashMap::new(),
+                    body_json: None,
+                    trace_id: None,
+                    deadline_ms: None,
+                    parts: None,
+                },
+            )
+            .await
+            .unwrap();
+        match res {
+            crate::js::JsResponse::FnResOk { body, .. } => {
+                assert_eq!(b"small", body.as_ref());
+            }
+            res => panic!("unexpected response: {res:?}"),
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn ctx_admin_add_remove() {
+        let rth = RuntimeHandle::default();
+        rth.set_obj(
+            crate::obj::obj_file::ObjFile::create(None).await.unwrap(),
+        );
+        rth.set_js(crate::js::JsExecDefault::create());
+        rth.set_msg(crate::msg::MsgMem::create());
+        let server = Server::new(rth).await.unwrap();
+        let admin: Arc<str> = "test-admin".into();
+        server.set_sys_admin(vec![admin.clone()]).await.unwrap();
+        server
+            .ctx_setup_put(
+                admin.clone(),
+                CtxSetup {
+                    ctx: "test".into(),
+                    ctx_admin: vec!["a".into()],
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+
+        // non-sysadmin tokens are rejected
+        assert!(
+            server
+                .ctx_admin_add("a".into(), "test".into(), "b".into())
+                .await
+                .is_err()
+        );
+
+        // add is idempotent and returns the resulting list
+        let list = server
+            .ctx_admin_add(admin.clone(), "test".into(), "b".into())
+            .await
+            .unwrap();
+        assert_eq!(vec![Arc::from("a"), Arc::from("b")], list);
+        let list = server
+            .ctx_admin_add(admin.clone(), "test".into(), "b".into())
+            .await
+            .unwrap();
+        assert_eq!(2, list.len());
+
+        // the new token is honored immediately
+        server
+            .ctx_status("b".into(), "test".into())
+            .unwrap();
+
+        // remove drops the token and revokes access
+        let list = server
+            .ctx_admin_remove(admin.clone(), "test".into(), "a".into())
+            .await
+            .unwrap();
+        assert_eq!(vec![Arc::from("b")], list);
+        assert!(server.ctx_status("a".into(), "test".into()).is_err());
+
+        // unknown contexts error rather than being created
+        assert!(
+            server
+                .ctx_admin_add(admin, "nope".into(), "b".into())
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn ctx_tokens_rotation() {
+        let rth = RuntimeHandle::default();
+        rth.set_obj(
+            crate::obj::obj_file::ObjFile::create(None).await.unwrap(),
+        );
+        rth.set_js(crate::js::JsExecDefault::create());
+        rth.set_msg(crate::msg::MsgMem::create());
+        let server = Arc::new(Server::new(rth).await.unwrap());
+        let admin: Arc<str> = "test-admin".into();
+        server.set_sys_admin(vec![admin.clone()]).await.unwrap();
+        server
+            .ctx_setup_put(
+                admin.clone(),
+                CtxSetup {
+                    ctx: "test".into(),
+                    ctx_admin: vec!["a".into()],
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+
+        // a ctxadmin can rotate without sysadmin permissions
+        let list = server
+            .ctx_tokens(
+                "a".into(),
+                "test".into(),
+                CtxTokens {
+                    add: vec!["b".into()],
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(2, list.len());
+
+        // removal is honored by permission checks immediately
+        let list = server
+            .ctx_tokens(
+                "b".into(),
+                "test".into(),
+                CtxTokens {
+                    remove: vec!["a".into()],
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(vec![Arc::from("b")], list);
+        assert!(server.ctx_status("a".into(), "test".into()).is_err());
+        server.ctx_status("b".into(), "test".into()).unwrap();
+
+        // removing the last remaining token requires sysadmin
+        assert!(
+            server
+                .ctx_tokens(
+                    "b".into(),
+                    "test".into(),
+                    CtxTokens {
+                        remove: vec!["b".into()],
+                        ..Default::default()
+                    },
+                )
+                .await
+                .is_err()
+        );
+        // but a sysadmin can swap it out in a single call
+        let list = server
+            .ctx_tokens(
+                admin.clone(),
+                "test".into(),
+                CtxTokens {
+                    add: vec!["c".into()],
+                    remove: vec!["b".into()],
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(vec![Arc::from("c")], list);
+
+        // two rotations racing under the lock both land
+        let s1 = server.clone();
+        let t1 = tokio::task::spawn(async move {
+            s1.ctx_tokens(
+                "c".into(),
+                "test".into(),
+                CtxTokens {
+                    add: vec!["d".into()],
+                    ..Default::default()
+                },
+            )
+            .await
+        });
+        let s2 = server.clone();
+        let t2 = tokio::task::spawn(async move {
+            s2.ctx_tokens(
+                "c".into(),
+                "test".into(),
+                CtxTokens {
+                    add: vec!["e".into()],
+                    ..Default::default()
+                },
+            )
+            .await
+        });
+        t1.await.unwrap().unwrap();
+        t2.await.unwrap().unwrap();
+        server.ctx_status("d".into(), "test".into()).unwrap();
+        server.ctx_status("e".into(), "test".into()).unwrap();
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn ctx_put_partial_update() {
+        let rth = RuntimeHandle::default();
+        rth.set_obj(
+            crate::obj::obj_file::ObjFile::create(None).await.unwrap(),
+        );
+        rth.set_js(crate::js::JsExecDefault::create());
+        rth.set_msg(crate::msg::MsgMem::create());
+        let server = Server::new(rth).await.unwrap();
+        let admin: Arc<str> = "test-admin".into();
+        server.set_sys_admin(vec![admin.clone()]).await.unwrap();
+        server
+            .ctx_setup_put(
+                admin.clone(),
+                CtxSetup {
+                    ctx: "test".into(),
+                    ctx_admin: vec!["a".into()],
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        server
+            .ctx_config_put(
+                admin.clone(),
+                CtxConfig {
+                    ctx: "test".into(),
+                    ctx_admin: vec!["b".into()],
+                    code: "async function vm(req) {
+                        return {
+                            type: 'fnResOk',
+                            body: new TextEncoder().encode('one'),
+                        };
+                    }"
+                    .into(),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+
+        let fn_req = || crate::js::JsRequest::FnReq {
+            method: "GET".into(),
+            path: "/".into(),
+            body: None,
+            headers: HashMap::new(),
+            body_json: None,
+            trace_id: None,
+            deadline_ms: None,
+            parts: None,
+        };
+
+        // a ctxadmin rotates its own token list without touching code
+        server
+            .ctx_put(
+                "b".into(),
+                CtxPut {
+                    ctx: "test".into(),
+                    ctx_admin: Some(vec!["c".into()]),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        server.ctx_status("c".into(), "test".into()).unwrap();
+        assert!(server.ctx_status("b".into(), "test".into()).is_err());
+        let res = server.fn_req("test".into(), fn_req()).await.unwrap();
+        match res {
+            crate::js::JsResponse::FnResOk { body, .. } => {
+                assert_eq!(b"one", body.as_ref());
+            }
+            res => panic!("unexpected response: {res:?}"),
+        }
+
+        // null ctx_admin leaves tokens unchanged while code updates
+        server
+            .ctx_put(
+                "c".into(),
+                CtxPut {
+                    ctx: "test".into(),
+                    code: Some(
+                        "async function vm(req) {
+                            return {
+                                type: 'fnResOk',
+                                body: new TextEncoder().encode('two'),
+                            };
+                        }"
+                        .into(),
+                    ),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        server.ctx_status("c".into(), "test".into()).unwrap();
+        let res = server.fn_req("test".into(), fn_req()).await.unwrap();
+        match res {
+            crate::js::JsResponse::FnResOk { body, .. } => {
+                assert_eq!(b"two", body.as_ref());
+            }
+            res => panic!("unexpected response: {res:?}"),
+        }
+
+        // broken replacement code is rejected with the old code kept
+        let err = server
+            .ctx_put(
+                "c".into(),
+                CtxPut {
+                    ctx: "test".into(),
+                    code: Some("async function vm(req {".into()),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(std::io::ErrorKind::InvalidInput, err.kind());
+
+        // setup-side fields are rejected for a plain ctxadmin
+        let err = server
+            .ctx_put(
+                "c".into(),
+                CtxPut {
+                    ctx: "test".into(),
+                    timeout_secs: Some(5.0),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(std::io::ErrorKind::PermissionDenied, err.kind());
+        let err = server
+            .ctx_put(
+                "c".into(),
+                CtxPut {
+                    ctx: "test".into(),
+                    delete: true,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(std::io::ErrorKind::PermissionDenied, err.kind());
+
+        // an empty array clears the config token list; the setup-side
+        // token still grants access
+        server
+            .ctx_put(
+                admin.clone(),
+                CtxPut {
+                    ctx: "test".into(),
+                    ctx_admin: Some(Vec::new()),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        assert!(server.ctx_status("c".into(), "test".into()).is_err());
+        server.ctx_status("a".into(), "test".into()).unwrap();
+
+        // unknown contexts error rather than being created
+        assert!(
+            server
+                .ctx_put(
+                    admin.clone(),
+                    CtxPut {
+                        ctx: "nope".into(),
+                        ..Default::default()
+                    },
+                )
+                .await
+                .is_err()
+        );
+
+        // delete removes the context entirely (sysadmin only)
+        server
+            .ctx_put(
+                admin,
+                CtxPut {
+                    ctx: "test".into(),
+                    delete: true,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        assert!(server.ctx_status("a".into(), "test".into()).is_err());
+        assert!(server.fn_req("test".into(), fn_req()).await.is_err());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn ctx_revalidate_reports_failures() {
+        let rth = RuntimeHandle::default();
+        rth.set_obj(
+            crate::obj::obj_file::ObjFile::create(None).await.unwrap(),
+        );
+        rth.set_js(crate::js::JsExecDefault::create());
+        rth.set_msg(crate::msg::MsgMem::create());
+        let server = Server::new(rth).await.unwrap();
+        let admin: Arc<str> = "test-admin".into();
+        server.set_sys_admin(vec![admin.clone()]).await.unwrap();
+        server
+            .ctx_setup_put(
+                admin.clone(),
+                CtxSetup {
+                    ctx: "test".into(),
+                    ctx_admin: vec!["a".into()],
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        server
+            .ctx_config_put(
+                admin,
+                CtxConfig {
+                    ctx: "test".into(),
+                    code: "
+async function vm(req) {
+    if (req.type === 'objCheckReq') {
+        if (new TextDecoder().decode(req.data).includes('bad')) {
+            throw new Error('bad marker');
+        }
+        return { type: 'objCheckResOk' };
+    }
+    throw new Error('unhandled');
+}
+"
+                    .into(),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+
+        let obj = server.runtime.runtime().obj().unwrap();
+
+        // a passing object, stored through the normal checked path
+        server
+            .obj_put(
+                "a".into(),
+                crate::obj::ObjMeta("c/test/good/0/0/0".into()),
+                bytes::Bytes::from_static(b"fine"),
+                None,
+            )
+            .await
+            .unwrap();
+
+        // a failing object, stored directly to bypass the obj check
+        let data = bytes::Bytes::from_static(b"bad data");
+        let bad = crate::obj::ObjMeta::new_context(
+            "test",
+            "naughty",
+            safe_now(),
+            0.0,
+            data.len() as f64,
+        );
+        obj.put(bad.clone(), data).await.unwrap();
+
+        // non-admin tokens are rejected
+        assert!(
+            server
+                .ctx_revalidate(
+                    "nope".into(),
+                    "test".into(),
+                    "".into(),
+                    0.0,
+                    100,
+                    false,
+                )
+                .await
+                .is_err()
+        );
+
+        // the full pass reports the single failure
+        let report = server
+            .ctx_revalidate(
+                "a".into(),
+                "test".into(),
+                "".into(),
+                0.0,
+                100,
+                false,
+            )
+            .await
+            .unwrap();
+        assert_eq!(2, report.checked);
+        assert_eq!(1, report.failures.len());
+        assert_eq!("naughty", report.failures[0].meta.app_path());
+        assert!(report.failures[0].error.contains("bad marker"));
+        assert!(report.continuation.is_none());
+
+        // a full page yields a continuation watermark for resuming
+        let report = server
+            .ctx_revalidate(
+                "a".into(),
+                "test".into(),
+                "".into(),
+                0.0,
+                1,
+                false,
+            )
+            .await
+            .unwrap();
+        assert_eq!(1, report.checked);
+        assert!(report.continuation.is_some());
+
+        // delete_failures removes the failing object
+        let report = server
+            .ctx_revalidate(
+                "a".into(),
+                "test".into(),
+                "".into(),
+                0.0,
+                100,
+                true,
+            )
+            .await
+            .unwrap();
+        assert_eq!(1, report.failures.len());
+        assert!(obj.get(bad).await.is_err());
+        let report2 = server
+            .ctx_revalidate(
+                "a".into(),
+                "test".into(),
+                "".into(),
+                0.0,
+                100,
+                false,
+            )
+            .await
+            .unwrap();
+        assert_eq!(report.checked - 1, report2.checked);
+        assert!(report2.failures.is_empty());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn signed_obj_url_access() {
+        let rth = RuntimeHandle::default();
+        rth.set_obj(
+            crate::obj::obj_file::ObjFile::create(None).await.unwrap(),
+        );
+        rth.set_js(crate::js::JsExecDefault::create());
+        rth.set_msg(crate::msg::MsgMem::create());
+        let server = Server::new(rth).await.unwrap();
+        let admin: Arc<str> = "test-admin".into();
+        server.set_sys_admin(vec![admin.clone()]).await.unwrap();
+        server
+            .ctx_setup_put(
+                admin.clone(),
+                CtxSetup {
+                    ctx: "test".into(),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+
+        // store an object directly, bypassing the obj check
+        let data = bytes::Bytes::from_static(b"hello");
+        let meta = crate::obj::ObjMeta::new_context(
+            "test",
+            "a",
+            safe_now(),
+            0.0,
+            data.len() as f64,
+        );
+        server
+            .runtime
+            .runtime()
+            .obj()
+            .unwrap()
+            .put(meta, data)
+            .await
+            .unwrap();
+
+        // non-admin tokens cannot sign
+        assert!(server
+            .sign_obj_url(
+                "nope".into(),
+                "test".into(),
+                "a".into(),
+                safe_now() + 10.0,
+            )
+            .is_err());
+
+        let query = server
+            .sign_obj_url(
+                admin.clone(),
+                "test".into(),
+                "a".into(),
+                safe_now() + 10.0,
+            )
+            .unwrap();
+        let query = query.strip_prefix("?exp=").unwrap();
+        let (exp, sig) = query.split_once("&sig=").unwrap();
+
+        // a valid signature serves the object without a token
+        let (_, got) = server
+            .obj_get_signed("test".into(), "a".into(), exp, sig)
+            .await
+            .unwrap();
+        assert_eq!(b"hello", got.as_ref());
+
+        // tampered signature rejected
+        assert!(server
+            .obj_get_signed("test".into(), "a".into(), exp, "AAAA")
+            .await
+            .is_err());
+
+        // the signature does not transfer to another path
+        assert!(server
+            .obj_get_signed("test".into(), "b".into(), exp, sig)
+            .await
+            .is_err());
+
+        // an expired grant is rejected even with a valid signature
+        let query = server
+            .sign_obj_url(admin, "test".into(), "a".into(), safe_now() - 10.0)
+            .unwrap();
+        let query = query.strip_prefix("?exp=").unwrap();
+        let (exp, sig) = query.split_once("&sig=").unwrap();
+        assert!(server
+            .obj_get_signed("test".into(), "a".into(), exp, sig)
+            .await
+            .is_err());
+    }
+
+    #[test]
+    fn filter_fn_headers_rules() {
+        let mut headers = HashMap::new();
+        headers.insert("Cookie".into(), "secret=1".into());
+        headers.insert("authorization".into(), "Bearer tok".into());
+        headers.insert("x-keep".into(), "yes".into());
+        headers.insert("x-internal".into(), "proxy".into());
+        headers.insert("x-huge".into(), "v".repeat(HDR_MAX_VALUE_BYTES + 1));
+
+        // default config: cookie stripped, oversized dropped,
+        // everything else forwarded lowercased
+        let out = filter_fn_headers(
+            "test",
+            &CtxConfig::default(),
+            headers.clone(),
+        );
+        assert!(!out.contains_key("cookie"));
+        assert!(!out.contains_key("x-huge"));
+        assert_eq!("Bearer tok", out.get("authorization").unwrap());
+        assert_eq!("yes", out.get("x-keep").unwrap());
+        assert_eq!("proxy", out.get("x-internal").unwrap());
+
+        // per-context denylist
+        let out = filter_fn_headers(
+            "test",
+            &CtxConfig {
+                hdr_deny: vec!["X-Internal".into()],
+                ..Default::default()
+            },
+            headers.clone(),
+        );
+        assert!(!out.contains_key("x-internal"));
+        assert_eq!("yes", out.get("x-keep").unwrap());
+
+        // per-context allowlist
+        let out = filter_fn_headers(
+            "test",
+            &CtxConfig {
+                hdr_allow: vec!["x-keep".into()],
+                ..Default::default()
+            },
+            headers,
+        );
+        assert_eq!(1, out.len());
+        assert_eq!("yes", out.get("x-keep").unwrap());
+    }
+
+    #[test]
+    fn glob_match_basic() {
+        assert!(glob_match("api/*", "api/users"));
+        assert!(glob_match("api/*", "api/"));
+        assert!(glob_match("*", "anything/at/all"));
+        assert!(glob_match("api/*/get", "api/users/get"));
+        assert!(!glob_match("api/*", "admin/users"));
+        assert!(!glob_match("api", "api/users"));
+        assert!(glob_match("api", "api"));
+
+        // multiple stars, including a long non-matching path, which
+        // must stay linear rather than backtracking per star
+        assert!(glob_match("a*b*c", "axxbyyc"));
+        assert!(!glob_match("a*b*c", "axxbyy"));
+        assert!(glob_match("*/*/*/*/*", "a/b/c/d/e"));
+        assert!(!glob_match("*/*/*/*/*", &"a".repeat(4096)));
+    }
+}
@@ -0,0 +1,453 @@
+//! WASM logic execution.
+//!
+//! [WasmExec] is a [crate::js::JsExec] wrapper: when a setup carries a
+//! wasm module ([crate::js::JsSetup::wasm]), requests are dispatched to
+//! it through wasmtime, otherwise they fall through to the wrapped
+//! executor. This lets logic be written in any language that compiles
+//! to wasm instead of javascript, while reusing the existing executor
+//! plumbing, timeouts, and heap limits.
+//!
+//! The guest contract:
+//!
+//! - export a linear `memory`
+//! - export `vm_alloc(len: i32) -> i32` returning a pointer to `len`
+//!   writable bytes
+//! - export `vm_handle(ptr: i32, len: i32) -> (i32, i32)` taking a
+//!   msgpack-encoded [crate::js::JsRequest] and returning the pointer
+//!   and length of a msgpack-encoded [crate::js::JsResponse]
+//!
+//! The host exposes a single import `vm.host(ptr, len) -> i64` taking
+//! a msgpack-encoded op envelope (tagged with `op`: `objPut`,
+//! `objGet`, or `objList`, mirroring the deno op inputs) and returning
+//! the guest pointer and length of the msgpack result packed as
+//! `(ptr << 32) | len`. Errors are returned as a `{ "err": string }`
+//! map.
+//!
+//! Execution is interrupted via epoch preemption when
+//! [crate::js::JsSetup::timeout] elapses, and guest memory growth is
+//! capped at [crate::js::JsSetup::heap_size].
+
+use crate::bytes_ext::BytesExt;
+use crate::js::{DynJsExec, JsExec, JsRequest, JsResponse, JsSetup};
+use crate::*;
+use bytes::Bytes;
+use std::sync::Arc;
+
+/// Check whether `wasm` is a valid WebAssembly binary, without
+/// instantiating it.
+pub fn validate(wasm: &[u8]) -> bool {
+    let engine = wasmtime::Engine::default();
+    wasmtime::Module::validate(&engine, wasm).is_ok()
+}
+
+/// Wasm executor wrapper. See the module docs for the guest contract.
+pub struct WasmExec(DynJsExec);
+
+impl WasmExec {
+    /// Create a WasmExec wrapper around another executor. Setups
+    /// without a wasm module are passed through to `inner`.
+    pub fn create(inner: DynJsExec) -> DynJsExec {
+        let out: DynJsExec = Arc::new(Self(inner));
+        out
+    }
+}
+
+impl JsExec for WasmExec {
+    fn exec(
+        &self,
+        setup: JsSetup,
+        request: JsRequest,
+    ) -> BoxFut<'_, Result<JsResponse>> {
+        Box::pin(async move {
+            let Some(wasm) = setup.wasm.clone() else {
+                return self.0.exec(setup, request).await;
+            };
+            let req = Bytes::from_encode(&request)?;
+            let handle = tokio::runtime::Handle::current();
+            tokio::task::spawn_blocking(move || {
+                run_guest(setup, wasm, req, handle)
+            })
+            .await
+            .map_err(Error::other)?
+        })
+    }
+
+    fn warm(&self, setup: JsSetup, count: usize) -> BoxFut<'_, ()> {
+        // wasm guests are instantiated per request; only the wrapped
+        // js executor pools threads worth warming
+        if setup.wasm.is_some() {
+            Box::pin(async {})
+        } else {
+            self.0.warm(setup, count)
+        }
+    }
+}
+
+struct HostState {
+    setup: JsSetup,
+    handle: tokio::runtime::Handle,
+    limits: wasmtime::StoreLimits,
+}
+
+fn run_guest(
+    setup: JsSetup,
+    wasm: Bytes,
+    req: Bytes,
+    handle: tokio::runtime::Handle,
+) -> Result<JsResponse> {
+    let timeout = setup.timeout;
+    let heap_size = setup.heap_size;
+
+    let mut config = wasmtime::Config::new();
+    config.epoch_interruption(true);
+    let engine = wasmtime::Engine::new(&config).map_err(Error::other)?;
+
+    // tick the epoch once the timeout elapses, interrupting the guest
+    {
+        let engine = engine.clone();
+        std::thread::spawn(move || {
+            std::thread::sleep(timeout);
+            engine.increment_epoch();
+        });
+    }
+
+    let module = wasmtime::Module::new(&engine, &wasm).map_err(Error::other)?;
+
+    let limits = wasmtime::StoreLimitsBuilder::new()
+        .memory_size(heap_size)
+        .build();
+    let mut store = wasmtime::Store::new(
+        &engine,
+        HostState {
+            setup,
+            handle,
+            limits,
+        },
+    );
+    store.limiter(|state| &mut state.limits);
+    store.set_epoch_deadline(1);
+
+    let mut linker = wasmtime::Linker::new(&engine);
+    linker
+        .func_wrap("vm", "host", host_op)
+        .map_err(Error::other)?;
+
+    let instance = linker
+        .instantiate(&mut store, &module)
+        .map_err(Error::other)?;
+
+    let memory = instance
+        .get_memory(&mut store, "memory")
+        .ok_or_else(|| Error::other("wasm module must export memory"))?;
+
+    let alloc = instance
+        .get_typed_func::<i32, i32>(&mut store, "vm_alloc")
+        .map_err(Error::other)?;
+    let ptr = alloc
+        .call(&mut store, req.len() as i32)
+        .map_err(Error::other)?;
+    memory
+        .write(&mut store, ptr as usize, &req)
+        .map_err(Error::other)?;
+
+    let vm_handle = instance
+        .get_typed_func::<(i32, i32), (i32, i32)>(&mut store, "vm_handle")
+        .map_err(Error::other)?;
+    let (res_ptr, res_len) = vm_handle
+        .call(&mut store, (ptr, req.len() as i32))
+        .map_err(|err| {
+            if err.is::<wasmtime::Trap>() {
+                Error::timeout(format!("wasm exec interrupted: {err}"))
+            } else {
+                Error::other(err)
+            }
+        })?;
+
+    let mut out = vec![0_u8; res_len as usize];
+    memory
+        .read(&store, res_ptr as usize, &mut out)
+        .map_err(Error::other)?;
+    Bytes::from(out).to_decode()
+}
+
+fn f64_1000() -> f64 {
+    1000.0
+}
+
+/// Host op envelope, mirroring the deno op inputs.
+#[derive(Debug, serde::Deserialize)]
+#[serde(tag = "op", rename_all = "camelCase", rename_all_fields = "camelCase")]
+enum HostOp {
+    ObjPut {
+        #[serde(default)]
+        meta: Arc<str>,
+        #[serde(default)]
+        data: Bytes,
+        #[serde(default)]
+        expires_secs: Option<f64>,
+        #[serde(default)]
+        ttl_secs: Option<f64>,
+    },
+    ObjGet {
+        #[serde(default)]
+        meta: Arc<str>,
+    },
+    ObjList {
+        #[serde(default)]
+        app_path_prefix: Arc<str>,
+        #[serde(default)]
+        created_gt: f64,
+        #[serde(default = "f64_1000")]
+        limit: f64,
+    },
+}
+
+#[derive(Debug, serde::Serialize)]
+#[serde(untagged, rename_all = "camelCase", rename_all_fields = "camelCase")]
+enum HostOpRes {
+    Err {
+        err: String,
+    },
+    ObjPut {
+        meta: Arc<str>,
+    },
+    ObjGet {
+        meta: Arc<str>,
+        data: Bytes,
+    },
+    ObjList {
+        meta_list: Vec<crate::obj::ObjMeta>,
+    },
+}
+
+fn host_op(
+    mut caller: wasmtime::Caller<'_, HostState>,
+    ptr: i32,
+    len: i32,
+) -> i64 {
+    let res = match host_op_inner(&mut caller, ptr, len) {
+        Ok(res) => res,
+        Err(err) => HostOpRes::Err {
+            err: err.to_string(),
+        },
+    };
+    let res = match Bytes::from_encode(&res) {
+        Ok(res) => res,
+        Err(_) => return 0,
+    };
+    // hand the result back through guest-allocated memory
+    match write_guest(&mut caller, &res) {
+        Ok(packed) => packed,
+        Err(_) => 0,
+    }
+}
+
+fn write_guest(
+    caller: &mut wasmtime::Caller<'_, HostState>,
+    data: &[u8],
+) -> Result<i64> {
+    let alloc = caller
+        .get_export("vm_alloc")
+        .and_then(|e| e.into_func())
+        .ok_or_else(|| Error::other("wasm module must export vm_alloc"))?
+        .typed::<i32, i32>(&mut *caller)
+        .map_err(Error::other)?;
+    let ptr = alloc
+        .call(&mut *caller, data.len() as i32)
+        .map_err(Error::other)?;
+    let memory = caller
+        .get_export("memory")
+        .and_then(|e| e.into_memory())
+        .ok_or_else(|| Error::other("wasm module must export memory"))?;
+    memory
+        .write(&mut *caller, ptr as usize, data)
+        .map_err(Error::other)?;
+    Ok(((ptr as i64) << 32) | (data.len() as i64))
+}
+
+fn host_op_inner(
+    caller: &mut wasmtime::Caller<'_, HostState>,
+    ptr: i32,
+    len: i32,
+) -> Result<HostOpRes> {
+    let memory = caller
+        .get_export("memory")
+        .and_then(|e| e.into_memory())
+        .ok_or_else(|| Error::other("wasm module must export memory"))?;
+    let mut input = vec![0_u8; len as usize];
+    memory
+        .read(&mut *caller, ptr as usize, &mut input)
+        .map_err(Error::other)?;
+    let input: HostOp = Bytes::from(input).to_decode()?;
+
+    let state = caller.data();
+    let setup = state.setup.clone();
+    let handle = state.handle.clone();
+
+    match input {
+        HostOp::ObjPut {
+            meta,
+            data,
+            expires_secs,
+            ttl_secs,
+        } => {
+            let input_meta = crate::obj::ObjMeta(meta);
+
+            let now = safe_now();
+            let expires_secs = match (expires_secs, ttl_secs) {
+                (Some(abs), _) => abs,
+                (None, Some(ttl)) => now + ttl,
+                (None, None) => input_meta.expires_secs(),
+            };
+
+            // unlike the js op there is no obj check round trip here:
+            // for wasm logic the module itself is the validator
+            let meta = crate::obj::ObjMeta::new_context(
+                &setup.ctx,
+                input_meta.app_path(),
+                now,
+                expires_secs,
+                data.len() as f64,
+            );
+
+            handle.block_on(setup.runtime.obj()?.put(meta.clone(), data))?;
+
+            Ok(HostOpRes::ObjPut { meta: meta.0 })
+        }
+        HostOp::ObjGet { meta } => {
+            let meta = crate::obj::ObjMeta(meta);
+            if meta.sys_prefix() != crate::obj::ObjMeta::SYS_CTX {
+                return Err(Error::other("invalid sys prefix"));
+            }
+            if meta.ctx() != &*setup.ctx {
+                return Err(Error::other("invalid sys context"));
+            }
+            let (meta, data) =
+                handle.block_on(setup.runtime.obj()?.get(meta))?;
+            Ok(HostOpRes::ObjGet { meta: meta.0, data })
+        }
+        HostOp::ObjList {
+            app_path_prefix,
+            created_gt,
+            limit,
+        } => {
+            let path = format!(
+                "{}/{}/{}",
+                crate::obj::ObjMeta::SYS_CTX,
+                setup.ctx,
+                app_path_prefix,
+            );
+            let limit = limit.clamp(0.0, 1000.0) as u32;
+            let meta_list = handle.block_on(setup.runtime.obj()?.list(
+                &path,
+                created_gt,
+                limit,
+            ))?;
+            Ok(HostOpRes::ObjList { meta_list })
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::js::JsExecDefault;
+
+    fn setup(wasm: &'static [u8], timeout: std::time::Duration) -> JsSetup {
+        JsSetup {
+            runtime: RuntimeHandle::default().runtime(),
+            ctx: "test".into(),
+            env: Arc::new(serde_json::Value::Null),
+            modules: Default::default(),
+            entry: "".into(),
+            code: "".into(),
+            wasm: Some(Bytes::from_static(wasm)),
+            timeout,
+            heap_size: JsSetup::DEF_HEAP_SIZE,
+            max_code_bytes: JsSetup::DEF_MAX_CODE_BYTES,
+        }
+    }
+
+    fn fn_req() -> JsRequest {
+        JsRequest::FnReq {
+            method: "GET".into(),
+            path: "".into(),
+            body: None,
+            headers: Default::default(),
+            body_json: None,
+            trace_id: None,
+            deadline_ms: None,
+            parts: None,
+        }
+    }
+
+    // answers every request with msgpack { "type": "fnResOk" }
+    const WASM_FN_RES_OK: &[u8] = br#"(module
+  (memory (export "memory") 1)
+  (data (i32.const 0) "\81\a4type\a7fnResOk")
+  (func (export "vm_alloc") (param i32) (result i32) i32.const 4096)
+  (func (export "vm_handle") (param i32 i32) (result i32 i32)
+    i32.const 0
+    i32.const 14))"#;
+
+    // answers every request with msgpack { "type": "objCheckResOk" }
+    const WASM_OBJ_CHECK_RES_OK: &[u8] = br#"(module
+  (memory (export "memory") 1)
+  (data (i32.const 0) "\81\a4type\adobjCheckResOk")
+  (func (export "vm_alloc") (param i32) (result i32) i32.const 4096)
+  (func (export "vm_handle") (param i32 i32) (result i32 i32)
+    i32.const 0
+    i32.const 20))"#;
+
+    // never returns
+    const WASM_LOOP: &[u8] = br#"(module
+  (memory (export "memory") 1)
+  (func (export "vm_alloc") (param i32) (result i32) i32.const 4096)
+  (func (export "vm_handle") (param i32 i32) (result i32 i32)
+    (loop $l (br $l))
+    unreachable))"#;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn wasm_fn_req() {
+        let js = WasmExec::create(JsExecDefault::create());
+
+        let res = js
+            .exec(setup(WASM_FN_RES_OK, JsSetup::DEF_TIMEOUT), fn_req())
+            .await
+            .unwrap();
+        assert!(matches!(res, JsResponse::FnResOk { .. }));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn wasm_obj_check_req() {
+        let js = WasmExec::create(JsExecDefault::create());
+
+        let res = js
+            .exec(
+                setup(WASM_OBJ_CHECK_RES_OK, JsSetup::DEF_TIMEOUT),
+                JsRequest::ObjCheckReq {
+                    data: Bytes::from_static(b"hello"),
+                    meta: crate::obj::ObjMeta("c/test/a/1/0/5".into()),
+                },
+            )
+            .await
+            .unwrap();
+        assert!(matches!(res, JsResponse::ObjCheckResOk));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn wasm_timeout() {
+        let js = WasmExec::create(JsExecDefault::create());
+
+        let start = std::time::Instant::now();
+        let err = js
+            .exec(
+                setup(WASM_LOOP, std::time::Duration::from_millis(100)),
+                fn_req(),
+            )
+            .await
+            .unwrap_err();
+        println!("got expected error: {err:?}");
+        assert!(start.elapsed() < std::time::Duration::from_secs(5));
+    }
+}
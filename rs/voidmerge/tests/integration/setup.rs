@@ -139,6 +139,10 @@ impl Test {
                     path: "".into(),
                     body: Some(body),
                     headers: Default::default(),
+                    body_json: None,
+                    trace_id: None,
+                    deadline_ms: None,
+                    parts: None,
                 },
             )
             .await?;